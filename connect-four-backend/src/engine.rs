@@ -0,0 +1,247 @@
+use crate::game_query::{COMPUTER_SUFFIX, HUMAN_SUFFIX};
+use connect_four_cli::board::Player;
+use connect_four_cli::connect_four::{self, State as ConnectFourState};
+use connect_four_cli::save::{self, SavedGame, SavedVariant, SAVE_VERSION};
+use connect_four_cli::toot_otto::{self, State as TootOttoState};
+
+// Game types as the frontend names them, before who player two was
+const CONNECT_FOUR_TYPE: &str = "Connect4";
+const TOOT_OTTO_TYPE: &str = "TOOT-OTTO";
+
+// Winner name of a game nobody won, which is why no player may be called it
+pub const DRAW: &str = "Draw";
+
+pub fn check_player_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Players need a name".to_string());
+    }
+    if name.trim().eq_ignore_ascii_case(DRAW) {
+        return Err(format!("{} is not a player name", DRAW));
+    }
+    Ok(())
+}
+
+// Both names of a game, which must also tell the players apart
+pub fn check_player_names(p1: &str, p2: &str) -> Result<(), String> {
+    check_player_name(p1)?;
+    check_player_name(p2)?;
+    if p1.trim().eq_ignore_ascii_case(p2.trim()) {
+        return Err(format!("Both players are called {}", p2.trim()));
+    }
+    Ok(())
+}
+
+pub fn game_type(game: &str, with_ai: bool) -> String {
+    let name = if game == save::TOOT_OTTO {
+        TOOT_OTTO_TYPE
+    } else {
+        CONNECT_FOUR_TYPE
+    };
+    let opponent = if with_ai {
+        COMPUTER_SUFFIX
+    } else {
+        HUMAN_SUFFIX
+    };
+    format!("{}{}", name, opponent)
+}
+
+// The game of the engine and whether the computer played, from a game type
+pub fn parse_game_type(game_type: &str) -> Result<(&'static str, bool), String> {
+    let unknown = || format!("Unknown game type {}", game_type);
+    let (name, with_ai) = if let Some(name) = game_type.strip_suffix(COMPUTER_SUFFIX) {
+        (name, true)
+    } else if let Some(name) = game_type.strip_suffix(HUMAN_SUFFIX) {
+        (name, false)
+    } else {
+        return Err(unknown());
+    };
+    match name {
+        CONNECT_FOUR_TYPE => Ok((save::CONNECT_FOUR, with_ai)),
+        TOOT_OTTO_TYPE => Ok((save::TOOT_OTTO, with_ai)),
+        _ => Err(unknown()),
+    }
+}
+
+// TOOT against OTTO, the words of a TOOT-OTTO game that does not give any
+pub fn standard_variant() -> SavedVariant {
+    let variant = toot_otto::Variant::toot_otto();
+    SavedVariant {
+        p1_word: variant.word_string(Player::One),
+        p2_word: variant.word_string(Player::Two),
+        letters: variant.letters.iter().map(|letter| letter.0).collect(),
+        chips_per_letter: variant.chips_per_letter,
+    }
+}
+
+// A game of connect4 or toot-otto before the first move, between two people with
+// player one to start. The caller fills in the rest
+pub fn new_game(game: &str, rows: usize, cols: usize) -> SavedGame {
+    let toot_otto = game == save::TOOT_OTTO;
+    SavedGame {
+        version: SAVE_VERSION,
+        game: game.to_string(),
+        rows,
+        cols,
+        p1: String::new(),
+        p2: String::new(),
+        with_ai: false,
+        ai_player: 2,
+        first_player: 1,
+        max_ai_depth: 1,
+        evaluator: if toot_otto { "patterns" } else { "chains" }.to_string(),
+        moves: Vec::new(),
+        variant: if toot_otto {
+            Some(standard_variant())
+        } else {
+            None
+        },
+    }
+}
+
+// A game of the CLI engine, which has the final say on which moves are legal and who
+// won, for the sessions and the games submitted to /games
+pub enum Engine {
+    ConnectFour(connect_four::Game),
+    TootOtto(toot_otto::Game),
+}
+
+impl Engine {
+    // Rebuild the game of a save by playing its moves again
+    pub fn load(saved: &SavedGame) -> Result<Self, String> {
+        match saved.game.as_str() {
+            save::CONNECT_FOUR => saved.to_connect_four().map(Engine::ConnectFour),
+            save::TOOT_OTTO => saved.to_toot_otto().map(Engine::TootOtto),
+            x => Err(format!(
+                "game must be {} or {}, not {}",
+                save::CONNECT_FOUR,
+                save::TOOT_OTTO,
+                x
+            )),
+        }
+    }
+
+    pub fn saved(&self) -> SavedGame {
+        match self {
+            Engine::ConnectFour(game) => SavedGame::from_connect_four(game),
+            Engine::TootOtto(game) => SavedGame::from_toot_otto(game),
+        }
+    }
+
+    pub fn running(&self) -> bool {
+        match self {
+            Engine::ConnectFour(game) => game.state == ConnectFourState::Running,
+            Engine::TootOtto(game) => game.state == TootOttoState::Running,
+        }
+    }
+
+    // Name of the winner or DRAW once the game is over
+    pub fn winner(&self) -> Option<String> {
+        if self.running() {
+            return None;
+        }
+        match self {
+            Engine::ConnectFour(game) => Some(game.winner.clone()),
+            Engine::TootOtto(game) => Some(game.winner.clone()),
+        }
+    }
+
+    pub fn to_move(&self) -> Option<String> {
+        if !self.running() {
+            return None;
+        }
+        match self {
+            Engine::ConnectFour(game) => Some(game.player_name(game.current_player())),
+            Engine::TootOtto(game) => Some(game.player_name(game.current_player())),
+        }
+    }
+
+    // Rows from the top, of R, Y and _ for Connect-4 or of letters, upper case for
+    // player one, for TOOT-OTTO
+    pub fn board(&self) -> Vec<String> {
+        match self {
+            Engine::ConnectFour(game) => (0..game.grid.num_rows)
+                .map(|r| {
+                    (0..game.grid.num_cols)
+                        .map(|c| match game.grid.get(r, c) {
+                            1 => 'R',
+                            -1 => 'Y',
+                            _ => '_',
+                        })
+                        .collect()
+                })
+                .collect(),
+            Engine::TootOtto(game) => (0..game.grid.num_rows)
+                .map(|r| {
+                    (0..game.grid.num_cols)
+                        .map(|c| match game.grid.get(r, c) {
+                            Some((Player::One, chip_type)) => chip_type.0.to_ascii_uppercase(),
+                            Some((Player::Two, chip_type)) => chip_type.0.to_ascii_lowercase(),
+                            None => '_',
+                        })
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    // Play one move of the player to move, a column for Connect-4 or letter and column
+    // such as T3 for TOOT-OTTO. The error says why the move is not legal
+    pub fn play(&mut self, mv: &str) -> Result<(), String> {
+        if !self.running() {
+            return Err("The game is over".to_string());
+        }
+        match self {
+            Engine::ConnectFour(game) => {
+                let col = match connect_four::parse_moves(mv)?.as_slice() {
+                    [col] => *col,
+                    _ => return Err(format!("{} is not one move", mv)),
+                };
+                if game.make_move(col).is_err() {
+                    return Err(if col >= game.grid.num_cols {
+                        format!("Column {} is not on the board", col)
+                    } else {
+                        format!("Column {} is full", col)
+                    });
+                }
+            }
+            Engine::TootOtto(game) => {
+                let (chip_type, col) = match toot_otto::parse_moves(mv)?.as_slice() {
+                    [chip_move] => *chip_move,
+                    _ => return Err(format!("{} is not one move", mv)),
+                };
+                let chips = game.current_chips();
+                if game.make_move(chip_type, col).is_err() {
+                    return Err(if col >= game.grid.num_cols {
+                        format!("Column {} is not on the board", col)
+                    } else if chips.counts.iter().all(|(letter, _)| *letter != chip_type) {
+                        format!("{} is not a letter of this game", chip_type)
+                    } else if chips.remaining(chip_type) == 0 {
+                        format!("No {} tiles left", chip_type)
+                    } else {
+                        format!("Column {} is full", col)
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Let the computer move when it is its turn
+    pub fn ai_reply(&mut self) {
+        if !self.running() {
+            return;
+        }
+        match self {
+            Engine::ConnectFour(game) => {
+                if game.with_ai && game.current_player() == game.ai_player {
+                    let _ = game.ai_make_move();
+                }
+            }
+            Engine::TootOtto(game) => {
+                if game.with_ai && game.current_player() == game.ai_player {
+                    let _ = game.ai_make_move();
+                }
+            }
+        }
+    }
+}
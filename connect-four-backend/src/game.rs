@@ -1,5 +1,7 @@
+use crate::engine::{self, Engine};
 use chrono::serde::ts_milliseconds;
 use chrono::{DateTime, Utc};
+use connect_four_cli::save::SavedVariant;
 use mongodb::bson;
 use serde::{Deserialize, Serialize};
 
@@ -7,6 +9,8 @@ use serde::{Deserialize, Serialize};
 
 // Need to keep snake cases to be consistent with frontend expectations
 
+// Sizes are u32 as BSON has no unsigned 64 bit integers. Games stored before the moves
+// were kept have none and a board of 0 x 0
 #[allow(non_snake_case)]
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Game {
@@ -18,6 +22,16 @@ pub struct Game {
     pub WinnerName: String,
     #[serde(with = "ts_milliseconds")]
     pub GameDate: DateTime<Utc>,
+    #[serde(default)]
+    pub rows: u32,
+    #[serde(default)]
+    pub cols: u32,
+    #[serde(default)]
+    pub moves: Vec<String>,
+    #[serde(default = "first_player")]
+    pub first_player: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<SavedVariant>,
 }
 
 #[allow(non_snake_case)]
@@ -29,6 +43,16 @@ pub struct InsertableGame {
     pub WinnerName: String,
     #[serde(with = "ts_milliseconds")]
     pub GameDate: DateTime<Utc>,
+    #[serde(default)]
+    pub rows: u32,
+    #[serde(default)]
+    pub cols: u32,
+    #[serde(default)]
+    pub moves: Vec<String>,
+    #[serde(default = "first_player")]
+    pub first_player: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<SavedVariant>,
 }
 
 // Serialiazable game, designed to match conect_four_backend except without the date since you cannot get the current date in WebAssembly
@@ -39,4 +63,152 @@ pub struct SerializableGame {
     pub Player1Name: String,
     pub Player2Name: String,
     pub WinnerName: String,
+
+    // How the game went, played again by the engine before the game is stored. Moves
+    // are columns for Connect-4 and letter and column such as T3 for TOOT-OTTO, the
+    // player to move first is 1 or 2
+    pub rows: u32,
+    pub cols: u32,
+    pub moves: Vec<String>,
+    #[serde(default = "first_player")]
+    pub first_player: u8,
+
+    // Words and letters of a TOOT-OTTO game, TOOT and OTTO without them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<SavedVariant>,
+}
+
+fn first_player() -> u8 {
+    1
+}
+
+// Who a winner name says won
+fn outcome(winner: &str) -> String {
+    if winner == engine::DRAW {
+        "a draw".to_string()
+    } else {
+        format!("a win for {}", winner)
+    }
+}
+
+impl SerializableGame {
+    // Play the moves through the engine and check that they end the way the game says
+    pub fn verify(&self) -> Result<(), String> {
+        engine::check_player_names(&self.Player1Name, &self.Player2Name)?;
+        let (game, with_ai) = engine::parse_game_type(&self.gameType)?;
+        let mut saved = engine::new_game(game, self.rows as usize, self.cols as usize);
+        saved.p1 = self.Player1Name.clone();
+        saved.p2 = self.Player2Name.clone();
+        saved.with_ai = with_ai;
+        saved.first_player = self.first_player;
+        if self.variant.is_some() {
+            saved.variant = self.variant.clone();
+        }
+
+        let mut engine = Engine::load(&saved)?;
+        for (idx, mv) in self.moves.iter().enumerate() {
+            engine
+                .play(mv)
+                .map_err(|msg| format!("Move {} ({}): {}", idx + 1, mv, msg))?;
+        }
+        match engine.winner() {
+            None => Err(format!(
+                "The game is not over after {} moves",
+                self.moves.len()
+            )),
+            Some(winner) if winner != self.WinnerName => Err(format!(
+                "The moves end in {}, not {}",
+                outcome(&winner),
+                outcome(&self.WinnerName)
+            )),
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+// A stored game as it was submitted, to check it again when it is replaced
+impl From<&Game> for SerializableGame {
+    fn from(game: &Game) -> Self {
+        SerializableGame {
+            gameType: game.gameType.clone(),
+            Player1Name: game.Player1Name.clone(),
+            Player2Name: game.Player2Name.clone(),
+            WinnerName: game.WinnerName.clone(),
+            rows: game.rows,
+            cols: game.cols,
+            moves: game.moves.clone(),
+            first_player: game.first_player,
+            variant: game.variant.clone(),
+        }
+    }
+}
+
+// Games won by a player, as /wins returns them
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PlayerWins {
+    pub _id: String,
+    pub count: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A Connect-4 game between Ann and Bob, won by winner after the moves
+    fn game(moves: &[&str], winner: &str) -> SerializableGame {
+        SerializableGame {
+            gameType: "Connect4 with Human".to_string(),
+            Player1Name: "Ann".to_string(),
+            Player2Name: "Bob".to_string(),
+            WinnerName: winner.to_string(),
+            rows: 6,
+            cols: 7,
+            moves: moves.iter().map(|mv| mv.to_string()).collect(),
+            first_player: 1,
+            variant: None,
+        }
+    }
+
+    #[test]
+    fn genuine_win_is_accepted() {
+        assert_eq!(
+            game(&["0", "1", "0", "1", "0", "1", "0"], "Ann").verify(),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn wrong_winner_is_turned_down() {
+        let err = game(&["0", "1", "0", "1", "0", "1", "0"], "Bob")
+            .verify()
+            .unwrap_err();
+        assert!(err.contains("a win for Ann, not a win for Bob"), "{}", err);
+        assert!(game(&["0", "1", "0", "1", "0", "1", "0"], "Draw")
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn illegal_move_is_turned_down() {
+        let err = game(&["0", "1", "0", "1", "0", "1", "9"], "Ann")
+            .verify()
+            .unwrap_err();
+        assert!(err.starts_with("Move 7 (9)"), "{}", err);
+    }
+
+    #[test]
+    fn unfinished_game_is_turned_down() {
+        let err = game(&["0", "1", "0", "1", "0", "1"], "Ann")
+            .verify()
+            .unwrap_err();
+        assert!(err.contains("not over"), "{}", err);
+    }
+
+    #[test]
+    fn same_names_are_turned_down() {
+        let mut same = game(&["0", "1", "0", "1", "0", "1", "0"], "Ann");
+        same.Player2Name = " ANN".to_string();
+        let err = same.verify().unwrap_err();
+        assert!(err.contains("Both players are called"), "{}", err);
+    }
 }
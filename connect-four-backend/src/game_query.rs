@@ -0,0 +1,156 @@
+use crate::game::Game;
+
+// Game types end with who player two was, as the frontend posts them
+pub const COMPUTER_SUFFIX: &str = " with Computer";
+pub const HUMAN_SUFFIX: &str = " with Human";
+
+// Most games a page may hold
+pub const MAX_LIMIT: u64 = 1000;
+
+// Query string of GET /games: filters, order and page of the games to list
+#[derive(FromForm, Clone, Default, Debug)]
+pub struct GameQuery {
+    pub game_type: Option<String>,
+
+    // Games the name played in, as either player
+    pub player: Option<String>,
+    pub winner: Option<String>,
+
+    // computer or human
+    pub opponent: Option<String>,
+
+    // Dates in milliseconds since the epoch, from inclusive and to exclusive
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+
+    // oldest (the default) or newest first
+    pub sort: Option<String>,
+
+    // Pages count from 1. Without a limit every game is listed, with limit 0 none are
+    // and only the total is of use
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
+}
+
+// The games of the page asked for and how many games match the filters in all
+pub struct GamePage {
+    pub games: Vec<Game>,
+    pub total: u64,
+}
+
+impl GameQuery {
+    pub fn validate(&self) -> Result<(), String> {
+        self.opponent_suffix()?;
+        match self.sort.as_deref() {
+            None | Some("oldest") | Some("newest") => {}
+            Some(x) => return Err(format!("sort must be oldest or newest, not {}", x)),
+        }
+        match (self.page, self.limit) {
+            (Some(0), _) => return Err("page counts from 1".to_string()),
+            (Some(_), None) => return Err("page needs a limit".to_string()),
+            (_, Some(limit)) if limit > MAX_LIMIT => {
+                return Err(format!("limit must be at most {}", MAX_LIMIT))
+            }
+            _ => {}
+        }
+        match self.checked_skip() {
+            Some(_) => Ok(()),
+            None => Err("page is too far to count".to_string()),
+        }
+    }
+
+    // Suffix of the game types the opponent filter keeps
+    pub fn opponent_suffix(&self) -> Result<Option<&'static str>, String> {
+        match self.opponent.as_deref() {
+            None => Ok(None),
+            Some("computer") => Ok(Some(COMPUTER_SUFFIX)),
+            Some("human") => Ok(Some(HUMAN_SUFFIX)),
+            Some(x) => Err(format!("opponent must be computer or human, not {}", x)),
+        }
+    }
+
+    pub fn newest_first(&self) -> bool {
+        self.sort.as_deref() == Some("newest")
+    }
+
+    // Games before the page, None when they do not fit the i64 the databases take
+    fn checked_skip(&self) -> Option<u64> {
+        match (self.page, self.limit) {
+            (Some(page), Some(limit)) => page
+                .checked_sub(1)?
+                .checked_mul(limit)
+                .filter(|skip| *skip <= i64::max_value() as u64),
+            _ => Some(0),
+        }
+    }
+
+    // Games before the page of a validated query
+    pub fn skip(&self) -> u64 {
+        self.checked_skip().unwrap_or(0)
+    }
+
+    pub fn matches(&self, game: &Game) -> bool {
+        let date = game.GameDate.timestamp_millis();
+        self.game_type.iter().all(|t| game.gameType == *t)
+            && self
+                .player
+                .iter()
+                .all(|p| game.Player1Name == *p || game.Player2Name == *p)
+            && self.winner.iter().all(|w| game.WinnerName == *w)
+            && self.from.iter().all(|from| date >= *from)
+            && self.to.iter().all(|to| date < *to)
+            && match self.opponent_suffix() {
+                Ok(Some(suffix)) => game.gameType.ends_with(suffix),
+                _ => true,
+            }
+    }
+
+    // Filter, sort and cut the games of a store that keeps them in order of insertion
+    pub fn page_of(&self, games: &[Game]) -> GamePage {
+        let mut games = games
+            .iter()
+            .filter(|game| self.matches(game))
+            .cloned()
+            .collect::<Vec<_>>();
+        games.sort_by_key(|game| game.GameDate);
+        if self.newest_first() {
+            games.reverse();
+        }
+        let total = games.len() as u64;
+        let games = games
+            .into_iter()
+            .skip(self.skip() as usize)
+            .take(
+                self.limit
+                    .map_or(usize::max_value(), |limit| limit as usize),
+            )
+            .collect();
+        GamePage { games, total }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(page: u64, limit: u64) -> GameQuery {
+        GameQuery {
+            page: Some(page),
+            limit: Some(limit),
+            ..GameQuery::default()
+        }
+    }
+
+    #[test]
+    fn pages_must_be_countable() {
+        assert_eq!(page(3, 20).validate(), Ok(()));
+        assert_eq!(page(3, 20).skip(), 40);
+        assert_eq!(page(1, MAX_LIMIT).validate(), Ok(()));
+        assert!(page(1, MAX_LIMIT + 1).validate().is_err());
+        assert!(page(0, 20).validate().is_err());
+        assert!(page(u64::max_value(), MAX_LIMIT).validate().is_err());
+        assert!(page(u64::max_value() / MAX_LIMIT, MAX_LIMIT)
+            .validate()
+            .is_err());
+    }
+}
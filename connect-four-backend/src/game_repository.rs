@@ -1,13 +1,68 @@
-use crate::game::{Game, InsertableGame, SerializableGame};
-use crate::mongo_connection::Conn;
+use crate::game::{Game, InsertableGame, PlayerWins, SerializableGame};
+use crate::game_query::{GamePage, GameQuery};
+use crate::game_store::{GameStore, StoreError};
+use crate::mongo_connection::{Conn, Pool};
 use crate::r2d2_mongodb::mongodb::db::ThreadedDatabase;
 use chrono::Utc;
+use mongodb::coll::options::FindOptions;
 use mongodb::{bson, coll::results::DeleteResult, doc, error::Error, oid::ObjectId};
 
-pub fn get_all_games_handler(connection: &Conn) -> Result<Vec<Game>, Error> {
-    let cursor = connection.collection("games").find(None, None).unwrap();
+// Conditions of the query that every game listed has to meet
+fn games_filter(query: &GameQuery) -> bson::Document {
+    let mut conditions = Vec::new();
+    if let Some(game_type) = &query.game_type {
+        conditions.push(doc! {"gameType": (game_type.clone())});
+    }
+    if let Some(player) = &query.player {
+        conditions.push(doc! {
+            "$or": [{"Player1Name": (player.clone())}, {"Player2Name": (player.clone())}]
+        });
+    }
+    if let Some(winner) = &query.winner {
+        conditions.push(doc! {"WinnerName": (winner.clone())});
+    }
+    if let Ok(Some(suffix)) = query.opponent_suffix() {
+        conditions.push(doc! {"gameType": {"$regex": (format!("{}$", suffix))}});
+    }
+    if let Some(from) = query.from {
+        conditions.push(doc! {"GameDate": {"$gte": from}});
+    }
+    if let Some(to) = query.to {
+        conditions.push(doc! {"GameDate": {"$lt": to}});
+    }
+
+    let mut filter = bson::Document::new();
+    if !conditions.is_empty() {
+        let conditions = conditions.into_iter().map(bson::Bson::Document).collect();
+        filter.insert("$and", bson::Bson::Array(conditions));
+    }
+    filter
+}
+
+pub fn get_all_games_handler(query: &GameQuery, connection: &Conn) -> Result<GamePage, Error> {
+    let filter = games_filter(query);
+    let total = connection
+        .collection("games")
+        .count(Some(filter.clone()), None)?;
+
+    // Mongo takes a limit of 0 as no limit
+    if query.limit == Some(0) {
+        return Ok(GamePage {
+            games: Vec::new(),
+            total: total as u64,
+        });
+    }
+
+    let order = if query.newest_first() { -1 } else { 1 };
+    let mut options = FindOptions::new();
+    options.sort = Some(doc! {"GameDate": order, "_id": order});
+    options.skip = Some(query.skip() as i64);
+    options.limit = query.limit.map(|limit| limit as i64);
+    let cursor = connection
+        .collection("games")
+        .find(Some(filter), Some(options))?;
 
-    cursor
+    let games = cursor
         .map(|result| match result {
             Ok(doc) => match bson::from_bson(bson::Bson::Document(doc)) {
                 Ok(result_model) => Ok(result_model),
@@ -21,7 +76,11 @@ pub fn get_all_games_handler(connection: &Conn) -> Result<Vec<Game>, Error> {
                 Err(err)
             }
         })
-        .collect::<Result<Vec<Game>, Error>>()
+        .collect::<Result<Vec<Game>, Error>>()?;
+    Ok(GamePage {
+        games,
+        total: total as u64,
+    })
 }
 
 pub fn get_game_with_id_handler(id: ObjectId, conn: &Conn) -> Result<Option<Game>, Error> {
@@ -59,16 +118,21 @@ pub fn update_game_with_id_handler(
     id: bson::oid::ObjectId,
     game: Game,
     connection: &Conn,
-) -> Result<Game, Error> {
+) -> Result<Game, StoreError> {
+    SerializableGame::from(&game)
+        .verify()
+        .map_err(StoreError::Invalid)?;
     let mut game = game.clone();
     game.id = Some(id.clone());
-    match bson::to_bson(&game) {
+    let updated = match bson::to_bson(&game) {
         Ok(model_bson) => match model_bson {
             bson::Bson::Document(doc) => {
                 match connection
                     .collection("games")
                     .replace_one(doc! {"_id": id}, doc, None)
                 {
+                    // replace_one matches nothing for an id that is not stored
+                    Ok(res) if res.matched_count == 0 => return Err(StoreError::NotFound),
                     Ok(_) => Ok(game),
                     Err(err) => Err(err),
                 }
@@ -78,18 +142,28 @@ pub fn update_game_with_id_handler(
             ))),
         },
         Err(_) => Err(Error::DefaultError(String::from("Failed to generate BSON"))),
-    }
+    };
+    Ok(updated?)
 }
 
-pub fn insert_game_handler(game: SerializableGame, connection: &Conn) -> Result<ObjectId, Error> {
+pub fn insert_game_handler(
+    game: SerializableGame,
+    connection: &Conn,
+) -> Result<ObjectId, StoreError> {
+    game.verify().map_err(StoreError::Invalid)?;
     let new_game = InsertableGame {
         gameType: game.gameType,
         Player1Name: game.Player1Name,
         Player2Name: game.Player2Name,
         WinnerName: game.WinnerName,
         GameDate: Utc::now(),
+        rows: game.rows,
+        cols: game.cols,
+        moves: game.moves,
+        first_player: game.first_player,
+        variant: game.variant,
     };
-    match bson::to_bson(&new_game) {
+    let inserted = match bson::to_bson(&new_game) {
         Ok(model_bson) => match model_bson {
             bson::Bson::Document(model_doc) => {
                 match connection.collection("games").insert_one(model_doc, None) {
@@ -108,9 +182,61 @@ pub fn insert_game_handler(game: SerializableGame, connection: &Conn) -> Result<
             ))),
         },
         Err(_) => Err(Error::DefaultError(String::from("Failed to create BSON"))),
-    }
+    };
+    Ok(inserted?)
 }
 
 pub fn delete_game_handler(id: ObjectId, conn: &Conn) -> Result<DeleteResult, Error> {
     conn.collection("games").delete_one(doc! {"_id": id}, None)
 }
+
+// The games collection of MongoDB
+pub struct MongoStore {
+    pool: Pool,
+}
+
+impl MongoStore {
+    pub fn new(pool: Pool) -> Self {
+        MongoStore { pool }
+    }
+
+    fn conn(&self) -> Result<Conn, StoreError> {
+        self.pool
+            .get()
+            .map(Conn)
+            .map_err(|_| StoreError::Unavailable)
+    }
+}
+
+impl GameStore for MongoStore {
+    fn games(&self, query: &GameQuery) -> Result<GamePage, StoreError> {
+        Ok(get_all_games_handler(query, &self.conn()?)?)
+    }
+
+    fn game(&self, id: ObjectId) -> Result<Option<Game>, StoreError> {
+        Ok(get_game_with_id_handler(id, &self.conn()?)?)
+    }
+
+    fn insert_game(&self, game: SerializableGame) -> Result<ObjectId, StoreError> {
+        insert_game_handler(game, &self.conn()?)
+    }
+
+    fn update_game(&self, id: ObjectId, game: Game) -> Result<Game, StoreError> {
+        update_game_with_id_handler(id, game, &self.conn()?)
+    }
+
+    fn delete_game(&self, id: ObjectId) -> Result<(), StoreError> {
+        delete_game_handler(id, &self.conn()?)?;
+        Ok(())
+    }
+
+    fn wins(&self) -> Result<Vec<PlayerWins>, StoreError> {
+        get_wins_by_player(&self.conn()?)?
+            .into_iter()
+            .map(|doc| {
+                bson::from_bson(bson::Bson::Document(doc))
+                    .map_err(|e| StoreError::Failed(format!("{:?}", e)))
+            })
+            .collect()
+    }
+}
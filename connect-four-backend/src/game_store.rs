@@ -0,0 +1,217 @@
+use crate::game::{Game, PlayerWins, SerializableGame};
+use crate::game_query::{GamePage, GameQuery};
+use crate::game_repository::MongoStore;
+use crate::memory_store::MemoryStore;
+use crate::mongo_connection::{self, DatabaseConfig};
+use crate::sqlite_store::SqliteStore;
+use mongodb::error::Error;
+use mongodb::oid::ObjectId;
+use rocket::config::{Config, ConfigError};
+
+// File the sqlite store uses without a sqlite_path
+const DEFAULT_SQLITE_PATH: &str = "connect4.sqlite";
+
+// Why a store could not do what it was asked
+#[derive(Debug)]
+pub enum StoreError {
+    NotFound,
+
+    // The database cannot be reached right now
+    Unavailable,
+
+    // A game that was not stored, and why
+    Invalid(String),
+    Failed(String),
+}
+
+impl From<Error> for StoreError {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::CursorNotFoundError => StoreError::NotFound,
+            err => StoreError::Failed(format!("{:?}", err)),
+        }
+    }
+}
+
+// Where the games are kept, chosen with the store key of the Rocket config. Every
+// store hands out ObjectIds so the API looks the same whichever is used
+pub trait GameStore: Send + Sync {
+    // The page of the games that match the query, which was validated
+    fn games(&self, query: &GameQuery) -> Result<GamePage, StoreError>;
+    fn game(&self, id: ObjectId) -> Result<Option<Game>, StoreError>;
+
+    // Check the moves of the game with the engine and store it with the current date,
+    // returns its id
+    fn insert_game(&self, game: SerializableGame) -> Result<ObjectId, StoreError>;
+
+    // Check the moves of the game with the engine and replace the game of the id with
+    // it, NotFound when there is none
+    fn update_game(&self, id: ObjectId, game: Game) -> Result<Game, StoreError>;
+    fn delete_game(&self, id: ObjectId) -> Result<(), StoreError>;
+
+    // Games won by each winner name, draws included as the name Draw
+    fn wins(&self) -> Result<Vec<PlayerWins>, StoreError>;
+}
+
+pub type Store = Box<dyn GameStore>;
+
+// Id for a game of a store other than Mongo
+pub fn new_id() -> Result<ObjectId, StoreError> {
+    ObjectId::new().map_err(|e| StoreError::Failed(format!("{:?}", e)))
+}
+
+// The store named by the store key of the config: mongo (the default), memory or sqlite
+pub fn open_store(config: &Config) -> Result<Store, String> {
+    match config.get_str("store") {
+        Ok("mongo") | Err(ConfigError::Missing(_)) => open_mongo(config),
+        Ok("memory") => Ok(Box::new(MemoryStore::default())),
+        Ok("sqlite") => {
+            let path = match config.get_str("sqlite_path") {
+                Ok(path) => path,
+                Err(ConfigError::Missing(_)) => DEFAULT_SQLITE_PATH,
+                Err(e) => return Err(format!("sqlite_path: {}", e)),
+            };
+            println!("Storing the games in {}", path);
+            Ok(Box::new(SqliteStore::open(path)?))
+        }
+        Ok(x) => Err(format!("store must be mongo, memory or sqlite, not {}", x)),
+        Err(e) => Err(format!("store: {}", e)),
+    }
+}
+
+// An unreachable database is not an error, the frontend is served and the API answers
+// 503 until it can be reached
+fn open_mongo(config: &Config) -> Result<Store, String> {
+    let config = DatabaseConfig::from_config(config)?;
+    let pool = mongo_connection::init_connection(&config);
+    match pool.get() {
+        Ok(_) => println!(
+            "Connected to database {} at {}",
+            config.database,
+            config.address()
+        ),
+        Err(e) => {
+            println!(
+                "Could not connect to MongoDB at {}: {}",
+                config.address(),
+                e
+            );
+            println!("Serving the frontend only until the database can be reached");
+        }
+    }
+    Ok(Box::new(MongoStore::new(pool)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Ann wins down column 0, Bob never blocks
+    fn won_game() -> SerializableGame {
+        SerializableGame {
+            gameType: "Connect4 with Human".to_string(),
+            Player1Name: "Ann".to_string(),
+            Player2Name: "Bob".to_string(),
+            WinnerName: "Ann".to_string(),
+            rows: 6,
+            cols: 7,
+            moves: ["0", "1", "0", "1", "0", "1", "0"]
+                .iter()
+                .map(|mv| mv.to_string())
+                .collect(),
+            first_player: 1,
+            variant: None,
+        }
+    }
+
+    fn wins_of(store: &dyn GameStore, name: &str) -> i64 {
+        store
+            .wins()
+            .unwrap()
+            .into_iter()
+            .find(|wins| wins._id == name)
+            .map_or(0, |wins| wins.count)
+    }
+
+    fn opponent_total(store: &dyn GameStore, opponent: &str) -> u64 {
+        let query = GameQuery {
+            opponent: Some(opponent.to_string()),
+            ..GameQuery::default()
+        };
+        store.games(&query).unwrap().total
+    }
+
+    // The same cases for every store that runs without a server
+    fn check_store(store: &dyn GameStore) {
+        let id = store.insert_game(won_game()).unwrap();
+        let game = store.game(id.clone()).unwrap().unwrap();
+        assert_eq!(game.id, Some(id.clone()));
+        assert_eq!(game.Player1Name, "Ann");
+        assert_eq!(game.WinnerName, "Ann");
+        assert_eq!((game.rows, game.cols), (6, 7));
+        assert_eq!(game.moves, won_game().moves);
+        assert!(store.game(new_id().unwrap()).unwrap().is_none());
+
+        let mut unfinished = won_game();
+        unfinished.moves.pop();
+        match store.insert_game(unfinished) {
+            Err(StoreError::Invalid(_)) => {}
+            _ => panic!("an unfinished game was stored"),
+        }
+
+        let mut renamed = game.clone();
+        renamed.Player2Name = "Bea".to_string();
+        let updated = store.update_game(id.clone(), renamed).unwrap();
+        assert_eq!(updated.id, Some(id.clone()));
+        assert_eq!(store.game(id.clone()).unwrap().unwrap().Player2Name, "Bea");
+        match store.update_game(new_id().unwrap(), game.clone()) {
+            Err(StoreError::NotFound) => {}
+            _ => panic!("a missing game was updated"),
+        }
+        let mut cheated = game;
+        cheated.WinnerName = "Bob".to_string();
+        match store.update_game(id.clone(), cheated) {
+            Err(StoreError::Invalid(_)) => {}
+            _ => panic!("a game that did not end that way was stored"),
+        }
+        assert_eq!(store.game(id.clone()).unwrap().unwrap().WinnerName, "Ann");
+
+        store.insert_game(won_game()).unwrap();
+        assert_eq!(wins_of(store, "Ann"), 2);
+        assert_eq!(opponent_total(store, "human"), 2);
+        assert_eq!(opponent_total(store, "computer"), 0);
+        assert_eq!(wins_of(store, "Bob"), 0);
+
+        store.delete_game(id.clone()).unwrap();
+        assert!(store.game(id).unwrap().is_none());
+        assert_eq!(wins_of(store, "Ann"), 1);
+    }
+
+    #[test]
+    fn memory_store() {
+        check_store(&MemoryStore::default());
+    }
+
+    #[test]
+    fn sqlite_store() {
+        check_store(&SqliteStore::open(":memory:").unwrap());
+
+        // A game type in other case, as an older table may hold, is not of either
+        let path = std::env::temp_dir().join(format!("game_store_{}.sqlite", std::process::id()));
+        let path = path.to_str().unwrap();
+        let store = SqliteStore::open(path).unwrap();
+        rusqlite::Connection::open(path)
+            .unwrap()
+            .execute(
+                "INSERT INTO games (id, game_type, player1_name, player2_name, winner_name,
+                 game_date) VALUES ('old', 'Connect4 with human', 'Ann', 'Bob', 'Ann', 0)",
+                rusqlite::NO_PARAMS,
+            )
+            .unwrap();
+        store.insert_game(won_game()).unwrap();
+        assert_eq!(store.games(&GameQuery::default()).unwrap().total, 2);
+        assert_eq!(opponent_total(&store, "human"), 1);
+        assert_eq!(opponent_total(&store, "computer"), 0);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -5,97 +5,188 @@
 
 #![feature(decl_macro, proc_macro_hygiene)]
 extern crate r2d2_mongodb;
+mod engine;
 mod game;
+mod game_query;
 mod game_repository;
+mod game_store;
+mod memory_store;
 mod mongo_connection;
+mod session;
+mod sqlite_store;
 #[macro_use]
 extern crate rocket;
 
-use crate::game::{Game, SerializableGame};
-use crate::mongo_connection::Conn;
-use mongodb::{bson, doc, error::Error, oid::ObjectId};
+use crate::game::{Game, PlayerWins, SerializableGame};
+use crate::game_query::{GamePage, GameQuery};
+use crate::game_store::{Store, StoreError};
+use crate::session::{
+    lock_session, NewSession, Session, SessionError, SessionMove, SessionState, Sessions,
+};
+use mongodb::oid::ObjectId;
+use rocket::fairing::AdHoc;
 use rocket::http::Status;
+use rocket::request::LenientForm;
+use rocket::response::{self, status, Responder, Response};
+use rocket::{Request, Rocket, State};
 use rocket_contrib::json::Json;
 use rocket_contrib::serve::StaticFiles;
 
-fn error_status(error: Error) -> Status {
+fn error_status(error: StoreError) -> Status {
     match error {
-        Error::CursorNotFoundError => Status::NotFound,
-        _ => Status::InternalServerError,
+        StoreError::NotFound => Status::NotFound,
+        StoreError::Unavailable => Status::ServiceUnavailable,
+        StoreError::Invalid(_) => Status::BadRequest,
+        StoreError::Failed(_) => Status::InternalServerError,
     }
 }
 
+// The status with a message to show, why the game was turned down or the reason
+// phrase of the status
+fn error_message(error: StoreError) -> status::Custom<String> {
+    match error {
+        StoreError::Invalid(msg) => status::Custom(Status::BadRequest, msg),
+        err => {
+            println!("store -> {:?}", err);
+            let status = error_status(err);
+            status::Custom(status, status.reason.to_string())
+        }
+    }
+}
+
+// The games as a JSON array, the number of games that match in X-Total-Count
+impl<'r> Responder<'r> for GamePage {
+    fn respond_to(self, request: &Request) -> response::Result<'r> {
+        Response::build_from(Json(self.games).respond_to(request)?)
+            .raw_header("X-Total-Count", self.total.to_string())
+            .ok()
+    }
+}
+
+// Id of a stored game, one that cannot be parsed names no game
+fn parse_id(id: &str) -> Result<ObjectId, StoreError> {
+    ObjectId::with_string(id).map_err(|_| StoreError::NotFound)
+}
+
 // index.js
-#[get("/")]
-pub fn get_all_games(connection: Conn) -> Result<Json<Vec<Game>>, Status> {
-    match game_repository::get_all_games_handler(&connection) {
-        Ok(res) => Ok(Json(res)),
+#[get("/?<query..>")]
+pub fn get_all_games(
+    query: LenientForm<GameQuery>,
+    store: State<Store>,
+) -> Result<GamePage, Status> {
+    if let Err(msg) = query.validate() {
+        println!("get_all_games -> {}", msg);
+        return Err(Status::BadRequest);
+    }
+    match store.games(&query) {
+        Ok(res) => Ok(res),
         Err(err) => Err(error_status(err)),
     }
 }
 
 #[get("/")]
-fn get_player_wins(connection: Conn) -> Result<Json<Vec<bson::Document>>, Status> {
-    match game_repository::get_wins_by_player(&connection) {
+fn get_player_wins(store: State<Store>) -> Result<Json<Vec<PlayerWins>>, Status> {
+    match store.wins() {
         Ok(res) => Ok(Json(res)),
         Err(err) => Err(error_status(err)),
     }
 }
 
+// The moves are played again by the engine, a game that does not end the way it
+// claims is turned down with 400 and the reason
 #[post("/", format = "application/json", data = "<game>")]
-fn insert_game(game: Json<SerializableGame>, connection: Conn) -> Result<Json<ObjectId>, Status> {
-    match game_repository::insert_game_handler(game.into_inner(), &connection) {
+fn insert_game(
+    game: Json<SerializableGame>,
+    store: State<Store>,
+) -> Result<Json<ObjectId>, status::Custom<String>> {
+    match store.insert_game(game.into_inner()) {
         Ok(res) => Ok(Json(res)),
-        Err(err) => Err(error_status(err)),
+        Err(err) => Err(error_message(err)),
     }
 }
 
+// Checked by the engine like a posted game, 400 with the reason when it does not hold
 #[put("/<id>", format = "application/json", data = "<game>")]
 fn update_game_with_id(
     id: String,
     game: Json<Game>,
-    connection: Conn,
-) -> Result<Json<Game>, Status> {
-    match ObjectId::with_string(&String::from(&id)) {
-        Ok(res) => {
-            match game_repository::update_game_with_id_handler(res, game.into_inner(), &connection)
-            {
-                Ok(res) => Ok(Json(res)),
-                Err(err) => Err(error_status(err)),
-            }
-        }
-        Err(_) => Err(error_status(Error::DefaultError(String::from(
-            "Failed to parse ObjectId",
-        )))),
+    store: State<Store>,
+) -> Result<Json<Game>, status::Custom<String>> {
+    match parse_id(&id).and_then(|id| store.update_game(id, game.into_inner())) {
+        Ok(res) => Ok(Json(res)),
+        Err(err) => Err(error_message(err)),
     }
 }
 
 #[get("/<id>")]
-fn get_game_with_id(id: String, connection: Conn) -> Result<Json<Game>, Status> {
-    match ObjectId::with_string(&String::from(&id)) {
-        Ok(r) => match game_repository::get_game_with_id_handler(r, &connection) {
-            Ok(res) => Ok(Json(res.unwrap())),
-            Err(err) => Err(error_status(err)),
-        },
-        Err(_) => Err(error_status(Error::DefaultError(String::from(
-            "Error parsing ObjectId",
-        )))),
+fn get_game_with_id(id: String, store: State<Store>) -> Result<Json<Game>, Status> {
+    match parse_id(&id).and_then(|id| store.game(id)) {
+        Ok(Some(res)) => Ok(Json(res)),
+        Ok(None) => Err(Status::NotFound),
+        Err(err) => Err(error_status(err)),
     }
 }
 
 #[delete("/<id>")]
-fn games_id_delete(id: String, connection: Conn) -> Result<Json<String>, Status> {
-    match ObjectId::with_string(&String::from(&id)) {
-        Ok(r) => match game_repository::delete_game_handler(r, &connection) {
-            Ok(_) => Ok(Json(id)),
-            Err(err) => Err(error_status(err)),
-        },
-        Err(_) => Err(error_status(Error::DefaultError(String::from(
-            "Error parsing ObjectId",
-        )))),
+fn games_id_delete(id: String, store: State<Store>) -> Result<Json<String>, Status> {
+    match parse_id(&id).and_then(|id| store.delete_game(id)) {
+        Ok(_) => Ok(Json(id)),
+        Err(err) => Err(error_status(err)),
     }
 }
 
+// Status and message of a failed session request
+fn session_error(error: SessionError) -> status::Custom<String> {
+    match error {
+        SessionError::NotFound => status::Custom(Status::NotFound, "No such session".to_string()),
+        SessionError::Illegal(msg) => status::Custom(Status::BadRequest, msg),
+        SessionError::Finished => status::Custom(Status::Conflict, "The game is over".to_string()),
+        SessionError::Store(err) => error_message(err),
+    }
+}
+
+// Games played on the server, which checks every move with the engine and records
+// the result itself once the game is over
+#[post("/", format = "application/json", data = "<new>")]
+fn create_session(
+    new: Json<NewSession>,
+    sessions: State<Sessions>,
+) -> Result<Json<SessionState>, status::Custom<String>> {
+    let session =
+        Session::new(new.into_inner()).map_err(|msg| session_error(SessionError::Illegal(msg)))?;
+    let session = sessions.insert(session).map_err(session_error)?;
+    let state = lock_session(&session).state();
+    state.map(Json).map_err(session_error)
+}
+
+#[get("/<id>")]
+fn get_session(
+    id: String,
+    sessions: State<Sessions>,
+    store: State<Store>,
+) -> Result<Json<SessionState>, status::Custom<String>> {
+    let session = sessions.get(&id).map_err(session_error)?;
+    let mut session = lock_session(&session);
+    session.record(store.as_ref());
+    sessions.forget_recorded(&session);
+    session.state().map(Json).map_err(session_error)
+}
+
+#[post("/<id>/moves", format = "application/json", data = "<mv>")]
+fn play_session_move(
+    id: String,
+    mv: Json<SessionMove>,
+    sessions: State<Sessions>,
+    store: State<Store>,
+) -> Result<Json<SessionState>, status::Custom<String>> {
+    let session = sessions.get(&id).map_err(session_error)?;
+    let mut session = lock_session(&session);
+    session.play(&mv.mv).map_err(session_error)?;
+    session.record(store.as_ref());
+    sessions.forget_recorded(&session);
+    session.state().map(Json).map_err(session_error)
+}
+
 // users.js
 #[get("/")]
 fn users() -> String {
@@ -106,37 +197,49 @@ fn users() -> String {
 // This section is not implemented because it is never used by the frontend
 #[allow(unused_variables)]
 #[get("/posts")]
-fn api_posts_get(connection: Conn) -> Result<Json<Vec<Game>>, Status> {
+fn api_posts_get(store: State<Store>) -> Result<Json<Vec<Game>>, Status> {
     Err(Status::NotFound)
 }
 
 #[allow(unused_variables)]
 #[post("/posts")]
-fn api_posts_post(connection: Conn) -> Result<Json<Vec<Game>>, Status> {
+fn api_posts_post(store: State<Store>) -> Result<Json<Vec<Game>>, Status> {
     Err(Status::NotFound)
 }
 
 #[allow(unused_variables)]
 #[put("/posts/<id>")]
-fn api_posts_id_put(id: i64, connection: Conn) -> Result<Json<Vec<Game>>, Status> {
+fn api_posts_id_put(id: i64, store: State<Store>) -> Result<Json<Vec<Game>>, Status> {
     Err(Status::NotFound)
 }
 
 #[allow(unused_variables)]
 #[get("/posts/<id>")]
-fn api_posts_id_get(id: i64, connection: Conn) -> Result<Json<Vec<Game>>, Status> {
+fn api_posts_id_get(id: i64, store: State<Store>) -> Result<Json<Vec<Game>>, Status> {
     Err(Status::NotFound)
 }
 
 #[allow(unused_variables)]
 #[delete("/posts/<id>")]
-fn api_posts_id_delete(id: i64, connection: Conn) -> Result<Json<Vec<Game>>, Status> {
+fn api_posts_id_delete(id: i64, store: State<Store>) -> Result<Json<Vec<Game>>, Status> {
     Err(Status::NotFound)
 }
 
+// Manage the store the config asks for, a bad config stops the launch
+fn attach_store(rocket: Rocket) -> Result<Rocket, Rocket> {
+    match game_store::open_store(rocket.config()) {
+        Ok(store) => Ok(rocket.manage(store)),
+        Err(msg) => {
+            println!("Invalid storage configuration: {}", msg);
+            Err(rocket)
+        }
+    }
+}
+
 fn main() {
     rocket::ignite()
-        .manage(mongo_connection::init_connection())
+        .attach(AdHoc::on_attach("Game store", attach_store))
+        .manage(Sessions::default())
         .mount("/", StaticFiles::from("./public"))
         .mount(
             "/games",
@@ -148,6 +251,10 @@ fn main() {
                 games_id_delete
             ],
         )
+        .mount(
+            "/sessions",
+            routes![create_session, get_session, play_session_move],
+        )
         .mount("/wins", routes![get_player_wins,])
         .mount("/users", routes![users])
         .mount(
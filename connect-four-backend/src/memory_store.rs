@@ -0,0 +1,90 @@
+use crate::game::{Game, PlayerWins, SerializableGame};
+use crate::game_query::{GamePage, GameQuery};
+use crate::game_store::{new_id, GameStore, StoreError};
+use chrono::Utc;
+use mongodb::oid::ObjectId;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard};
+
+// Games kept in memory until the server stops, for trying out the backend and tests
+#[derive(Default)]
+pub struct MemoryStore {
+    games: Mutex<Vec<Game>>,
+}
+
+impl MemoryStore {
+    fn lock(&self) -> MutexGuard<Vec<Game>> {
+        // A handler that panicked cannot leave a game half written
+        match self.games.lock() {
+            Ok(games) => games,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+impl GameStore for MemoryStore {
+    fn games(&self, query: &GameQuery) -> Result<GamePage, StoreError> {
+        Ok(query.page_of(&self.lock()))
+    }
+
+    fn game(&self, id: ObjectId) -> Result<Option<Game>, StoreError> {
+        Ok(self
+            .lock()
+            .iter()
+            .find(|game| game.id.as_ref() == Some(&id))
+            .cloned())
+    }
+
+    fn insert_game(&self, game: SerializableGame) -> Result<ObjectId, StoreError> {
+        game.verify().map_err(StoreError::Invalid)?;
+        let id = new_id()?;
+        self.lock().push(Game {
+            id: Some(id.clone()),
+            gameType: game.gameType,
+            Player1Name: game.Player1Name,
+            Player2Name: game.Player2Name,
+            WinnerName: game.WinnerName,
+            GameDate: Utc::now(),
+            rows: game.rows,
+            cols: game.cols,
+            moves: game.moves,
+            first_player: game.first_player,
+            variant: game.variant,
+        });
+        Ok(id)
+    }
+
+    fn update_game(&self, id: ObjectId, game: Game) -> Result<Game, StoreError> {
+        SerializableGame::from(&game)
+            .verify()
+            .map_err(StoreError::Invalid)?;
+        let mut games = self.lock();
+        match games
+            .iter_mut()
+            .find(|stored| stored.id.as_ref() == Some(&id))
+        {
+            Some(stored) => {
+                *stored = game;
+                stored.id = Some(id);
+                Ok(stored.clone())
+            }
+            None => Err(StoreError::NotFound),
+        }
+    }
+
+    fn delete_game(&self, id: ObjectId) -> Result<(), StoreError> {
+        self.lock().retain(|game| game.id.as_ref() != Some(&id));
+        Ok(())
+    }
+
+    fn wins(&self) -> Result<Vec<PlayerWins>, StoreError> {
+        let mut counts = BTreeMap::new();
+        for game in self.lock().iter() {
+            *counts.entry(game.WinnerName.clone()).or_insert(0) += 1;
+        }
+        Ok(counts
+            .into_iter()
+            .map(|(_id, count)| PlayerWins { _id, count })
+            .collect())
+    }
+}
@@ -4,43 +4,137 @@
     https://medium.com/@louis.beaumont/rest-api-with-rust-mongodb-10eeb6bd51d7
 */
 
+use mongodb::connstring;
 use r2d2::PooledConnection;
 use r2d2_mongodb::{ConnectionOptions, MongodbConnectionManager};
-use rocket::http::Status;
-use rocket::request::{self, FromRequest};
-use rocket::{Outcome, Request, State};
+use rocket::config::{Config, ConfigError};
 use std::ops::Deref;
+use std::time::Duration;
 
-type Pool = r2d2::Pool<MongodbConnectionManager>;
+// Used when Rocket.toml and the ROCKET_MONGO_* variables leave a setting out
+const DEFAULT_URI: &str = "mongodb://localhost:27017";
+const DEFAULT_DATABASE: &str = "Connect4RustDB";
+const DEFAULT_POOL_SIZE: u32 = 64;
+const DEFAULT_CONNECT_TIMEOUT: u64 = 5;
+
+pub type Pool = r2d2::Pool<MongodbConnectionManager>;
 pub struct Conn(pub PooledConnection<MongodbConnectionManager>);
 
-pub fn init_connection() -> Pool {
-    let mongo_address = "localhost";
-    let mongo_port = 27017 as u16;
-    let db_name = "Connect4RustDB";
-    let manager = MongodbConnectionManager::new(
-        ConnectionOptions::builder()
-            .with_host(&mongo_address, mongo_port)
-            .with_db(&db_name)
-            .build(),
-    );
-
-    match Pool::builder().max_size(64).build(manager) {
-        Ok(pool) => pool,
-        Err(e) => panic!(e),
-    }
+// Where the games are stored, read from the mongo_* keys of the Rocket config
+#[derive(Clone, Debug)]
+pub struct DatabaseConfig {
+    pub hosts: Vec<(String, u16)>,
+    pub database: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub pool_size: u32,
+
+    // Longest wait for a connection, an unreachable database fails requests after it
+    pub connect_timeout: Duration,
+
+    // Idle connections are closed after it, after ten minutes without one
+    pub idle_timeout: Option<Duration>,
 }
 
-impl<'a, 'r> FromRequest<'a, 'r> for Conn {
-    type Error = ();
+impl DatabaseConfig {
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let uri = string(config, "mongo_uri")?.unwrap_or_else(|| DEFAULT_URI.to_string());
+        let parsed = connstring::parse(&uri)
+            .map_err(|e| format!("mongo_uri is not a MongoDB URI: {}", e))?;
+        let hosts = parsed
+            .hosts
+            .iter()
+            .map(|host| (host.host_name.clone(), host.port))
+            .collect::<Vec<_>>();
+        if hosts.is_empty() {
+            return Err("mongo_uri has no host".to_string());
+        }
 
-    fn from_request(request: &'a Request<'r>) -> request::Outcome<Conn, ()> {
-        let pool = request.guard::<State<Pool>>()?;
-        match pool.get() {
-            Ok(db) => Outcome::Success(Conn(db)),
-            Err(_) => Outcome::Failure((Status::ServiceUnavailable, ())),
+        // Credentials of their own keys win over the ones in the URI
+        let username = string(config, "mongo_username")?.or(parsed.user);
+        let password = string(config, "mongo_password")?.or(parsed.password);
+        if username.is_some() != password.is_some() {
+            return Err("mongo_username and mongo_password must be given together".to_string());
         }
+
+        let pool_size = match integer(config, "mongo_pool_size")? {
+            Some(size) if size < 1 || size > u32::max_value() as i64 => {
+                return Err(format!(
+                    "mongo_pool_size must be a positive number, not {}",
+                    size
+                ))
+            }
+            Some(size) => size as u32,
+            None => DEFAULT_POOL_SIZE,
+        };
+
+        Ok(DatabaseConfig {
+            hosts,
+            database: string(config, "mongo_database")?
+                .unwrap_or_else(|| DEFAULT_DATABASE.to_string()),
+            username,
+            password,
+            pool_size,
+            connect_timeout: seconds(config, "mongo_connect_timeout")?
+                .unwrap_or_else(|| Duration::from_secs(DEFAULT_CONNECT_TIMEOUT)),
+            idle_timeout: seconds(config, "mongo_idle_timeout")?,
+        })
+    }
+
+    // Hosts of the URI for messages, which leave the credentials out
+    pub fn address(&self) -> String {
+        self.hosts
+            .iter()
+            .map(|(host, port)| format!("{}:{}", host, port))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+fn string(config: &Config, name: &str) -> Result<Option<String>, String> {
+    match config.get_string(name) {
+        Ok(value) => Ok(Some(value)),
+        Err(ConfigError::Missing(_)) => Ok(None),
+        Err(e) => Err(format!("{}: {}", name, e)),
+    }
+}
+
+fn integer(config: &Config, name: &str) -> Result<Option<i64>, String> {
+    match config.get_int(name) {
+        Ok(value) => Ok(Some(value)),
+        Err(ConfigError::Missing(_)) => Ok(None),
+        Err(e) => Err(format!("{}: {}", name, e)),
+    }
+}
+
+fn seconds(config: &Config, name: &str) -> Result<Option<Duration>, String> {
+    match integer(config, name)? {
+        Some(secs) if secs < 1 => Err(format!("{} must be at least 1 second", name)),
+        Some(secs) => Ok(Some(Duration::from_secs(secs as u64))),
+        None => Ok(None),
+    }
+}
+
+// The pool connects lazily, so it is built even when the database is down and
+// requests succeed once it comes up
+pub fn init_connection(config: &DatabaseConfig) -> Pool {
+    let mut options = ConnectionOptions::builder();
+    for (host, port) in config.hosts.iter() {
+        options.with_host(host, *port);
+    }
+    options.with_db(&config.database);
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        options.with_auth(username, password);
+    }
+    let manager = MongodbConnectionManager::new(options.build());
+
+    let mut builder = Pool::builder()
+        .max_size(config.pool_size)
+        .connection_timeout(config.connect_timeout);
+    if config.idle_timeout.is_some() {
+        builder = builder.idle_timeout(config.idle_timeout);
     }
+    builder.build_unchecked(manager)
 }
 
 impl Deref for Conn {
@@ -0,0 +1,409 @@
+use crate::engine::{self, Engine};
+use crate::game::SerializableGame;
+use crate::game_store::{new_id, GameStore, StoreError};
+use connect_four_cli::save::{self, SavedGame, SavedVariant};
+use mongodb::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+// Search depth of the computer when the session does not give one, and the deepest
+// it may ask for, as expert in the frontend. TOOT-OTTO has more moves to search and
+// stops at the depth the CLI plays it at
+const DEFAULT_AI_DEPTH: u32 = 4;
+const MAX_AI_DEPTH: u32 = 6;
+const TOOT_OTTO_AI_DEPTH: u32 = 3;
+
+// How long a session is kept without a request and how many are kept at most, the
+// least recently used one makes room for a new session
+const SESSION_TTL: Duration = Duration::from_secs(60 * 60);
+const MAX_SESSIONS: usize = 1000;
+
+fn default_rows() -> usize {
+    6
+}
+
+fn default_cols() -> usize {
+    7
+}
+
+// Body of POST /sessions. Without p2 player two is the computer
+#[derive(Deserialize, Clone, Debug)]
+pub struct NewSession {
+    // connect4 or toot-otto
+    pub game: String,
+    #[serde(default = "default_rows")]
+    pub rows: usize,
+    #[serde(default = "default_cols")]
+    pub cols: usize,
+    pub p1: String,
+    pub p2: Option<String>,
+    pub max_ai_depth: Option<u32>,
+
+    // Words and letters of a TOOT-OTTO game, TOOT and OTTO without them
+    pub variant: Option<SavedVariant>,
+}
+
+// Body of POST /sessions/<id>/moves: a column for Connect-4, letter and column such
+// as "T3" for TOOT-OTTO
+#[derive(Deserialize, Clone, Debug)]
+pub struct SessionMove {
+    #[serde(rename = "move")]
+    pub mv: String,
+}
+
+// What the session routes answer. The board has a row of R, Y and _ for Connect-4 or
+// of letters, upper case for player one, from the top
+#[derive(Serialize, Clone, Debug)]
+pub struct SessionState {
+    pub id: String,
+    #[serde(flatten)]
+    pub saved: SavedGame,
+    pub board: Vec<String>,
+
+    // running, won or draw
+    pub state: String,
+    pub winner: Option<String>,
+    pub to_move: Option<String>,
+
+    // Id of the game in /games once the result is recorded
+    pub game_id: Option<ObjectId>,
+}
+
+#[derive(Debug)]
+pub enum SessionError {
+    NotFound,
+
+    // The move is not legal, or not a move at all
+    Illegal(String),
+    Finished,
+    Store(StoreError),
+}
+
+impl From<StoreError> for SessionError {
+    fn from(error: StoreError) -> Self {
+        SessionError::Store(error)
+    }
+}
+
+// A game played on the server. Only the saved game is kept, every move rebuilds the
+// engine game from it so the moves are checked by the same rules as a loaded save
+pub struct Session {
+    pub id: String,
+    pub saved: SavedGame,
+    pub game_id: Option<ObjectId>,
+}
+
+impl Session {
+    pub fn new(new: NewSession) -> Result<Self, String> {
+        let with_ai = new.p2.is_none();
+        let p2 = new.p2.unwrap_or_else(|| "Computer".to_string());
+        engine::check_player_names(&new.p1, &p2)?;
+        let (default_depth, max_depth) = if new.game == save::TOOT_OTTO {
+            (TOOT_OTTO_AI_DEPTH, TOOT_OTTO_AI_DEPTH)
+        } else {
+            (DEFAULT_AI_DEPTH, MAX_AI_DEPTH)
+        };
+        let depth = new.max_ai_depth.unwrap_or(default_depth);
+        if depth == 0 || depth > max_depth {
+            return Err(format!("max_ai_depth must be from 1 to {}", max_depth));
+        }
+
+        let mut saved = engine::new_game(&new.game, new.rows, new.cols);
+        saved.with_ai = with_ai;
+        saved.p1 = new.p1;
+        saved.p2 = p2;
+        saved.max_ai_depth = depth;
+        if saved.game == save::TOOT_OTTO && new.variant.is_some() {
+            saved.variant = new.variant;
+        }
+        // Checks the game, the board and the words
+        Engine::load(&saved)?;
+        Ok(Session {
+            id: String::new(),
+            saved,
+            game_id: None,
+        })
+    }
+
+    // The moves were checked when they were played, a save that does not load is a bug
+    fn engine(&self) -> Result<Engine, SessionError> {
+        Engine::load(&self.saved).map_err(|msg| SessionError::Store(StoreError::Failed(msg)))
+    }
+
+    // Play a move of the player to move, then the reply of the computer
+    pub fn play(&mut self, mv: &str) -> Result<(), SessionError> {
+        let mut engine = self.engine()?;
+        if !engine.running() {
+            return Err(SessionError::Finished);
+        }
+        engine.play(mv).map_err(SessionError::Illegal)?;
+        engine.ai_reply();
+        self.saved = engine.saved();
+        Ok(())
+    }
+
+    // The game to record once it is over, with the game types the frontend posts
+    pub fn result(&self) -> Result<Option<SerializableGame>, SessionError> {
+        let winner = match self.engine()?.winner() {
+            Some(winner) => winner,
+            None => return Ok(None),
+        };
+        Ok(Some(SerializableGame {
+            gameType: engine::game_type(&self.saved.game, self.saved.with_ai),
+            Player1Name: self.saved.p1.clone(),
+            Player2Name: self.saved.p2.clone(),
+            WinnerName: winner,
+            rows: self.saved.rows as u32,
+            cols: self.saved.cols as u32,
+            moves: self.saved.moves.clone(),
+            first_player: self.saved.first_player,
+            variant: self.saved.variant.clone(),
+        }))
+    }
+
+    // Record the result once the game is over. A store that cannot take it now is
+    // tried again on the next request for the session
+    pub fn record(&mut self, store: &dyn GameStore) {
+        if self.game_id.is_some() {
+            return;
+        }
+        let recorded = match self.result() {
+            Ok(Some(game)) => store
+                .insert_game(game)
+                .map(Some)
+                .map_err(SessionError::from),
+            Ok(None) => Ok(None),
+            Err(err) => Err(err),
+        };
+        match recorded {
+            Ok(game_id) => self.game_id = game_id,
+            Err(err) => println!("Could not record session {}: {:?}", self.id, err),
+        }
+    }
+
+    pub fn state(&self) -> Result<SessionState, SessionError> {
+        let engine = self.engine()?;
+        let winner = engine.winner();
+        let state = match winner.as_deref() {
+            None => "running",
+            Some(engine::DRAW) => "draw",
+            Some(_) => "won",
+        };
+        Ok(SessionState {
+            id: self.id.clone(),
+            saved: self.saved.clone(),
+            board: engine.board(),
+            state: state.to_string(),
+            winner: winner.filter(|winner| winner != engine::DRAW),
+            to_move: engine.to_move(),
+            game_id: self.game_id.clone(),
+        })
+    }
+}
+
+struct Entry {
+    session: Arc<Mutex<Session>>,
+    used: Instant,
+}
+
+// Sessions in play, kept in memory until their game is recorded or they are left
+// alone too long. Each has its own lock so the computer thinking in one does not hold
+// up the others
+pub struct Sessions {
+    sessions: Mutex<HashMap<String, Entry>>,
+    ttl: Duration,
+    max_sessions: usize,
+}
+
+impl Default for Sessions {
+    fn default() -> Self {
+        Sessions::new(SESSION_TTL, MAX_SESSIONS)
+    }
+}
+
+impl Sessions {
+    pub fn new(ttl: Duration, max_sessions: usize) -> Self {
+        Sessions {
+            sessions: Mutex::new(HashMap::new()),
+            ttl,
+            max_sessions,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<HashMap<String, Entry>> {
+        match self.sessions.lock() {
+            Ok(sessions) => sessions,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    // Keep the session under a new id, after the ones that expired
+    pub fn insert(&self, mut session: Session) -> Result<Arc<Mutex<Session>>, SessionError> {
+        session.id = new_id()?.to_hex();
+        let id = session.id.clone();
+        let session = Arc::new(Mutex::new(session));
+
+        let now = Instant::now();
+        let mut sessions = self.lock();
+        let ttl = self.ttl;
+        sessions.retain(|_, entry| now.duration_since(entry.used) < ttl);
+        while sessions.len() >= self.max_sessions.max(1) {
+            let oldest = sessions
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(id, _)| id.clone());
+            match oldest {
+                Some(oldest) => sessions.remove(&oldest),
+                None => break,
+            };
+        }
+        sessions.insert(
+            id,
+            Entry {
+                session: session.clone(),
+                used: now,
+            },
+        );
+        Ok(session)
+    }
+
+    pub fn get(&self, id: &str) -> Result<Arc<Mutex<Session>>, SessionError> {
+        let now = Instant::now();
+        let mut sessions = self.lock();
+        match sessions.get_mut(id) {
+            Some(entry) if now.duration_since(entry.used) < self.ttl => {
+                entry.used = now;
+                Ok(entry.session.clone())
+            }
+            Some(_) => {
+                sessions.remove(id);
+                Err(SessionError::NotFound)
+            }
+            None => Err(SessionError::NotFound),
+        }
+    }
+
+    // Forget the session once its game is recorded, /games has it from then on
+    pub fn forget_recorded(&self, session: &Session) {
+        if session.game_id.is_some() {
+            self.lock().remove(&session.id);
+        }
+    }
+}
+
+pub fn lock_session(session: &Mutex<Session>) -> MutexGuard<Session> {
+    match session.lock() {
+        Ok(session) => session,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_session() -> Session {
+        Session::new(NewSession {
+            game: save::CONNECT_FOUR.to_string(),
+            rows: 6,
+            cols: 7,
+            p1: "Ann".to_string(),
+            p2: Some("Bob".to_string()),
+            max_ai_depth: None,
+            variant: None,
+        })
+        .unwrap()
+    }
+
+    fn id_of(session: &Arc<Mutex<Session>>) -> String {
+        lock_session(session).id.clone()
+    }
+
+    #[test]
+    fn player_names_are_checked() {
+        let mut new = NewSession {
+            game: save::CONNECT_FOUR.to_string(),
+            rows: 6,
+            cols: 7,
+            p1: "Draw".to_string(),
+            p2: None,
+            max_ai_depth: None,
+            variant: None,
+        };
+        assert!(Session::new(new.clone()).is_err());
+        new.p1 = "Ann".to_string();
+        new.p2 = Some(" draw ".to_string());
+        assert!(Session::new(new.clone()).is_err());
+        new.p2 = Some(" ann".to_string());
+        assert!(Session::new(new.clone()).is_err());
+        new.p2 = None;
+        new.p1 = "Computer".to_string();
+        assert!(Session::new(new.clone()).is_err());
+        new.p1 = "Ann".to_string();
+        new.p2 = Some("Bob".to_string());
+        assert!(Session::new(new).is_ok());
+    }
+
+    #[test]
+    fn toot_otto_searches_less_deep() {
+        let mut new = NewSession {
+            game: save::TOOT_OTTO.to_string(),
+            rows: 4,
+            cols: 6,
+            p1: "Ann".to_string(),
+            p2: None,
+            max_ai_depth: None,
+            variant: None,
+        };
+        assert_eq!(Session::new(new.clone()).unwrap().saved.max_ai_depth, 3);
+        new.max_ai_depth = Some(4);
+        match Session::new(new.clone()) {
+            Err(msg) => assert_eq!(msg, "max_ai_depth must be from 1 to 3"),
+            Ok(_) => panic!("a TOOT-OTTO session searches too deep"),
+        }
+        new.game = save::CONNECT_FOUR.to_string();
+        new.max_ai_depth = Some(6);
+        assert!(Session::new(new).is_ok());
+    }
+
+    #[test]
+    fn least_recently_used_session_makes_room() {
+        let sessions = Sessions::new(SESSION_TTL, 2);
+        let first = id_of(&sessions.insert(new_session()).unwrap());
+        let second = id_of(&sessions.insert(new_session()).unwrap());
+        sessions.get(&first).unwrap();
+        let third = id_of(&sessions.insert(new_session()).unwrap());
+
+        assert!(sessions.get(&first).is_ok());
+        assert!(sessions.get(&second).is_err());
+        assert!(sessions.get(&third).is_ok());
+    }
+
+    #[test]
+    fn idle_sessions_expire() {
+        let sessions = Sessions::new(Duration::from_secs(0), MAX_SESSIONS);
+        let id = id_of(&sessions.insert(new_session()).unwrap());
+        assert!(sessions.get(&id).is_err());
+    }
+
+    #[test]
+    fn recorded_sessions_are_forgotten() {
+        let store = crate::memory_store::MemoryStore::default();
+        let sessions = Sessions::default();
+        let session = sessions.insert(new_session()).unwrap();
+        let mut session = lock_session(&session);
+        for mv in ["0", "1", "0", "1", "0", "1"].iter() {
+            session.play(mv).unwrap();
+            session.record(&store);
+            sessions.forget_recorded(&session);
+            assert!(sessions.get(&session.id).is_ok());
+        }
+        session.play("0").unwrap();
+        session.record(&store);
+        sessions.forget_recorded(&session);
+        assert!(sessions.get(&session.id).is_err());
+        assert!(session.game_id.is_some());
+    }
+}
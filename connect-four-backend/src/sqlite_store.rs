@@ -0,0 +1,279 @@
+use crate::game::{Game, PlayerWins, SerializableGame};
+use crate::game_query::{GamePage, GameQuery};
+use crate::game_store::{new_id, GameStore, StoreError};
+use chrono::{TimeZone, Utc};
+use connect_four_cli::save::SavedVariant;
+use mongodb::oid::ObjectId;
+use rusqlite::{params, Connection, OptionalExtension, Row, ToSql, NO_PARAMS};
+use std::sync::{Mutex, MutexGuard};
+
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS games (
+    id TEXT PRIMARY KEY,
+    game_type TEXT NOT NULL,
+    player1_name TEXT NOT NULL,
+    player2_name TEXT NOT NULL,
+    winner_name TEXT NOT NULL,
+    game_date INTEGER NOT NULL
+)";
+
+// Columns added to the table since it was first created, with what older games get.
+// Moves are kept separated by spaces and the variant as JSON
+const ADDED_COLUMNS: &[(&str, &str)] = &[
+    ("num_rows", "INTEGER NOT NULL DEFAULT 0"),
+    ("num_cols", "INTEGER NOT NULL DEFAULT 0"),
+    ("moves", "TEXT NOT NULL DEFAULT ''"),
+    ("first_player", "INTEGER NOT NULL DEFAULT 1"),
+    ("variant", "TEXT"),
+];
+
+const SELECT_GAMES: &str =
+    "SELECT id, game_type, player1_name, player2_name, winner_name, game_date,
+    num_rows, num_cols, moves, first_player, variant FROM games";
+
+// Games in a SQLite file, for running the backend without a MongoDB server. Ids are
+// kept as the hex of an ObjectId and dates in milliseconds
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    // Open the file, creating it and the table when they do not exist
+    pub fn open(path: &str) -> Result<Self, String> {
+        let failed =
+            |e: rusqlite::Error| format!("Could not open the SQLite database {}: {}", path, e);
+        let conn = Connection::open(path).map_err(failed)?;
+        conn.execute(CREATE_TABLE, NO_PARAMS).map_err(failed)?;
+        add_columns(&conn).map_err(failed)?;
+        Ok(SqliteStore {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<Connection> {
+        match self.conn.lock() {
+            Ok(conn) => conn,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+// Bring a table made by an older version up to date
+fn add_columns(conn: &Connection) -> rusqlite::Result<()> {
+    let columns = conn
+        .prepare("PRAGMA table_info(games)")?
+        .query_map(NO_PARAMS, |row| row.get::<_, String>(1))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    for (name, definition) in ADDED_COLUMNS {
+        if !columns.iter().any(|column| column == name) {
+            conn.execute(
+                &format!("ALTER TABLE games ADD COLUMN {} {}", name, definition),
+                NO_PARAMS,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+// WHERE clause of the query and the values of its parameters
+fn games_filter(query: &GameQuery) -> (String, Vec<Box<dyn ToSql>>) {
+    let mut conditions = Vec::new();
+    let mut values: Vec<Box<dyn ToSql>> = Vec::new();
+    if let Some(game_type) = &query.game_type {
+        values.push(Box::new(game_type.clone()));
+        conditions.push(format!("game_type = ?{}", values.len()));
+    }
+    if let Some(player) = &query.player {
+        values.push(Box::new(player.clone()));
+        let n = values.len();
+        conditions.push(format!("(player1_name = ?{} OR player2_name = ?{})", n, n));
+    }
+    if let Some(winner) = &query.winner {
+        values.push(Box::new(winner.clone()));
+        conditions.push(format!("winner_name = ?{}", values.len()));
+    }
+    // LIKE ignores case, the other stores do not
+    if let Ok(Some(suffix)) = query.opponent_suffix() {
+        values.push(Box::new(suffix));
+        let n = values.len();
+        conditions.push(format!("substr(game_type, -length(?{})) = ?{}", n, n));
+    }
+    if let Some(from) = query.from {
+        values.push(Box::new(from));
+        conditions.push(format!("game_date >= ?{}", values.len()));
+    }
+    if let Some(to) = query.to {
+        values.push(Box::new(to));
+        conditions.push(format!("game_date < ?{}", values.len()));
+    }
+
+    if conditions.is_empty() {
+        (String::new(), values)
+    } else {
+        (format!(" WHERE {}", conditions.join(" AND ")), values)
+    }
+}
+
+fn variant_json(variant: &Option<SavedVariant>) -> Option<String> {
+    variant
+        .as_ref()
+        .map(|variant| serde_json::to_string(variant).unwrap())
+}
+
+fn failed(e: rusqlite::Error) -> StoreError {
+    StoreError::Failed(e.to_string())
+}
+
+fn game_of_row(row: &Row) -> rusqlite::Result<Game> {
+    let id: String = row.get(0)?;
+    let moves: String = row.get(8)?;
+    let variant: Option<String> = row.get(10)?;
+    Ok(Game {
+        id: ObjectId::with_string(&id).ok(),
+        gameType: row.get(1)?,
+        Player1Name: row.get(2)?,
+        Player2Name: row.get(3)?,
+        WinnerName: row.get(4)?,
+        GameDate: Utc.timestamp_millis(row.get(5)?),
+        rows: row.get(6)?,
+        cols: row.get(7)?,
+        moves: moves.split_whitespace().map(String::from).collect(),
+        first_player: row.get(9)?,
+        variant: variant.and_then(|variant| serde_json::from_str(&variant).ok()),
+    })
+}
+
+impl GameStore for SqliteStore {
+    fn games(&self, query: &GameQuery) -> Result<GamePage, StoreError> {
+        let (conditions, values) = games_filter(query);
+        let values = values
+            .iter()
+            .map(|value| value.as_ref())
+            .collect::<Vec<_>>();
+        let conn = self.lock();
+
+        let total: i64 = conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM games{}", conditions),
+                &values,
+                |row| row.get(0),
+            )
+            .map_err(failed)?;
+
+        let order = if query.newest_first() { "DESC" } else { "ASC" };
+        let limit = query.limit.map_or(-1, |limit| limit as i64);
+        let mut statement = conn
+            .prepare(&format!(
+                "{}{} ORDER BY game_date {}, rowid {} LIMIT {} OFFSET {}",
+                SELECT_GAMES,
+                conditions,
+                order,
+                order,
+                limit,
+                query.skip()
+            ))
+            .map_err(failed)?;
+        let games = statement
+            .query_map(&values, game_of_row)
+            .map_err(failed)?
+            .collect::<rusqlite::Result<Vec<Game>>>()
+            .map_err(failed)?;
+        Ok(GamePage {
+            games,
+            total: total as u64,
+        })
+    }
+
+    fn game(&self, id: ObjectId) -> Result<Option<Game>, StoreError> {
+        self.lock()
+            .query_row(
+                &format!("{} WHERE id = ?1", SELECT_GAMES),
+                params![id.to_hex()],
+                game_of_row,
+            )
+            .optional()
+            .map_err(failed)
+    }
+
+    fn insert_game(&self, game: SerializableGame) -> Result<ObjectId, StoreError> {
+        game.verify().map_err(StoreError::Invalid)?;
+        let id = new_id()?;
+        self.lock()
+            .execute(
+                "INSERT INTO games (id, game_type, player1_name, player2_name, winner_name, game_date,
+                 num_rows, num_cols, moves, first_player, variant)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    id.to_hex(),
+                    game.gameType,
+                    game.Player1Name,
+                    game.Player2Name,
+                    game.WinnerName,
+                    Utc::now().timestamp_millis(),
+                    game.rows,
+                    game.cols,
+                    game.moves.join(" "),
+                    game.first_player,
+                    variant_json(&game.variant)
+                ],
+            )
+            .map_err(failed)?;
+        Ok(id)
+    }
+
+    fn update_game(&self, id: ObjectId, mut game: Game) -> Result<Game, StoreError> {
+        SerializableGame::from(&game)
+            .verify()
+            .map_err(StoreError::Invalid)?;
+        let updated = self
+            .lock()
+            .execute(
+                "UPDATE games SET game_type = ?2, player1_name = ?3, player2_name = ?4,
+                 winner_name = ?5, game_date = ?6, num_rows = ?7, num_cols = ?8, moves = ?9,
+                 first_player = ?10, variant = ?11 WHERE id = ?1",
+                params![
+                    id.to_hex(),
+                    game.gameType,
+                    game.Player1Name,
+                    game.Player2Name,
+                    game.WinnerName,
+                    game.GameDate.timestamp_millis(),
+                    game.rows,
+                    game.cols,
+                    game.moves.join(" "),
+                    game.first_player,
+                    variant_json(&game.variant)
+                ],
+            )
+            .map_err(failed)?;
+        if updated == 0 {
+            return Err(StoreError::NotFound);
+        }
+        game.id = Some(id);
+        Ok(game)
+    }
+
+    fn delete_game(&self, id: ObjectId) -> Result<(), StoreError> {
+        self.lock()
+            .execute("DELETE FROM games WHERE id = ?1", params![id.to_hex()])
+            .map_err(failed)?;
+        Ok(())
+    }
+
+    fn wins(&self) -> Result<Vec<PlayerWins>, StoreError> {
+        let conn = self.lock();
+        let mut statement = conn
+            .prepare("SELECT winner_name, COUNT(*) FROM games GROUP BY winner_name")
+            .map_err(failed)?;
+        let wins = statement
+            .query_map(NO_PARAMS, |row| {
+                Ok(PlayerWins {
+                    _id: row.get(0)?,
+                    count: row.get(1)?,
+                })
+            })
+            .map_err(failed)?
+            .collect::<rusqlite::Result<Vec<PlayerWins>>>()
+            .map_err(failed);
+        wins
+    }
+}
@@ -0,0 +1,252 @@
+use crate::connect_four::{
+    Game as ConnectFourGame, SearchContext as ConnectFourSearch, State as ConnectFourState,
+};
+use crate::search::{Search, SearchGame};
+use crate::toot_otto::{
+    Game as TootOttoGame, Move as TootOttoMove, SearchContext as TootOttoSearch,
+    State as TootOttoState,
+};
+use std::time::{Duration, Instant};
+
+// Nodes searched between looks at the clock
+const NODES_PER_CHECK: u64 = 2000;
+
+// A position the analysis plays moves in and searches from
+pub trait Position: Clone {
+    type Move: Copy;
+    type Context: SearchGame<Move = Self::Move>;
+
+    fn legal_moves(&self) -> Vec<Self::Move>;
+
+    // Play mv, once it ends the game the result for the player that made it: 1 for a
+    // win, 0 for a draw and -1 for a loss
+    fn play(&mut self, mv: Self::Move) -> Option<i64>;
+
+    // Search for the player to move, max_depth as in Game::max_ai_depth
+    fn search(&self, max_depth: u32) -> Search<Self::Context>;
+
+    fn move_text(mv: Self::Move) -> String;
+}
+
+impl Position for ConnectFourGame {
+    type Move = usize;
+    type Context = ConnectFourSearch;
+
+    fn legal_moves(&self) -> Vec<usize> {
+        (0..self.grid.num_cols)
+            .filter(|col| self.grid.is_empty(0, *col))
+            .collect()
+    }
+
+    fn play(&mut self, col: usize) -> Option<i64> {
+        let player = self.player_name(self.current_player());
+        self.make_move(col).unwrap();
+        match self.state {
+            ConnectFourState::Running => None,
+            _ => Some(result(&self.winner, &player)),
+        }
+    }
+
+    fn search(&self, max_depth: u32) -> Search<ConnectFourSearch> {
+        let mut game = self.clone();
+        game.max_ai_depth = max_depth;
+        game.ai_search(game.player_move_translate() as i64)
+    }
+
+    fn move_text(col: usize) -> String {
+        col.to_string()
+    }
+}
+
+impl Position for TootOttoGame {
+    type Move = TootOttoMove;
+    type Context = TootOttoSearch;
+
+    fn legal_moves(&self) -> Vec<TootOttoMove> {
+        let chips = self.current_chips();
+        let mut moves = Vec::new();
+        for (chip_type, count) in chips.counts.iter() {
+            if *count == 0 {
+                continue;
+            }
+            for col in 0..self.grid.num_cols {
+                if self.grid.is_empty(0, col) {
+                    moves.push((*chip_type, col));
+                }
+            }
+        }
+        moves
+    }
+
+    fn play(&mut self, (chip_type, col): TootOttoMove) -> Option<i64> {
+        let player = self.player_name(self.current_player());
+        self.make_move(chip_type, col).unwrap();
+        match self.state {
+            TootOttoState::Running => None,
+            _ => Some(result(&self.winner, &player)),
+        }
+    }
+
+    fn search(&self, max_depth: u32) -> Search<TootOttoSearch> {
+        let mut game = self.clone();
+        game.max_ai_depth = max_depth;
+        game.ai_search()
+    }
+
+    fn move_text((chip_type, col): TootOttoMove) -> String {
+        format!("{}{}", chip_type, col)
+    }
+}
+
+fn result(winner: &str, player: &str) -> i64 {
+    if winner == "Draw" {
+        0
+    } else if winner == player {
+        1
+    } else {
+        -1
+    }
+}
+
+// What a move leads to for the player making it
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Outcome {
+    // Forced win (positive) or loss (negative) within that many moves of both players,
+    // counting the move itself
+    Forced(i64),
+
+    // The move ends the game in a draw
+    Draw,
+
+    // Evaluation at the end of the best line, higher is better
+    Score(i64),
+}
+
+impl Outcome {
+    // Higher is better for the player making the move, quick wins and slow losses first
+    fn rank(&self) -> i64 {
+        match *self {
+            Outcome::Forced(moves) if moves > 0 => 1_000_000_000 - moves,
+            Outcome::Forced(moves) => -1_000_000_000 - moves,
+            Outcome::Draw => 0,
+            Outcome::Score(value) => value.clamp(-900_000, 900_000),
+        }
+    }
+
+    fn is_decided(&self) -> bool {
+        !matches!(self, Outcome::Score(_))
+    }
+}
+
+pub struct MoveAnalysis {
+    pub mv: String,
+    pub outcome: Outcome,
+
+    // Best play after the move, starting with it
+    pub line: Vec<String>,
+}
+
+pub struct Analysis {
+    // Best move first
+    pub moves: Vec<MoveAnalysis>,
+
+    // Depth the moves were searched to, as in Game::max_ai_depth
+    pub depth: u32,
+    pub nodes: u64,
+    pub elapsed: Duration,
+}
+
+// How long to analyse for
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Limit {
+    Depth(u32),
+
+    // Deepen the search until the time is up, the last depth that was complete counts
+    Time(Duration),
+}
+
+// Every legal move of the position with its outcome and best line
+pub fn analyze<P: Position>(position: &P, limit: Limit) -> Analysis {
+    let start = Instant::now();
+    let mut nodes = 0;
+    let (mut depth, deadline) = match limit {
+        Limit::Depth(depth) => (depth.max(1), None),
+        Limit::Time(time) => (1, Some(start + time)),
+    };
+
+    // The first depth is finished even when the time is short
+    let mut moves = analyze_depth(position, depth, None, &mut nodes).unwrap();
+    if let Some(deadline) = deadline {
+        while !moves.iter().all(|analysis| analysis.outcome.is_decided()) {
+            match analyze_depth(position, depth + 1, Some(deadline), &mut nodes) {
+                Some(deeper) => {
+                    moves = deeper;
+                    depth += 1;
+                }
+                None => break,
+            }
+        }
+    }
+
+    Analysis {
+        moves,
+        depth,
+        nodes,
+        elapsed: start.elapsed(),
+    }
+}
+
+// The moves searched to depth, None when the deadline passed first
+fn analyze_depth<P: Position>(
+    position: &P,
+    depth: u32,
+    deadline: Option<Instant>,
+    nodes: &mut u64,
+) -> Option<Vec<MoveAnalysis>> {
+    let mut moves = Vec::new();
+    for mv in position.legal_moves() {
+        let mut child = position.clone();
+        let mut line = vec![P::move_text(mv)];
+        let outcome = match child.play(mv) {
+            Some(result) if result > 0 => Outcome::Forced(1),
+            Some(result) if result < 0 => Outcome::Forced(-1),
+            Some(_) => Outcome::Draw,
+            None => {
+                // The reply is searched one level shallower, so that the move and the
+                // search below it cover depth as a search of the position would
+                let mut search = child.search(depth - 1);
+                match deadline {
+                    Some(deadline) => {
+                        while !search.step(NODES_PER_CHECK) {
+                            if Instant::now() >= deadline {
+                                return None;
+                            }
+                        }
+                    }
+                    None => search.run(),
+                }
+                let stats = search.into_stats();
+                *nodes += stats.nodes;
+
+                // No reply at all, the game cannot go on
+                if stats.principal_variation.is_empty() {
+                    Outcome::Draw
+                } else {
+                    line.extend(stats.principal_variation.iter().map(|mv| P::move_text(*mv)));
+                    match stats.forced_result() {
+                        Some(moves) if moves > 0 => Outcome::Forced(-(moves + 1)),
+                        Some(moves) => Outcome::Forced(-moves + 1),
+                        None => Outcome::Score(-stats.value),
+                    }
+                }
+            }
+        };
+        moves.push(MoveAnalysis {
+            mv: P::move_text(mv),
+            outcome,
+            line,
+        });
+    }
+    moves.sort_by_key(|analysis| -analysis.outcome.rank());
+    Some(moves)
+}
@@ -0,0 +1,234 @@
+use crate::board;
+use std::str::FromStr;
+
+pub const USAGE: &str = "Usage: connect_four_cli [COMMAND] [OPTIONS]
+
+Without a command the interactive menu is shown.
+
+Commands:
+    play                 Start a game with the options below
+    analyze <POSITION>   Evaluate every legal move of a position given as moves, a
+                         board diagram or a file holding either
+    selfplay             Let the computer play both sides
+    replay <FILE|MOVES>  Step through a saved game or a move list with evaluations
+    batch [FILE]         Play the moves of a file or stdin and print the result as
+                         JSON, without prompts
+    resume <FILE>        Continue a game saved with save <file>
+    host [ADDR]          Play against a player that joins over the network, on
+                         0.0.0.0:7878 by default
+    join <ADDR>          Join a game hosted at an address such as 192.168.1.5:7878
+    history              List the games recorded by the backend
+    leaderboard          Rank the players by the games they won
+
+Options:
+    --game <GAME>        connect4 (default) or toot-otto
+    --rows <N>           Board rows, 6 by default
+    --cols <N>           Board columns, 7 by default
+    --ai-depth <N>       Search depth, play against the computer when given
+    --first <WHO>        human (default), computer or random, the host is the human
+                         and the guest the computer in network games
+    --seed <N>           Seed for the random choices the CLI makes
+    --eval <NAME>        chains or threats for Connect-4, patterns or chains for TOOT-OTTO
+    --words <P1,P2>      Custom TOOT-OTTO words
+    --letters <LETTERS>  Letters of the custom TOOT-OTTO words
+    --time <SECONDS>     Analyze for about this long instead of to --ai-depth
+    --verbose            Print search statistics after every AI move
+    --tui                Play in a full screen colour terminal UI
+    --backend <URL>      Record finished games at the backend such as
+                         http://localhost:8000, CONNECT_FOUR_BACKEND works as well
+    --help               Show this message
+
+Moves are columns counted from 0 such as \"3 3 4\", TOOT-OTTO moves are a letter
+and a column such as \"T3 O4\".
+
+A board diagram lists the rows from the top separated by / or new lines, with _ or .
+for an empty cell such as \"......./......./......./......./...Y.../..RRY..\". Connect-4
+chips are R and Y, TOOT-OTTO tiles are upper case for player one and lower case for
+player two such as \"....../....../....../...O../..Tot.\".";
+
+// Address a network game is hosted on without one on the command line
+pub const DEFAULT_ADDRESS: &str = "0.0.0.0:7878";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameKind {
+    ConnectFour,
+    TootOtto,
+}
+
+// Who drops the first chip in a game against the computer
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum First {
+    Human,
+    Computer,
+    Random,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Command {
+    // Numbered menus, the default without a command
+    Menu,
+    Play,
+    Analyze(String),
+    Selfplay,
+    Replay(String),
+
+    // File of moves, stdin without one
+    Batch(Option<String>),
+    Resume(String),
+    Host(String),
+    Join(String),
+    History,
+    Leaderboard,
+    Help,
+}
+
+#[derive(Clone, Debug)]
+pub struct Options {
+    pub game: GameKind,
+    pub rows: usize,
+    pub cols: usize,
+
+    // None plays human against human
+    pub ai_depth: Option<u32>,
+
+    // Seconds to analyze for, ai_depth is the fixed depth otherwise
+    pub time: Option<f64>,
+    pub first: First,
+    pub seed: Option<u64>,
+    pub evaluator: Option<String>,
+    pub words: Option<String>,
+    pub letters: Option<String>,
+    pub verbose: bool,
+
+    // Full screen terminal UI instead of the line prompts when playing
+    pub tui: bool,
+
+    // Backend URL finished games are posted to
+    pub backend: Option<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            game: GameKind::ConnectFour,
+            rows: 6,
+            cols: 7,
+            ai_depth: None,
+            time: None,
+            first: First::Human,
+            seed: None,
+            evaluator: None,
+            words: None,
+            letters: None,
+            verbose: false,
+            tui: false,
+            backend: None,
+        }
+    }
+}
+
+// Parse the arguments after the program name
+pub fn parse(args: &[String]) -> Result<(Command, Options), String> {
+    let mut options = Options::default();
+    let mut args = args.iter();
+
+    let mut command = None;
+    let mut positional = Vec::new();
+
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            if command.is_none() {
+                command = Some(arg.clone());
+            } else {
+                positional.push(arg.clone());
+            }
+            continue;
+        }
+
+        let mut value = |name: &str| match args.next() {
+            Some(value) => Ok(value.clone()),
+            None => Err(format!("{} expects a value", name)),
+        };
+        match arg.as_str() {
+            "--game" => {
+                options.game = match value(arg)?.as_str() {
+                    "connect4" | "connect-four" => GameKind::ConnectFour,
+                    "toot-otto" | "toot" => GameKind::TootOtto,
+                    x => return Err(format!("Unknown game {}", x)),
+                }
+            }
+            "--rows" => options.rows = number(arg, &value(arg)?)?,
+            "--cols" => options.cols = number(arg, &value(arg)?)?,
+            "--ai-depth" => options.ai_depth = Some(number(arg, &value(arg)?)?),
+            "--time" => match number::<f64>(arg, &value(arg)?)? {
+                time if time > 0.0 && time < 1e6 => options.time = Some(time),
+                time => return Err(format!("--time expects a positive number, got {}", time)),
+            },
+            "--first" => {
+                options.first = match value(arg)?.as_str() {
+                    "human" => First::Human,
+                    "computer" => First::Computer,
+                    "random" => First::Random,
+                    x => return Err(format!("Unknown first player {}", x)),
+                }
+            }
+            "--seed" => options.seed = Some(number(arg, &value(arg)?)?),
+            "--eval" => options.evaluator = Some(value(arg)?),
+            "--words" => options.words = Some(value(arg)?),
+            "--letters" => options.letters = Some(value(arg)?),
+            "--verbose" => options.verbose = true,
+            "--tui" => options.tui = true,
+            "--backend" => options.backend = Some(value(arg)?),
+            "--help" => return Ok((Command::Help, options)),
+            _ => return Err(format!("Unknown option {}", arg)),
+        }
+    }
+
+    board::check_size(options.rows, options.cols)?;
+
+    let mut moves = || match positional.pop() {
+        Some(moves) if positional.is_empty() => Ok(moves),
+        Some(_) => Err("Expected a single move list, quote it".to_string()),
+        None => Err("Expected a move list".to_string()),
+    };
+    let command = match command.as_deref() {
+        None => Command::Menu,
+        Some("play") => Command::Play,
+        Some("analyze") => Command::Analyze(moves()?),
+        Some("selfplay") => Command::Selfplay,
+        Some("replay") => Command::Replay(moves()?),
+        Some("batch") => Command::Batch(positional.pop()),
+        Some("resume") => match positional.pop() {
+            Some(path) => Command::Resume(path),
+            None => return Err("Expected the file to resume".to_string()),
+        },
+        Some("host") => Command::Host(
+            positional
+                .pop()
+                .unwrap_or_else(|| DEFAULT_ADDRESS.to_string()),
+        ),
+        Some("join") => match positional.pop() {
+            Some(addr) => Command::Join(addr),
+            None => return Err("Expected the address of the host".to_string()),
+        },
+        Some("history") => Command::History,
+        Some("leaderboard") => Command::Leaderboard,
+        Some("help") => Command::Help,
+        Some(x) => return Err(format!("Unknown command {}", x)),
+    };
+    match command {
+        Command::Analyze(_) | Command::Replay(_) => {}
+        _ if !positional.is_empty() => {
+            return Err(format!("Unexpected argument {}", positional[0]));
+        }
+        _ => {}
+    }
+
+    Ok((command, options))
+}
+
+fn number<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} expects a number, got {}", name, value))
+}
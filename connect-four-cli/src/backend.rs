@@ -0,0 +1,181 @@
+use crate::save::SavedVariant;
+use chrono::serde::ts_milliseconds;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+// Backend the results are posted to when --backend is not given
+pub const BACKEND_VAR: &str = "CONNECT_FOUR_BACKEND";
+
+// Backend history and leaderboard read without either, the one the web UI uses
+pub const DEFAULT_BACKEND: &str = "http://localhost:8000";
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// Result of a game as the web posts it, with the field names of connect_four_backend.
+// The backend adds the date and plays the moves again, turning down a result they do
+// not lead to
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SerializableGame {
+    pub gameType: String,
+    pub Player1Name: String,
+    pub Player2Name: String,
+    pub WinnerName: String,
+    pub rows: usize,
+    pub cols: usize,
+    pub moves: Vec<String>,
+    pub first_player: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<SavedVariant>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Game {
+    pub gameType: String,
+    pub Player1Name: String,
+    pub Player2Name: String,
+    pub WinnerName: String,
+    #[serde(with = "ts_milliseconds")]
+    pub GameDate: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PlayerWins {
+    pub _id: String,
+    pub count: u32,
+}
+
+// Client for the REST API of connect_four_backend. Plain HTTP is enough for the
+// backend, which does not serve HTTPS
+pub struct Backend {
+    url: String,
+    authority: String,
+    base_path: String,
+}
+
+impl Backend {
+    pub fn new(url: &str) -> Result<Self, String> {
+        let rest = match url.strip_prefix("http://") {
+            Some(rest) => rest,
+            None => return Err(format!("{} is not an http:// URL", url)),
+        };
+        let (authority, base_path) = match rest.find('/') {
+            Some(idx) => (&rest[..idx], rest[idx..].trim_end_matches('/')),
+            None => (rest, ""),
+        };
+        if authority.is_empty() {
+            return Err(format!("{} has no host", url));
+        }
+        Ok(Backend {
+            url: url.trim_end_matches('/').to_string(),
+            authority: authority.to_string(),
+            base_path: base_path.to_string(),
+        })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn insert_game(&self, game: &SerializableGame) -> Result<(), String> {
+        let body = serde_json::to_string(game).unwrap();
+        self.request("POST", "/games", Some(&body))?;
+        Ok(())
+    }
+
+    pub fn games(&self) -> Result<Vec<Game>, String> {
+        let body = self.request("GET", "/games", None)?;
+        serde_json::from_str(&body).map_err(|e| self.invalid("/games", e))
+    }
+
+    pub fn wins(&self) -> Result<Vec<PlayerWins>, String> {
+        let body = self.request("GET", "/wins", None)?;
+        serde_json::from_str(&body).map_err(|e| self.invalid("/wins", e))
+    }
+
+    fn invalid(&self, path: &str, e: serde_json::Error) -> String {
+        format!("Unexpected answer from {}{}: {}", self.url, path, e)
+    }
+
+    // Body of the answer to the request, an error unless the status is a success
+    fn request(&self, method: &str, path: &str, body: Option<&str>) -> Result<String, String> {
+        let failed = |e: std::io::Error| format!("Could not reach {}: {}", self.url, e);
+
+        let addr = if self.authority.contains(':') {
+            self.authority.clone()
+        } else {
+            format!("{}:80", self.authority)
+        };
+        let mut stream = TcpStream::connect(addr).map_err(failed)?;
+        stream.set_read_timeout(Some(TIMEOUT)).map_err(failed)?;
+        stream.set_write_timeout(Some(TIMEOUT)).map_err(failed)?;
+
+        let mut request = format!(
+            "{} {}{} HTTP/1.1\r\nHost: {}\r\nAccept: application/json\r\nConnection: close\r\n",
+            method, self.base_path, path, self.authority
+        );
+        if let Some(body) = body {
+            request += &format!(
+                "Content-Type: application/json\r\nContent-Length: {}\r\n",
+                body.len()
+            );
+        }
+        request += "\r\n";
+        request += body.unwrap_or("");
+        stream.write_all(request.as_bytes()).map_err(failed)?;
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).map_err(failed)?;
+
+        // Decoded only once the chunks are joined, a chunk may end inside a character
+        let (head, body) = match find(&response, b"\r\n\r\n") {
+            Some(idx) => (&response[..idx], &response[idx + 4..]),
+            None => return Err(format!("Unexpected answer from {}{}", self.url, path)),
+        };
+        let head = String::from_utf8_lossy(head);
+        let mut lines = head.lines();
+        let status = lines.next().unwrap_or("");
+        match status.split_whitespace().nth(1) {
+            Some(code) if code.starts_with('2') => {}
+            _ => return Err(format!("{}{} answered {}", self.url, path, status)),
+        }
+
+        let chunked = lines.any(|line| {
+            let line = line.to_lowercase();
+            line.starts_with("transfer-encoding:") && line.contains("chunked")
+        });
+        if chunked {
+            Ok(String::from_utf8_lossy(&dechunk(body)).into_owned())
+        } else {
+            Ok(String::from_utf8_lossy(body).into_owned())
+        }
+    }
+}
+
+// Body sent in chunks, each a hexadecimal size line followed by the data
+fn dechunk(mut body: &[u8]) -> Vec<u8> {
+    let mut data = Vec::new();
+    while let Some(idx) = find(body, b"\r\n") {
+        let line = String::from_utf8_lossy(&body[..idx]);
+        let size = line.split(';').next().unwrap_or("").trim();
+        let size = match usize::from_str_radix(size, 16) {
+            Ok(size) if size > 0 => size,
+            _ => break,
+        };
+        let start = idx + 2;
+        let end = start.saturating_add(size).min(body.len());
+        data.extend_from_slice(&body[start..end]);
+        body = body[end..].strip_prefix(b"\r\n").unwrap_or(&body[end..]);
+    }
+    data
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
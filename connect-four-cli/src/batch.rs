@@ -0,0 +1,236 @@
+use crate::args::{GameKind, Options};
+use crate::board::Player;
+use crate::connect_four::{self, Game as ConnectFourGame, State as ConnectFourState};
+use crate::save::{self, player_number};
+use crate::setup::{connect_four_game, toot_otto_game};
+use crate::toot_otto::{self, Game as TootOttoGame, State as TootOttoState};
+use rand::rngs::StdRng;
+use serde::Serialize;
+use std::fs;
+use std::io::{self, Read};
+
+// The first move that could not be played, index counts from 1
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct BatchError {
+    pub index: usize,
+    #[serde(rename = "move")]
+    pub mv: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct BatchWord {
+    pub player: u8,
+    pub word: String,
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+}
+
+// What batch prints. The board is a diagram analyze reads, rows from the top
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct BatchResult {
+    pub game: String,
+    pub rows: usize,
+    pub cols: usize,
+    pub moves: Vec<String>,
+    pub board: Vec<String>,
+
+    // running, won or draw
+    pub state: String,
+    pub winner: Option<String>,
+    pub to_move: Option<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<BatchWord>,
+    pub error: Option<BatchError>,
+}
+
+// Play the moves of a file, or stdin without one. Text after a # is a comment. An
+// illegal move stops the game and is given in the error of the result
+pub fn run(options: &Options, path: Option<&str>, rng: &mut StdRng) -> Result<BatchResult, String> {
+    let text = match path {
+        Some(path) if path != "-" => {
+            fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?
+        }
+        _ => {
+            let mut text = String::new();
+            io::stdin()
+                .read_to_string(&mut text)
+                .map_err(|e| format!("Could not read the moves: {}", e))?;
+            text
+        }
+    };
+    let tokens = text
+        .lines()
+        .map(|line| line.split('#').next().unwrap())
+        .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+        .filter(|token| !token.is_empty())
+        .collect::<Vec<&str>>();
+
+    // Both sides are played from the moves, never by the computer
+    let mut options = options.clone();
+    options.ai_depth = None;
+    Ok(match options.game {
+        GameKind::ConnectFour => connect_four(connect_four_game(&options, rng)?, &tokens),
+        GameKind::TootOtto => toot_otto(toot_otto_game(&options, rng)?, &tokens),
+    })
+}
+
+fn connect_four(mut game: ConnectFourGame, tokens: &[&str]) -> BatchResult {
+    let mut error = None;
+    for (idx, token) in tokens.iter().enumerate() {
+        let failed = |message: String| {
+            Some(BatchError {
+                index: idx + 1,
+                mv: token.to_string(),
+                message,
+            })
+        };
+        if game.state != ConnectFourState::Running {
+            error = failed("The game is over".to_string());
+            break;
+        }
+        let col = match connect_four::parse_moves(token) {
+            Ok(moves) => moves[0],
+            Err(msg) => {
+                error = failed(msg);
+                break;
+            }
+        };
+        if game.make_move(col).is_err() {
+            error = failed(if col >= game.grid.num_cols {
+                format!("Column {} is not on the board", col)
+            } else {
+                format!("Column {} is full", col)
+            });
+            break;
+        }
+    }
+
+    let board = (0..game.grid.num_rows)
+        .map(|r| {
+            (0..game.grid.num_cols)
+                .map(|c| match game.grid.get(r, c) {
+                    1 => 'R',
+                    -1 => 'Y',
+                    _ => '_',
+                })
+                .collect()
+        })
+        .collect();
+    let running = game.state == ConnectFourState::Running;
+    BatchResult {
+        game: save::CONNECT_FOUR.to_string(),
+        rows: game.grid.num_rows,
+        cols: game.grid.num_cols,
+        moves: game.history.iter().map(|col| col.to_string()).collect(),
+        board,
+        state: state(running, &game.winner),
+        winner: winner(running, &game.winner),
+        to_move: to_move(running, game.player_name(game.current_player())),
+        words: Vec::new(),
+        error,
+    }
+}
+
+fn toot_otto(mut game: TootOttoGame, tokens: &[&str]) -> BatchResult {
+    let mut error = None;
+    for (idx, token) in tokens.iter().enumerate() {
+        let failed = |message: String| {
+            Some(BatchError {
+                index: idx + 1,
+                mv: token.to_string(),
+                message,
+            })
+        };
+        if game.state != TootOttoState::Running {
+            error = failed("The game is over".to_string());
+            break;
+        }
+        let (chip_type, col) = match toot_otto::parse_moves(token) {
+            Ok(moves) => moves[0],
+            Err(msg) => {
+                error = failed(msg);
+                break;
+            }
+        };
+        let chips = game.current_chips();
+        if game.make_move(chip_type, col).is_err() {
+            error = failed(if col >= game.grid.num_cols {
+                format!("Column {} is not on the board", col)
+            } else if chips.counts.iter().all(|(letter, _)| *letter != chip_type) {
+                format!("{} is not a letter of this game", chip_type)
+            } else if chips.remaining(chip_type) == 0 {
+                format!("No {} tiles left", chip_type)
+            } else {
+                format!("Column {} is full", col)
+            });
+            break;
+        }
+    }
+
+    let board = (0..game.grid.num_rows)
+        .map(|r| {
+            (0..game.grid.num_cols)
+                .map(|c| match game.grid.get(r, c) {
+                    Some((Player::One, chip_type)) => chip_type.0.to_ascii_uppercase(),
+                    Some((Player::Two, chip_type)) => chip_type.0.to_ascii_lowercase(),
+                    None => '_',
+                })
+                .collect()
+        })
+        .collect();
+    let running = game.state == TootOttoState::Running;
+    BatchResult {
+        game: save::TOOT_OTTO.to_string(),
+        rows: game.grid.num_rows,
+        cols: game.grid.num_cols,
+        moves: game
+            .history
+            .iter()
+            .map(|(chip_type, col)| format!("{}{}", chip_type, col))
+            .collect(),
+        board,
+        state: state(running, &game.winner),
+        winner: winner(running, &game.winner),
+        to_move: to_move(running, game.player_name(game.current_player())),
+        words: game
+            .words
+            .iter()
+            .map(|word| BatchWord {
+                player: player_number(word.player),
+                word: word.word.clone(),
+                from: word.from,
+                to: word.to,
+            })
+            .collect(),
+        error,
+    }
+}
+
+fn state(running: bool, winner: &str) -> String {
+    if running {
+        "running"
+    } else if winner == "Draw" {
+        "draw"
+    } else {
+        "won"
+    }
+    .to_string()
+}
+
+fn winner(running: bool, winner: &str) -> Option<String> {
+    if running || winner == "Draw" {
+        None
+    } else {
+        Some(winner.to_string())
+    }
+}
+
+fn to_move(running: bool, player: String) -> Option<String> {
+    if running {
+        Some(player)
+    } else {
+        None
+    }
+}
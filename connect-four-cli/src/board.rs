@@ -0,0 +1,123 @@
+// Owner of a chip, player one moves first unless the game says otherwise
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Player {
+    One,
+    Two,
+}
+
+impl Player {
+    pub fn other(&self) -> Player {
+        match self {
+            Player::One => Player::Two,
+            Player::Two => Player::One,
+        }
+    }
+}
+
+// What a player asks for on their turn in the CLI, M is the move type of the game
+#[derive(Clone, PartialEq, Debug)]
+pub enum Turn<M> {
+    Move(M),
+    Undo,
+    Hint,
+    Save(String),
+    Quit,
+}
+
+// Board shared by the games, C is what a cell holds and C::default() an empty cell.
+// Row 0 is the top row, chips are dropped in from there
+#[derive(Clone)]
+pub struct Board<C> {
+    pub items: [C; 80],
+    pub num_rows: usize,
+    pub num_cols: usize,
+}
+
+impl<C: Copy + Default + PartialEq> Board<C> {
+    pub fn new(row_size: usize, col_size: usize) -> Self {
+        Board {
+            items: [C::default(); 80],
+            num_rows: row_size,
+            num_cols: col_size,
+        }
+    }
+
+    // Drop a chip into col, returns the row it landed in
+    pub fn insert_chip(&mut self, col: usize, cell: C) -> Result<usize, ()> {
+        for r in (0..self.num_rows).rev() {
+            if self.is_empty(r, col) {
+                self.set(r, col, cell);
+                return Ok(r);
+            }
+        }
+        return Err(());
+    }
+
+    // Take the top chip out of col, returns what it held
+    pub fn remove_chip(&mut self, col: usize) -> Result<C, ()> {
+        for r in 0..self.num_rows {
+            if !self.is_empty(r, col) {
+                let cell = self.get(r, col);
+                self.set(r, col, C::default());
+                return Ok(cell);
+            }
+        }
+        return Err(());
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> C {
+        self.items[col * self.num_rows + (self.num_rows - 1 - row)]
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, val: C) {
+        self.items[col * self.num_rows + (self.num_rows - 1 - row)] = val;
+    }
+
+    pub fn is_empty(&self, row: usize, col: usize) -> bool {
+        self.get(row, col) == C::default()
+    }
+}
+
+// The board is stored in 80 cells and needs room for a line of four
+pub fn check_size(rows: usize, cols: usize) -> Result<(), String> {
+    let cells = rows.checked_mul(cols);
+    if rows < 4 || cols < 4 || cells.filter(|cells| *cells <= 80).is_none() {
+        return Err("The board must be at least 4 x 4 and have at most 80 cells".to_string());
+    }
+    Ok(())
+}
+
+// Cells of a board diagram such as "......./...X.../..OX..." given row by row from the
+// top, rows are separated by / or new lines and _ or . is an empty cell. Spaces are
+// ignored so that a printed board can be pasted. Chips must rest on the ones below
+pub fn diagram_rows(diagram: &str) -> Result<Vec<Vec<char>>, String> {
+    let rows = diagram
+        .split(['/', '\n'])
+        .map(|row| {
+            row.chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<Vec<char>>()
+        })
+        .filter(|row| !row.is_empty())
+        .collect::<Vec<Vec<char>>>();
+
+    let num_rows = rows.len();
+    let num_cols = rows.first().map_or(0, |row| row.len());
+    if rows.iter().any(|row| row.len() != num_cols) {
+        return Err("Every row of the diagram must have the same length".to_string());
+    }
+    check_size(num_rows, num_cols)?;
+
+    for pair in rows.windows(2) {
+        for (col, (above, below)) in pair[0].iter().zip(pair[1].iter()).enumerate() {
+            if is_empty_cell(*below) && !is_empty_cell(*above) {
+                return Err(format!("The chip in column {} is floating", col));
+            }
+        }
+    }
+    Ok(rows)
+}
+
+pub fn is_empty_cell(cell: char) -> bool {
+    cell == '_' || cell == '.'
+}
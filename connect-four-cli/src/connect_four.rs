@@ -1,18 +1,33 @@
+use crate::board::{diagram_rows, is_empty_cell, Board, Player, Turn};
+use crate::search::{Search, SearchGame, SearchStats};
 use rand::Rng;
-use std::cmp::{max, min};
 use std::fmt;
 
 pub trait GameEvents {
     fn introduction(&self);
     fn show_grid(&self, grid: &Grid);
     fn player_turn_message(&self, p1_turn: bool);
-    fn player_turn(&self, col_size: usize) -> Result<usize, ()>;
+    fn player_turn(&self, col_size: usize) -> Turn<usize>;
     fn selected_column(&self, player: String, col: usize);
     fn animate_chip(&self);
+    fn search_stats(&self, stats: &SearchStats<usize>);
+    fn hint(&self, col: usize, stats: &SearchStats<usize>);
+    fn moves_undone(&self, count: usize);
+    fn save_game(&self, path: &str, game: &Game);
     fn invalid_move(&self);
     fn game_over(&self, winner: String);
 }
 
+// Position evaluators the AI can use at the leaves of the search
+#[derive(Clone, Copy, PartialEq)]
+pub enum Evaluator {
+    // Sum of cubed chain sums over every window
+    Chains,
+
+    // Open windows only, with playable threats and odd/even threat parity
+    Threats,
+}
+
 #[derive(Clone, PartialEq)]
 pub enum State {
     Done,
@@ -35,6 +50,16 @@ pub struct Game {
     pub winner: String,
     pub p_move: i64,
     pub max_ai_depth: u32,
+    pub evaluator: Evaluator,
+
+    // Player that drops the first chip, red is always player one
+    pub first_player: Player,
+
+    // Player the computer plays when with_ai is set
+    pub ai_player: Player,
+
+    // Columns played so far, in order
+    pub history: Vec<usize>,
 }
 
 impl Game {
@@ -56,6 +81,10 @@ impl Game {
             winner: "".to_string(),
             p_move: 0,
             max_ai_depth: max_depth,
+            evaluator: Evaluator::Chains,
+            first_player: Player::One,
+            ai_player: Player::Two,
+            history: Vec::new(),
         };
         if with_ai {
             game.p2 = "Computer".to_string();
@@ -64,6 +93,28 @@ impl Game {
         game
     }
 
+    // Let the computer play player instead of player two, the names follow the sides
+    pub fn set_ai_player(&mut self, player: Player) {
+        if player != self.ai_player {
+            std::mem::swap(&mut self.p1, &mut self.p2);
+            self.ai_player = player;
+        }
+    }
+
+    pub fn current_player(&self) -> Player {
+        if (self.p_move % 2) == 0 {
+            return self.first_player;
+        }
+        return self.first_player.other();
+    }
+
+    pub fn player_name(&self, player: Player) -> String {
+        match player {
+            Player::One => self.p1.clone(),
+            Player::Two => self.p2.clone(),
+        }
+    }
+
     #[allow(dead_code)] // Used by web
     pub fn start_game(&mut self) {
         self.state = State::Running;
@@ -71,40 +122,58 @@ impl Game {
 
     pub fn start_game_cli<H: GameEvents>(&mut self, handler: H) {
         handler.introduction();
-        let mut p1_turn = true;
         let col_size = self.grid.num_cols;
         while self.state == State::Running {
+            let player = self.current_player();
             handler.show_grid(&self.grid);
-            handler.player_turn_message(p1_turn);
-            if !p1_turn && self.with_ai {
-                let col_num = self.ai_move_val(-1);
+            handler.player_turn_message(player == Player::One);
+            if self.with_ai && player == self.ai_player {
                 let grid_val = self.player_move_translate();
+                let (col_num, stats) = self.ai_move_val(grid_val as i64);
+                handler.search_stats(&stats);
                 if self.grid.insert_chip(col_num, grid_val).is_err() {
                     continue;
                 }
                 self.p_move += 1;
-                handler.selected_column(self.p1.clone(), col_num);
-                p1_turn = !p1_turn;
+                self.history.push(col_num);
+                handler.selected_column(self.player_name(player), col_num);
             } else {
-                let sel_col = handler.player_turn(col_size);
-                if sel_col.is_ok() {
-                    let col_num = sel_col.unwrap();
-                    let grid_val = self.player_move_translate();
-                    let insert_result = self.grid.insert_chip(col_num, grid_val);
-                    if insert_result.is_err() {
-                        handler.invalid_move();
+                match handler.player_turn(col_size) {
+                    Turn::Move(col_num) => {
+                        let grid_val = self.player_move_translate();
+                        let insert_result = self.grid.insert_chip(col_num, grid_val);
+                        if insert_result.is_err() {
+                            handler.invalid_move();
+                            continue;
+                        }
+                        self.p_move += 1;
+                        self.history.push(col_num);
+                        handler.selected_column(self.player_name(player), col_num);
+                    }
+                    Turn::Undo => {
+                        // Against the computer take back its reply as well
+                        let mut count = 0;
+                        while self.undo_move().is_ok() {
+                            count += 1;
+                            if !self.with_ai || self.current_player() != self.ai_player {
+                                break;
+                            }
+                        }
+                        handler.moves_undone(count);
                         continue;
                     }
-                    self.p_move += 1;
-                    if p1_turn {
-                        handler.selected_column(self.p1.clone(), col_num);
-                    } else {
-                        handler.selected_column(self.p2.clone(), col_num);
+                    Turn::Hint => {
+                        let (col_num, stats) =
+                            self.ai_move_val(self.player_move_translate() as i64);
+                        handler.hint(col_num, &stats);
+                        continue;
                     }
-                } else {
-                    continue;
+                    Turn::Save(path) => {
+                        handler.save_game(&path, self);
+                        continue;
+                    }
+                    Turn::Quit => return,
                 }
-                p1_turn = !p1_turn;
             }
             let result = self.check_win();
             if result.is_some() {
@@ -118,7 +187,7 @@ impl Game {
                     handler.game_over(self.winner.clone());
                 } else if winner == 0 {
                     self.winner = "Draw".to_string();
-                    println!("Draw");
+                    handler.game_over(self.winner.clone());
                 }
                 self.state = State::Done;
             }
@@ -126,14 +195,17 @@ impl Game {
     }
 
     pub fn player_move_translate(&self) -> i32 {
-        if (self.p_move % 2) == 0 {
-            return 1;
+        match self.current_player() {
+            Player::One => 1,
+            Player::Two => -1,
         }
-        return -1;
     }
 
     #[allow(dead_code)] // Used by web
     pub fn make_move(&mut self, col_num: usize) -> Result<(usize, usize), ()> {
+        if col_num >= self.grid.num_cols {
+            return Err(());
+        }
         let grid_val = self.player_move_translate();
 
         let insert_result = self.grid.insert_chip(col_num, grid_val);
@@ -142,6 +214,7 @@ impl Game {
         }
 
         self.p_move += 1;
+        self.history.push(col_num);
 
         let result = self.check_win();
         if result.is_some() {
@@ -159,6 +232,72 @@ impl Game {
         return Ok((insert_result.unwrap(), (self.p_move - 1) as usize));
     }
 
+    // Take back the last move, returns its column. A game that move ended goes on
+    pub fn undo_move(&mut self) -> Result<usize, ()> {
+        let col_num = match self.history.pop() {
+            Some(col) => col,
+            None => return Err(()),
+        };
+        self.grid.remove_chip(col_num)?;
+        self.p_move -= 1;
+        self.winner = "".to_string();
+        self.state = State::Running;
+        Ok(col_num)
+    }
+
+    // Set up the position of a board diagram, see board::diagram_rows. R or X is a chip of
+    // player one and Y or O one of player two. The player with fewer chips moves next,
+    // player one when both have as many. The moves that led there are not known
+    pub fn load_diagram(&mut self, diagram: &str) -> Result<(), String> {
+        let rows = diagram_rows(diagram)?;
+        let mut grid = Grid::new(rows.len(), rows[0].len());
+        let (mut p1_count, mut p2_count) = (0, 0);
+        for (r, row) in rows.iter().enumerate() {
+            for (c, cell) in row.iter().enumerate() {
+                let value = match cell.to_ascii_uppercase() {
+                    'R' | 'X' => 1,
+                    'Y' | 'O' => -1,
+                    x if is_empty_cell(x) => 0,
+                    x => return Err(format!("{} is not a chip, use R or Y", x)),
+                };
+                grid.set(r, c, value);
+                match value {
+                    1 => p1_count += 1,
+                    -1 => p2_count += 1,
+                    _ => {}
+                }
+            }
+        }
+
+        self.first_player = if p1_count == p2_count || p1_count == p2_count + 1 {
+            Player::One
+        } else if p2_count == p1_count + 1 {
+            Player::Two
+        } else {
+            return Err(format!(
+                "Player one has {} chips and player two {}, that is not a position of a game",
+                p1_count, p2_count
+            ));
+        };
+        self.grid = grid;
+        self.p_move = p1_count + p2_count;
+        self.history.clear();
+        self.state = State::Running;
+        self.winner = "".to_string();
+
+        if let Some(winner) = self.check_win() {
+            self.winner = if winner > 0 {
+                self.p1.clone()
+            } else if winner < 0 {
+                self.p2.clone()
+            } else {
+                "Draw".to_string()
+            };
+            self.state = State::Done;
+        }
+        Ok(())
+    }
+
     fn check_win(&self) -> Option<i64> {
         let mut temp_r: i64;
         let mut temp_b: i64;
@@ -220,8 +359,31 @@ impl Game {
     }
 
     #[allow(dead_code)] // Used by web
-    pub fn ai_make_move(&mut self) -> Result<(usize, usize, usize), ()> {
-        let mut col_num = self.ai_move_val(-1);
+    pub fn ai_make_move(&mut self) -> Result<(usize, usize, usize, SearchStats<usize>), ()> {
+        let mut search = self.ai_search(self.player_move_translate() as i64);
+        search.run();
+        self.ai_apply_move(search)
+    }
+
+    // Search for the AI move without running it, the caller steps the search a few
+    // nodes at a time and hands it to ai_apply_move once it is done
+    pub fn ai_search(&self, ai_move_val: i64) -> Search<SearchContext> {
+        let ctx = SearchContext::new(self.clone(), ai_move_val);
+        Search::new(ctx, self.grid.clone())
+    }
+
+    #[allow(dead_code)] // Used by web
+    pub fn ai_apply_move(
+        &mut self,
+        search: Search<SearchContext>,
+    ) -> Result<(usize, usize, usize, SearchStats<usize>), ()> {
+        let stats = search.into_stats();
+
+        // No legal move, fall back to a random column
+        let mut col_num = match stats.principal_variation.first() {
+            Some(col) => *col,
+            None => 0,
+        };
         let grid_val = self.player_move_translate();
 
         let mut insert_result = self.grid.insert_chip(col_num, grid_val);
@@ -234,6 +396,7 @@ impl Game {
         }
 
         self.p_move += 1;
+        self.history.push(col_num);
 
         let result = self.check_win();
         if result.is_some() {
@@ -248,14 +411,25 @@ impl Game {
             self.state = State::Done;
         }
 
-        return Ok((insert_result.unwrap(), (self.p_move - 1) as usize, col_num));
+        return Ok((
+            insert_result.unwrap(),
+            (self.p_move - 1) as usize,
+            col_num,
+            stats,
+        ));
     }
 
-    fn ai_move_val(&self, ai_move_val: i64) -> usize {
-        let state = &self.grid.clone();
-        let choice_val = self.ai_max_state(&state, 0, -100000000007, 100000000007, ai_move_val);
-        let choice = choice_val.1;
-        return choice as usize;
+    fn ai_move_val(&self, ai_move_val: i64) -> (usize, SearchStats<usize>) {
+        let mut search = self.ai_search(ai_move_val);
+        search.run();
+        let stats = search.into_stats();
+
+        // No legal move, let the caller fall back to a random column
+        let choice = match stats.principal_variation.first() {
+            Some(col) => *col,
+            None => 0,
+        };
+        return (choice, stats);
     }
 
     fn ai_check_state(state: &Grid) -> (i64, i64) {
@@ -311,15 +485,138 @@ impl Game {
         return (win_val, chain_val);
     }
 
-    fn ai_value(
-        &self,
-        state: &Grid,
-        depth: u32,
-        alpha: i64,
-        beta: i64,
-        ai_move_val: i64,
-    ) -> (i64, i64) {
-        let val = Game::ai_check_state(&state);
+    fn ai_check_state_threats(&self, state: &Grid) -> (i64, i64) {
+        let num_rows = state.num_rows;
+        let num_cols = state.num_cols;
+
+        let mut win_val: i64 = 0;
+        let mut score: i64 = 0;
+
+        // Empty cells that would complete a window, per player (index 0 for 1, 1 for -1)
+        let mut threats: [Vec<(usize, usize)>; 2] = [Vec::new(), Vec::new()];
+
+        // Only full windows can ever be completed, directions are right, down,
+        // down-right and up-right
+        let directions: [(i64, i64); 4] = [(0, 1), (1, 0), (1, 1), (-1, 1)];
+
+        for i in 0..num_rows as i64 {
+            for j in 0..num_cols as i64 {
+                for (di, dj) in directions.iter() {
+                    let end_i = i + 3 * di;
+                    let end_j = j + 3 * dj;
+                    if end_i < 0 || end_i >= num_rows as i64 || end_j >= num_cols as i64 {
+                        continue;
+                    }
+
+                    let mut count_p1 = 0;
+                    let mut count_p2 = 0;
+                    let mut empty = (0, 0);
+                    for k in 0..4 {
+                        let row = (i + k * di) as usize;
+                        let col = (j + k * dj) as usize;
+                        match state.get(row, col) {
+                            1 => count_p1 += 1,
+                            -1 => count_p2 += 1,
+                            _ => empty = (row, col),
+                        }
+                    }
+
+                    // Windows holding chips of both players are dead
+                    if count_p1 > 0 && count_p2 > 0 {
+                        continue;
+                    }
+
+                    if count_p1 == 4 {
+                        win_val = 4;
+                    } else if count_p2 == 4 {
+                        win_val = -4;
+                    } else if count_p1 == 3 {
+                        threats[0].push(empty);
+                    } else if count_p2 == 3 {
+                        threats[1].push(empty);
+                    }
+
+                    score += Game::ai_window_score(count_p1);
+                    score -= Game::ai_window_score(count_p2);
+                }
+            }
+        }
+
+        // The first player moves when both players have played the same number of chips
+        let chips = state.items[0..num_rows * num_cols]
+            .iter()
+            .filter(|chip| **chip != 0)
+            .count();
+        let first_side = if self.first_player == Player::One {
+            0
+        } else {
+            1
+        };
+        let p1_to_move = (chips % 2 == 0) == (first_side == 0);
+
+        for (side, side_threats) in threats.iter_mut().enumerate() {
+            side_threats.sort();
+            side_threats.dedup();
+
+            let sign = if side == 0 { 1 } else { -1 };
+            let to_move = (side == 0) == p1_to_move;
+            let mut playable = 0;
+
+            for (row, col) in side_threats.iter() {
+                if *row == num_rows - 1 || state.get(row + 1, *col) != 0 {
+                    playable += 1;
+                    continue;
+                }
+
+                // Counted from the bottom, the first player wants threats on odd rows
+                // and the second player on even rows, zugzwang forces the other player
+                // to fill the cell below once the rest of the board is full
+                let odd_row = (num_rows - row) % 2 == 1;
+                if odd_row == (side == first_side) {
+                    score += sign * 120;
+                } else {
+                    score += sign * 40;
+                }
+            }
+
+            // A playable threat wins for the player to move, otherwise two of them
+            // cannot both be blocked
+            if (to_move && playable > 0) || playable > 1 {
+                score += sign * 50000;
+            } else {
+                score += sign * 60 * playable;
+            }
+        }
+
+        return (win_val, score);
+    }
+
+    fn ai_window_score(count: i64) -> i64 {
+        match count {
+            1 => 1,
+            2 => 8,
+            3 => 32,
+            _ => 0,
+        }
+    }
+
+    fn ai_evaluate(&self, state: &Grid) -> (i64, i64) {
+        match self.evaluator {
+            Evaluator::Chains => Game::ai_check_state(state),
+            Evaluator::Threats => self.ai_check_state_threats(state),
+        }
+    }
+
+    // Score of a position the search reached, None if it has to look further
+    fn ai_leaf_value(&self, state: &Grid, depth: u32, ai_move_val: i64) -> Option<i64> {
+        let val = self.ai_evaluate(&state);
+
+        // A full board without a line of four is a draw, there are no moves to search
+        let board_full = (0..state.num_cols).all(|c| state.get(0, c) != 0);
+        if board_full && val.0.abs() != 4 {
+            return Some(0);
+        }
+
         if depth >= self.max_ai_depth {
             let mut ret_value;
             let win_val = val.0;
@@ -333,119 +630,113 @@ impl Game {
             }
             ret_value -= (depth * depth) as i64;
 
-            return (ret_value, -1);
+            return Some(ret_value);
         }
 
         let win = val.0;
         if win == 4 * ai_move_val {
-            return ((999999 - depth * depth) as i64, -1);
+            return Some((999999 - depth * depth) as i64);
         }
         if win == 4 * ai_move_val * -1 {
-            return (999999 * -1 - ((depth * depth) as i64), -1);
+            return Some(999999 * -1 - ((depth * depth) as i64));
         }
 
-        if depth % 2 == 0 {
-            return self.ai_min_state(state, depth + 1, alpha, beta, ai_move_val);
-        }
-        return self.ai_max_state(state, depth + 1, alpha, beta, ai_move_val);
+        None
     }
 
-    fn ai_max_state(
+    // Legal columns for the player dropping value, most promising first
+    fn ai_order_moves(
         &self,
         state: &Grid,
         depth: u32,
-        alpha: i64,
-        beta: i64,
-        ai_move_val: i64,
-    ) -> (i64, i64) {
-        let mut v: i64 = -100000000007;
-        let mut _move: i64 = -1;
-        let mut temp_val: (i64, i64);
-        let mut temp_state: Grid;
-        let mut move_queue: Vec<usize> = Vec::new();
-        let mut alpha = alpha;
-
-        for j in 0..self.grid.num_cols {
-            let temp_state_opt = self.ai_fill_map(state, j, ai_move_val);
-            if temp_state_opt.is_some() {
-                temp_state = temp_state_opt.unwrap();
-                temp_val = self.ai_value(&temp_state, depth, alpha, beta, ai_move_val);
-
-                if temp_val.0 > v {
-                    v = temp_val.0;
-                    _move = j as i64;
-                    move_queue.clear();
-                    move_queue.push(j);
-                } else if temp_val.0 == v {
-                    move_queue.push(j);
+        value: i64,
+        ctx: &SearchContext,
+    ) -> Vec<usize> {
+        let num_cols = self.grid.num_cols;
+        let mut moves = (0..num_cols)
+            .filter(|col| state.get(0, *col) == 0)
+            .collect::<Vec<usize>>();
+
+        // Every score drops with depth, so winning now is the best the AI can do. The
+        // opponent scores deeper losses of the AI lower and has to search on
+        let maximizing = value == ctx.ai_move_val;
+        if maximizing {
+            for col in moves.iter() {
+                if Game::ai_is_winning_move(state, *col, value) {
+                    return vec![*col];
                 }
-
-                if v > beta {
-                    _move = Game::choose(move_queue) as i64;
-                    return (v, _move as i64);
-                }
-                alpha = max(alpha, v);
             }
         }
 
-        if move_queue.len() == 0 {
-            (v, -1)
-        } else {
-            _move = Game::choose(move_queue) as i64;
-            (v, _move as i64)
+        // Anything but a block or a win lets the AI win on the reply, which the
+        // opponent never prefers as long as the reply is still searched
+        if !maximizing && depth < self.max_ai_depth {
+            let blocks = moves
+                .iter()
+                .filter(|col| {
+                    Game::ai_is_winning_move(state, **col, -value)
+                        || Game::ai_is_winning_move(state, **col, value)
+                })
+                .cloned()
+                .collect::<Vec<usize>>();
+            if !blocks.is_empty() {
+                return blocks;
+            }
         }
-    }
 
-    fn choose(choice: Vec<usize>) -> usize {
-        let mut rng = rand::thread_rng();
-        let rand_idx = rng.gen_range(0, choice.len());
-        return choice[rand_idx as usize];
+        // Killer moves, then history, then closest to the centre
+        let killers = ctx.killers(depth);
+        moves.sort_by_key(|col| {
+            let killer_rank = match killers.iter().position(|killer| *killer == Some(*col)) {
+                Some(rank) => rank,
+                None => killers.len(),
+            };
+            let centre_distance = (2 * *col as i64 - (num_cols as i64 - 1)).abs();
+            (
+                killer_rank,
+                -(ctx.history(value, *col) as i64),
+                centre_distance,
+            )
+        });
+        return moves;
     }
 
-    fn ai_min_state(
-        &self,
-        state: &Grid,
-        depth: u32,
-        alpha: i64,
-        beta: i64,
-        ai_move_val: i64,
-    ) -> (i64, i64) {
-        let mut v: i64 = 100000000007;
-        let mut _move: i64 = -1;
-        let mut temp_val: (i64, i64);
-        let mut temp_state: Grid;
-        let mut move_queue: Vec<usize> = Vec::new();
-        let mut beta = beta;
-
-        for j in 0..self.grid.num_cols {
-            let temp_state_opt = self.ai_fill_map(state, j, ai_move_val * -1);
-            if temp_state_opt.is_some() {
-                temp_state = temp_state_opt.unwrap();
-                temp_val = self.ai_value(&temp_state, depth, alpha, beta, ai_move_val);
-
-                if temp_val.0 < v {
-                    v = temp_val.0;
-                    _move = j as i64;
-                    move_queue.clear();
-                    move_queue.push(j);
-                } else if temp_val.0 == v {
-                    move_queue.push(j);
-                }
-
-                if v < alpha {
-                    _move = Game::choose(move_queue) as i64;
-                    return (v, _move as i64);
-                }
-                beta = min(beta, v);
+    // Whether dropping value into column connects four through the new chip
+    fn ai_is_winning_move(state: &Grid, column: usize, value: i64) -> bool {
+        let mut row = None;
+        for r in (0..state.num_rows).rev() {
+            if state.get(r, column) == 0 {
+                row = Some(r);
+                break;
             }
         }
+        let row = match row {
+            Some(r) => r as i64,
+            None => return false,
+        };
 
-        if move_queue.len() == 0 {
-            (v, -1)
-        } else {
-            _move = Game::choose(move_queue) as i64;
-            return (v, _move as i64);
+        let directions: [(i64, i64); 4] = [(0, 1), (1, 0), (1, 1), (-1, 1)];
+        for (di, dj) in directions.iter() {
+            let mut count = 1;
+            for sign in [-1, 1].iter() {
+                let mut i = row + sign * di;
+                let mut j = column as i64 + sign * dj;
+                while i >= 0
+                    && i < state.num_rows as i64
+                    && j >= 0
+                    && j < state.num_cols as i64
+                    && state.get(i as usize, j as usize) as i64 == value
+                {
+                    count += 1;
+                    i += sign * di;
+                    j += sign * dj;
+                }
+            }
+            if count >= 4 {
+                return true;
+            }
         }
+        return false;
     }
 
     fn ai_fill_map(&self, state: &Grid, column: usize, value: i64) -> Option<Grid> {
@@ -470,48 +761,106 @@ impl Game {
     }
 }
 
-#[derive(Clone)]
-pub struct Grid {
-    pub items: [i32; 80],
-    pub num_rows: usize,
-    pub num_cols: usize,
+// Columns of a move list such as "3 3 4" or "3,3,4", counted from 0
+pub fn parse_moves(moves: &str) -> Result<Vec<usize>, String> {
+    moves
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            token
+                .parse()
+                .map_err(|_| format!("{} is not a column", token))
+        })
+        .collect()
 }
 
-impl Grid {
-    pub fn new(row_size: usize, col_size: usize) -> Self {
-        let mut grid = Grid {
-            items: [0; 80],
-            num_rows: row_size,
-            num_cols: col_size,
-        };
-        for x in 0..(row_size * col_size) {
-            grid.items[x] = 0;
+// Bookkeeping for a single AI search, move ordering heuristics live here so that
+// they are shared by every node of the tree
+pub struct SearchContext {
+    game: Game,
+    ai_move_val: i64,
+
+    // Two moves per depth that recently caused a cutoff
+    killers: Vec<[Option<usize>; 2]>,
+
+    // Cutoff counts per player and column, player 1 first
+    history: Vec<u64>,
+    num_cols: usize,
+}
+
+impl SearchContext {
+    fn new(game: Game, ai_move_val: i64) -> Self {
+        let max_depth = game.max_ai_depth;
+        let num_cols = game.grid.num_cols;
+        SearchContext {
+            game,
+            ai_move_val,
+            killers: vec![[None, None]; max_depth as usize + 2],
+            history: vec![0; 2 * num_cols],
+            num_cols,
         }
-        grid
     }
 
-    pub fn insert_chip(&mut self, col: usize, grid_val: i32) -> Result<usize, ()> {
-        for r in (0..self.num_rows).rev() {
-            match self.get(r, col) {
-                0 => {
-                    self.set(r, col, grid_val as i32);
-                    return Ok(r);
-                }
-                _ => {}
-            }
+    fn killers(&self, depth: u32) -> [Option<usize>; 2] {
+        self.killers[depth as usize]
+    }
+
+    fn history(&self, value: i64, col: usize) -> u64 {
+        self.history[self.history_index(value, col)]
+    }
+
+    fn history_index(&self, value: i64, col: usize) -> usize {
+        if value > 0 {
+            return col;
         }
-        return Err(());
+        return self.num_cols + col;
     }
 
-    pub fn get(&self, row: usize, col: usize) -> i32 {
-        self.items[col * self.num_rows + (self.num_rows - 1 - row)]
+    // Chip dropped by the side to move, the AI maximizes
+    fn value(&self, maximizing: bool) -> i64 {
+        if maximizing {
+            return self.ai_move_val;
+        }
+        return -self.ai_move_val;
+    }
+}
+
+impl SearchGame for SearchContext {
+    type Position = Grid;
+    type Move = usize;
+
+    fn moves(&self, state: &Grid, depth: u32, maximizing: bool) -> Vec<usize> {
+        self.game
+            .ai_order_moves(state, depth, self.value(maximizing), self)
     }
 
-    pub fn set(&mut self, row: usize, col: usize, val: i32) {
-        self.items[col * self.num_rows + (self.num_rows - 1 - row)] = val;
+    fn play(&self, state: &Grid, col: usize, maximizing: bool) -> Grid {
+        self.game
+            .ai_fill_map(state, col, self.value(maximizing))
+            .unwrap()
+    }
+
+    fn leaf_value(&self, state: &Grid, depth: u32, _col: usize) -> Option<i64> {
+        self.game.ai_leaf_value(state, depth, self.ai_move_val)
+    }
+
+    fn cutoff(&mut self, depth: u32, maximizing: bool, col: usize) {
+        let killers = &mut self.killers[depth as usize];
+        if killers[0] != Some(col) {
+            killers[1] = killers[0];
+            killers[0] = Some(col);
+        }
+
+        // Cutoffs close to the root prune larger subtrees
+        let remaining = (self.game.max_ai_depth + 1).saturating_sub(depth) as u64;
+        let idx = self.history_index(self.value(maximizing), col);
+        self.history[idx] += remaining * remaining;
     }
 }
 
+// Cells hold 1 for player one, -1 for player two and 0 when empty
+pub type Grid = Board<i32>;
+
 impl fmt::Display for Grid {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         for r in 0..self.num_rows {
@@ -536,3 +885,67 @@ impl fmt::Display for Grid {
 pub struct Row {
     pub items: Vec<i64>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Plain alpha-beta: every legal column from the left, without shortcuts
+    struct Unordered(SearchContext);
+
+    impl SearchGame for Unordered {
+        type Position = Grid;
+        type Move = usize;
+
+        fn moves(&self, state: &Grid, _depth: u32, _maximizing: bool) -> Vec<usize> {
+            (0..state.num_cols)
+                .filter(|col| state.get(0, *col) == 0)
+                .collect()
+        }
+
+        fn play(&self, state: &Grid, col: usize, maximizing: bool) -> Grid {
+            self.0.play(state, col, maximizing)
+        }
+
+        fn leaf_value(&self, state: &Grid, depth: u32, col: usize) -> Option<i64> {
+            self.0.leaf_value(state, depth, col)
+        }
+    }
+
+    // Root values of the ordered and the plain search for the player to move
+    fn root_values(moves: &str, evaluator: Evaluator) -> (i64, i64) {
+        let mut game = Game::new(6, 7, false, "P1".to_string(), "P2".to_string(), 5);
+        game.evaluator = evaluator;
+        for col in parse_moves(moves).unwrap() {
+            game.make_move(col).unwrap();
+        }
+        let ai_move_val = game.player_move_translate() as i64;
+
+        let mut ordered = game.ai_search(ai_move_val);
+        ordered.run();
+        let context = SearchContext::new(game.clone(), ai_move_val);
+        let mut unordered = Search::new(Unordered(context), game.grid.clone());
+        unordered.run();
+        (ordered.into_stats().value, unordered.into_stats().value)
+    }
+
+    #[test]
+    fn move_ordering_keeps_the_root_value() {
+        // An opening, a win in one, a forced block and positions with threats for both
+        let positions = [
+            "",
+            "3 0 3 0",
+            "3 0 3 0 3",
+            "3 3 4 4 2",
+            "3 2 3 2 4 4 5",
+            "0 6 1 5 3 3 2 4",
+            "3 3 3 3 2 4 2 2",
+        ];
+        for moves in positions.iter() {
+            for evaluator in [Evaluator::Chains, Evaluator::Threats].iter() {
+                let (ordered, unordered) = root_values(moves, *evaluator);
+                assert_eq!(ordered, unordered, "after \"{}\"", moves);
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::board::Turn;
+use crate::toot_otto::{ChipType, Inventory};
+use std::io::{self, BufRead, Write};
+
+const COMMANDS: &str = "Commands: undo, hint, save <file>, quit";
+
+// A line typed by the player without surrounding whitespace, None at the end of input
+pub fn read_line() -> Option<String> {
+    print!("> ");
+    io::stdout().flush().expect("Error while Flushing Buffer");
+    let mut line = String::new();
+    match io::stdin().lock().read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line.trim().to_string()),
+    }
+}
+
+// Ask until parse accepts the answer. The game commands are accepted at every prompt
+// and come back as the error, the end of input quits the game
+pub fn prompt<M, T, F>(message: &str, parse: F) -> Result<T, Turn<M>>
+where
+    F: Fn(&str) -> Result<T, String>,
+{
+    loop {
+        println!("{}", message);
+        let line = match read_line() {
+            Some(line) => line,
+            None => {
+                println!("End of input, quitting the game");
+                return Err(Turn::Quit);
+            }
+        };
+        if line.is_empty() {
+            println!("Error: No selection made.");
+            continue;
+        }
+
+        match command(&line) {
+            Ok(Some(turn)) => return Err(turn),
+            Ok(None) => {}
+            Err(msg) => {
+                println!("{}", msg);
+                continue;
+            }
+        }
+
+        match parse(&line) {
+            Ok(answer) => return Ok(answer),
+            Err(msg) => {
+                println!("{}", msg);
+                println!("{}", COMMANDS);
+            }
+        }
+    }
+}
+
+// Game command on the line, None when it is an answer to the prompt and an error
+// when the command cannot be used as typed
+fn command<M>(line: &str) -> Result<Option<Turn<M>>, String> {
+    let mut words = line.split_whitespace();
+    let name = match words.next() {
+        Some(name) => name.to_lowercase(),
+        None => return Ok(None),
+    };
+    match name.as_str() {
+        "undo" => Ok(Some(Turn::Undo)),
+        "hint" => Ok(Some(Turn::Hint)),
+        "save" => match words.next() {
+            Some(path) => Ok(Some(Turn::Save(path.to_string()))),
+            None => Err("Usage: save <file>".to_string()),
+        },
+        "quit" | "exit" => {
+            println!("Quitting the game");
+            Ok(Some(Turn::Quit))
+        }
+        "help" => Err(COMMANDS.to_string()),
+        _ => Ok(None),
+    }
+}
+
+pub fn parse_column(text: &str, col_size: usize) -> Result<usize, String> {
+    match text.parse::<usize>() {
+        Ok(col) if col < col_size => Ok(col),
+        _ => Err(format!(
+            "{} is not a column, pick one from 0 to {}",
+            text,
+            col_size - 1
+        )),
+    }
+}
+
+// A letter the player still has tiles of, in either case
+pub fn parse_letter(text: &str, chips: &Inventory) -> Result<ChipType, String> {
+    let mut chars = text.chars();
+    let letter = match (chars.next(), chars.next()) {
+        (Some(letter), None) => ChipType(letter.to_ascii_uppercase()),
+        _ => return Err(format!("{} is not a letter", text)),
+    };
+    if !chips
+        .counts
+        .iter()
+        .any(|(chip_type, _)| *chip_type == letter)
+    {
+        return Err(format!("{} is not one of the letters", letter));
+    }
+    if chips.remaining(letter) == 0 {
+        return Err(format!(
+            "No {} tiles left. Please play another letter",
+            letter
+        ));
+    }
+    Ok(letter)
+}
@@ -1,2 +1,8 @@
+pub mod args;
+pub mod board;
 pub mod connect_four;
+pub mod protocol;
+pub mod save;
+pub mod search;
+pub mod setup;
 pub mod toot_otto;
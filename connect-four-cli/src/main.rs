@@ -1,69 +1,652 @@
+mod analysis;
+mod args;
+mod backend;
+mod batch;
+mod board;
 mod connect_four;
+mod input;
+mod net;
+mod protocol;
+mod replay;
+mod save;
+mod search;
+mod setup;
 mod toot_otto;
+mod tui;
 
+use crate::analysis::{Analysis, Limit, Outcome};
+use crate::args::{Command, First, GameKind, Options};
+use crate::backend::{Backend, SerializableGame};
+use crate::board::{Player, Turn};
 use crate::connect_four::{
-    Game as ConnectFourGame, GameEvents as ConnectFourGameEvents, Grid as ConnectFourGrid,
+    Evaluator as ConnectFourEvaluator, Game as ConnectFourGame,
+    GameEvents as ConnectFourGameEvents, Grid as ConnectFourGrid, State as ConnectFourState,
+};
+use crate::replay::Viewer;
+use crate::save::SavedGame;
+use crate::search::SearchStats;
+use crate::setup::{
+    connect_four_evaluator, connect_four_game, first_player, toot_otto_evaluator, toot_otto_game,
+    toot_otto_variant,
 };
 use crate::toot_otto::{
-    ChipType as TootOttoChipType, DummyGrid as TootOttoGrid, Game as TootOttoGame,
-    GameEvents as TootOttoGameEvents,
+    ChipType as TootOttoChipType, Evaluator as TootOttoEvaluator, Game as TootOttoGame,
+    GameEvents as TootOttoGameEvents, Grid as TootOttoGrid, Inventory as TootOttoInventory,
+    Move as TootOttoMove, SpelledWord as TootOttoSpelledWord, State as TootOttoState,
+    Variant as TootOttoVariant,
 };
-use std::io;
+use rand::rngs::StdRng;
+use rand::{FromEntropy, Rng, SeedableRng};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+use std::time::Duration;
 
 fn main() {
+    let args = env::args().skip(1).collect::<Vec<String>>();
+    let (command, mut options) = match args::parse(&args) {
+        Ok(parsed) => parsed,
+        Err(msg) => {
+            println!("{}", msg);
+            println!("{}", args::USAGE);
+            process::exit(2);
+        }
+    };
+
+    if options.backend.is_none() {
+        options.backend = env::var(backend::BACKEND_VAR).ok();
+    }
+
+    // Random choices of the CLI, reproducible with --seed
+    let mut rng = match options.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let result = match command {
+        Command::Help => {
+            println!("{}", args::USAGE);
+            Ok(())
+        }
+        Command::Menu => menu(&options, &mut rng),
+        Command::Play => play(&options, &mut rng),
+        Command::Analyze(moves) => analyze(&options, &moves),
+        Command::Selfplay => selfplay(&options, &mut rng),
+        Command::Replay(moves) => replay(&options, &moves),
+        Command::Batch(path) => batch::run(&options, path.as_deref(), &mut rng).map(|result| {
+            // Only the JSON is printed, an illegal move shows in its exit status
+            println!("{}", serde_json::to_string_pretty(&result).unwrap());
+            if result.error.is_some() {
+                process::exit(1);
+            }
+        }),
+        Command::Resume(path) => resume(&options, &path),
+        Command::Host(addr) => host(&options, &addr, &mut rng),
+        Command::Join(addr) => net::join(&addr),
+        Command::History => history(&options),
+        Command::Leaderboard => leaderboard(&options),
+    };
+    if let Err(msg) = result {
+        println!("{}", msg);
+        process::exit(1);
+    }
+}
+
+fn menu(options: &Options, rng: &mut StdRng) -> Result<(), String> {
     println!("Welcome to our game Command Line Interface.");
     println!("Please choose between the following: ");
     println!("1) Connect-4");
     println!("2) Toot and Otto");
 
-    let sel = retrieve_user_input();
-    let is_connect_four;
+    let is_connect_four = match retrieve_user_input()?.as_str() {
+        "1" => true,
+        "2" => false,
+        _ => return Err("Invalid input".to_string()),
+    };
 
-    match sel {
-        Ok(x) => match x.as_str() {
-            "1" => is_connect_four = true,
-            "2" => is_connect_four = false,
-            _ => {
-                println!("Invalid input");
-                return;
+    if is_connect_four {
+        let evaluator = connect_four_evaluator(options)?;
+        if let Some(game) = start_connect_four(options.verbose, evaluator, rng)? {
+            record_connect_four(options, &game);
+        }
+    } else {
+        let evaluator = toot_otto_evaluator(options)?;
+        let variant = toot_otto_variant(options)?;
+        if let Some(game) = start_toot_and_otto(options.verbose, evaluator, variant, rng)? {
+            record_toot_otto(options, &game);
+        }
+    }
+    Ok(())
+}
+
+fn play(options: &Options, rng: &mut StdRng) -> Result<(), String> {
+    match options.game {
+        GameKind::ConnectFour => {
+            let mut game = connect_four_game(options, rng)?;
+            if options.tui {
+                let handler = tui::ConnectFourTui::new(&game).map_err(|e| e.to_string())?;
+                game.start_game_cli(handler);
+            } else {
+                let verbose = options.verbose;
+                game.start_game_cli(ConnectFourCliInterface { verbose });
             }
-        },
-        Err(_) => {
-            println!("Invalid input");
-            return;
+            record_connect_four(options, &game);
+        }
+        GameKind::TootOtto => {
+            let mut game = toot_otto_game(options, rng)?;
+            if options.tui {
+                let handler = tui::TootOttoTui::new(&game).map_err(|e| e.to_string())?;
+                game.start_game_cli(handler);
+            } else {
+                let verbose = options.verbose;
+                game.start_game_cli(TootOttoCliInterface { verbose });
+            }
+            record_toot_otto(options, &game);
         }
     }
+    Ok(())
+}
 
-    if is_connect_four {
-        start_connect_four();
+// Computer against computer at --ai-depth, the first move is random so that runs
+// with different seeds play different games
+fn selfplay(options: &Options, rng: &mut StdRng) -> Result<(), String> {
+    let depth = options.ai_depth.unwrap_or(4);
+    match options.game {
+        GameKind::ConnectFour => {
+            let mut game = connect_four_game(options, rng)?;
+            game.p1 = "Computer 1".to_string();
+            game.p2 = "Computer 2".to_string();
+            game.max_ai_depth = depth;
+            let handler = ConnectFourCliInterface {
+                verbose: options.verbose,
+            };
+
+            let col = rng.gen_range(0, game.grid.num_cols);
+            let player = game.player_name(game.current_player());
+            game.make_move(col).unwrap();
+            handler.selected_column(player, col);
+            handler.show_grid(&game.grid);
+
+            while game.state == ConnectFourState::Running {
+                let player = game.player_name(game.current_player());
+                let (_, _, col, stats) = game
+                    .ai_make_move()
+                    .map_err(|_| "The computer has no legal move".to_string())?;
+                handler.search_stats(&stats);
+                handler.selected_column(player, col);
+                handler.show_grid(&game.grid);
+            }
+            print_result(&game.winner);
+        }
+        GameKind::TootOtto => {
+            let mut game = toot_otto_game(options, rng)?;
+            game.p1 = "Computer 1".to_string();
+            game.p2 = "Computer 2".to_string();
+            game.max_ai_depth = depth;
+            let handler = TootOttoCliInterface {
+                verbose: options.verbose,
+            };
+
+            let letters = game.variant.letters.clone();
+            let chip_type = letters[rng.gen_range(0, letters.len())];
+            let col = rng.gen_range(0, game.grid.num_cols);
+            let player = game.player_name(game.current_player());
+            game.make_move(chip_type, col).unwrap();
+            handler.selected_column(player, chip_type, col);
+            handler.show_grid(&game.grid);
+
+            while game.state == TootOttoState::Running {
+                let player = game.player_name(game.current_player());
+                let (_, _, col, chip_type, stats) = game
+                    .ai_make_move()
+                    .map_err(|_| "The computer has no legal move".to_string())?;
+                handler.search_stats(&stats);
+                handler.selected_column(player, chip_type, col);
+                handler.show_grid(&game.grid);
+            }
+            if !game.words.is_empty() {
+                handler.words_spelled(&game.words);
+            }
+            print_result(&game.winner);
+        }
+    }
+    Ok(())
+}
+
+// Every legal move of a position with its evaluation and best line. The position is a
+// move list or a board diagram, or a file holding either
+fn analyze(options: &Options, position: &str) -> Result<(), String> {
+    let mut options = options.clone();
+    let depth = options.ai_depth.take();
+    let limit = match options.time {
+        Some(seconds) => Limit::Time(Duration::from_secs_f64(seconds)),
+        None => Limit::Depth(depth.unwrap_or(match options.game {
+            GameKind::ConnectFour => 4,
+            GameKind::TootOtto => 3,
+        })),
+    };
+    if limit == Limit::Depth(0) {
+        return Err("The analysis needs a depth of at least 1".to_string());
+    }
+
+    let position = if Path::new(position).is_file() {
+        fs::read_to_string(position).map_err(|e| format!("Could not read {}: {}", position, e))?
+    } else {
+        position.to_string()
+    };
+    let position = position.trim();
+    let is_diagram = position.contains('/') || position.contains('\n');
+
+    let mut rng = StdRng::seed_from_u64(0);
+    match options.game {
+        GameKind::ConnectFour => {
+            let mut game = connect_four_game(&options, &mut rng)?;
+            if is_diagram {
+                game.load_diagram(position)?;
+            } else {
+                for (idx, col) in connect_four::parse_moves(position)?.into_iter().enumerate() {
+                    if game.state != ConnectFourState::Running {
+                        return Err(format!(
+                            "The game is over before move {} ({})",
+                            idx + 1,
+                            col
+                        ));
+                    }
+                    if game.make_move(col).is_err() {
+                        return Err(format!("Move {} ({}) is illegal", idx + 1, col));
+                    }
+                }
+            }
+            ConnectFourCliInterface { verbose: false }.show_grid(&game.grid);
+            if game.state != ConnectFourState::Running {
+                print_result(&game.winner);
+                return Ok(());
+            }
+            let mover = game.player_name(game.current_player());
+            print_analysis(&mover, &analysis::analyze(&game, limit));
+        }
+        GameKind::TootOtto => {
+            let mut game = toot_otto_game(&options, &mut rng)?;
+            if is_diagram {
+                game.load_diagram(position)?;
+            } else {
+                for (idx, (chip_type, col)) in
+                    toot_otto::parse_moves(position)?.into_iter().enumerate()
+                {
+                    if game.state != TootOttoState::Running {
+                        return Err(format!(
+                            "The game is over before move {} ({}{})",
+                            idx + 1,
+                            chip_type,
+                            col
+                        ));
+                    }
+                    if game.make_move(chip_type, col).is_err() {
+                        return Err(format!(
+                            "Move {} ({}{}) is illegal",
+                            idx + 1,
+                            chip_type,
+                            col
+                        ));
+                    }
+                }
+            }
+            TootOttoCliInterface { verbose: false }.show_grid(&game.grid);
+            if game.state != TootOttoState::Running {
+                if !game.words.is_empty() {
+                    TootOttoCliInterface { verbose: false }.words_spelled(&game.words);
+                }
+                print_result(&game.winner);
+                return Ok(());
+            }
+            let mover = game.player_name(game.current_player());
+            println!("Tiles left for {}: {}", mover, game.current_chips());
+            print_analysis(&mover, &analysis::analyze(&game, limit));
+        }
+    }
+    Ok(())
+}
+
+fn print_analysis(mover: &str, analysis: &Analysis) {
+    println!("{} to move, searched to depth {}", mover, analysis.depth);
+    println!("{:<6} {:<16} Best line", "Move", "Evaluation");
+    for analysis in analysis.moves.iter() {
+        let outcome = match analysis.outcome {
+            Outcome::Forced(moves) if moves > 0 => format!("wins in {}", moves),
+            Outcome::Forced(moves) => format!("loses in {}", -moves),
+            Outcome::Draw => "draw".to_string(),
+            Outcome::Score(value) => format!("{:+}", value),
+        };
+        println!(
+            "{:<6} {:<16} {}",
+            analysis.mv,
+            outcome,
+            analysis.line.join(" ")
+        );
+    }
+    if let Some(best) = analysis.moves.first() {
+        println!("Best move: {}", best.mv);
+    }
+    println!(
+        "Search: nodes {}, time {} ms",
+        analysis.nodes,
+        analysis.elapsed.as_millis()
+    );
+}
+
+// Carry on with a game saved during play, the saved players and AI settings are used
+fn resume(options: &Options, path: &str) -> Result<(), String> {
+    let saved = SavedGame::read(path)?;
+    let invalid = |msg: String| format!("{}: {}", path, msg);
+    match saved.game.as_str() {
+        save::CONNECT_FOUR => {
+            let mut game = saved.to_connect_four().map_err(invalid)?;
+            if game.state != ConnectFourState::Running {
+                print_result(&game.winner);
+                return Ok(());
+            }
+            if options.tui {
+                let handler = tui::ConnectFourTui::new(&game).map_err(|e| e.to_string())?;
+                game.start_game_cli(handler);
+            } else {
+                let verbose = options.verbose;
+                game.start_game_cli(ConnectFourCliInterface { verbose });
+            }
+            record_connect_four(options, &game);
+        }
+        save::TOOT_OTTO => {
+            let mut game = saved.to_toot_otto().map_err(invalid)?;
+            if game.state != TootOttoState::Running {
+                print_result(&game.winner);
+                return Ok(());
+            }
+            if options.tui {
+                let handler = tui::TootOttoTui::new(&game).map_err(|e| e.to_string())?;
+                game.start_game_cli(handler);
+            } else {
+                let verbose = options.verbose;
+                game.start_game_cli(TootOttoCliInterface { verbose });
+            }
+            record_toot_otto(options, &game);
+        }
+        x => return Err(invalid(format!("Unknown game {}", x))),
+    }
+    Ok(())
+}
+
+// Human against human over the network, the host plays player one and the guest is
+// the computer's side for --first
+fn host(options: &Options, addr: &str, rng: &mut StdRng) -> Result<(), String> {
+    let mut options = options.clone();
+    options.ai_depth = None;
+    let first = first_player(options.first, Player::Two, rng);
+    match options.game {
+        GameKind::ConnectFour => {
+            let mut game = connect_four_game(&options, rng)?;
+            game.p1 = "Host".to_string();
+            game.p2 = "Guest".to_string();
+            game.first_player = first;
+            let game = net::host(addr, game)?;
+            record_connect_four(&options, &game);
+            Ok(())
+        }
+        GameKind::TootOtto => {
+            let mut game = toot_otto_game(&options, rng)?;
+            game.p1 = "Host".to_string();
+            game.p2 = "Guest".to_string();
+            game.first_player = first;
+            let game = net::host(addr, game)?;
+            record_toot_otto(&options, &game);
+            Ok(())
+        }
+    }
+}
+
+// Step through a saved game or a move list, evaluating each position at --ai-depth
+fn replay(options: &Options, recorded: &str) -> Result<(), String> {
+    let mut options = options.clone();
+    let depth = options.ai_depth.take();
+    let mut rng = StdRng::seed_from_u64(0);
+    let saved = if Path::new(recorded).is_file() {
+        Some(SavedGame::read(recorded)?)
+    } else {
+        None
+    };
+    let invalid = |msg: String| format!("{}: {}", recorded, msg);
+
+    let mut viewer = match saved {
+        Some(saved) if saved.game == save::TOOT_OTTO => {
+            let mut game = saved.to_toot_otto().map_err(invalid)?;
+            let moves = game.history.clone();
+            while game.undo_move().is_ok() {}
+            game.max_ai_depth = depth.unwrap_or(game.max_ai_depth);
+            Viewer::toot_otto(game, &moves)?
+        }
+        Some(saved) => {
+            let mut game = saved.to_connect_four().map_err(invalid)?;
+            let moves = game.history.clone();
+            while game.undo_move().is_ok() {}
+            game.max_ai_depth = depth.unwrap_or(game.max_ai_depth);
+            Viewer::connect_four(game, &moves)?
+        }
+        None => match options.game {
+            GameKind::ConnectFour => {
+                let mut game = connect_four_game(&options, &mut rng)?;
+                game.max_ai_depth = depth.unwrap_or(4);
+                Viewer::connect_four(game, &connect_four::parse_moves(recorded)?)?
+            }
+            GameKind::TootOtto => {
+                let mut game = toot_otto_game(&options, &mut rng)?;
+                game.max_ai_depth = depth.unwrap_or(3);
+                Viewer::toot_otto(game, &toot_otto::parse_moves(recorded)?)?
+            }
+        },
+    };
+    viewer.run();
+    Ok(())
+}
+
+// Post the result of a finished game when a backend is configured, the game is over
+// either way so a failure is only reported
+fn record(options: &Options, game: SerializableGame) {
+    let url = match &options.backend {
+        Some(url) => url,
+        None => return,
+    };
+    match Backend::new(url).and_then(|backend| backend.insert_game(&game)) {
+        Ok(_) => println!("Recorded the game at {}", url),
+        Err(msg) => println!("Could not record the game: {}", msg),
+    }
+}
+
+fn record_connect_four(options: &Options, game: &ConnectFourGame) {
+    if game.state == ConnectFourState::Running {
+        return;
+    }
+    let opponent = if game.with_ai { "Computer" } else { "Human" };
+    let saved = SavedGame::from_connect_four(game);
+    record(
+        options,
+        SerializableGame {
+            gameType: format!("Connect4 with {}", opponent),
+            Player1Name: saved.p1,
+            Player2Name: saved.p2,
+            WinnerName: game.winner.clone(),
+            rows: saved.rows,
+            cols: saved.cols,
+            moves: saved.moves,
+            first_player: saved.first_player,
+            variant: saved.variant,
+        },
+    );
+}
+
+fn record_toot_otto(options: &Options, game: &TootOttoGame) {
+    if game.state == TootOttoState::Running {
+        return;
+    }
+    let opponent = if game.with_ai { "Computer" } else { "Human" };
+    let saved = SavedGame::from_toot_otto(game);
+    record(
+        options,
+        SerializableGame {
+            gameType: format!("TOOT-OTTO with {}", opponent),
+            Player1Name: saved.p1,
+            Player2Name: saved.p2,
+            WinnerName: game.winner.clone(),
+            rows: saved.rows,
+            cols: saved.cols,
+            moves: saved.moves,
+            first_player: saved.first_player,
+            variant: saved.variant,
+        },
+    );
+}
+
+fn backend(options: &Options) -> Result<Backend, String> {
+    Backend::new(
+        options
+            .backend
+            .as_deref()
+            .unwrap_or(backend::DEFAULT_BACKEND),
+    )
+}
+
+// Games recorded by the backend, the latest first
+fn history(options: &Options) -> Result<(), String> {
+    let backend = backend(options)?;
+    let mut games = backend.games()?;
+    if games.is_empty() {
+        println!("No games recorded at {}", backend.url());
+        return Ok(());
+    }
+    games.sort_by_key(|game| std::cmp::Reverse(game.GameDate));
+
+    println!(
+        "{:<17} {:<24} {:<12} {:<12} Winner",
+        "Date", "Game", "Player 1", "Player 2"
+    );
+    for game in games {
+        println!(
+            "{:<17} {:<24} {:<12} {:<12} {}",
+            game.GameDate.format("%Y-%m-%d %H:%M"),
+            game.gameType,
+            game.Player1Name,
+            game.Player2Name,
+            game.WinnerName
+        );
+    }
+    Ok(())
+}
+
+// Players by the games they won, draws are not counted
+fn leaderboard(options: &Options) -> Result<(), String> {
+    let backend = backend(options)?;
+    let mut wins = backend
+        .wins()?
+        .into_iter()
+        .filter(|player| player._id != "Draw" && !player._id.is_empty())
+        .collect::<Vec<_>>();
+    if wins.is_empty() {
+        println!("No games won at {}", backend.url());
+        return Ok(());
+    }
+    wins.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a._id.cmp(&b._id)));
+
+    println!("{:<6} {:<20} Wins", "Rank", "Player");
+    for (idx, player) in wins.iter().enumerate() {
+        println!("{:<6} {:<20} {}", idx + 1, player._id, player.count);
+    }
+    Ok(())
+}
+
+fn print_result(winner: &str) {
+    if winner == "Draw" {
+        println!("Draw");
     } else {
-        start_toot_and_otto();
+        println!("{} has won! Congratulations!", winner);
     }
 }
 
-fn retrieve_user_input() -> Result<String, ()> {
-    let mut command = String::new();
-    let command_vector;
-    print!("> ");
-    io::Write::flush(&mut io::stdout()).expect("Error while Flushing Buffer");
-    match io::stdin().read_line(&mut command) {
-        Ok(_) => {
-            command_vector = command.split_whitespace().collect::<Vec<&str>>();
+// Why a menu got no selection. The end of input leaves every menu at once, the CLI
+// then stops with a single message
+#[derive(Debug)]
+enum MenuError {
+    EndOfInput,
+    NoSelection,
+}
+
+impl From<MenuError> for String {
+    fn from(error: MenuError) -> Self {
+        match error {
+            MenuError::EndOfInput => "End of input, no game was started".to_string(),
+            MenuError::NoSelection => "Invalid input".to_string(),
         }
-        Err(_) => {
-            println!("Error reading input!");
-            return Err(());
+    }
+}
+
+fn retrieve_user_input() -> Result<String, MenuError> {
+    let line = match input::read_line() {
+        Some(line) => line,
+        None => return Err(MenuError::EndOfInput),
+    };
+    match line.split_whitespace().next() {
+        Some(selection) => Ok(selection.to_string()),
+        None => {
+            println!("Error: No selection made.");
+            Err(MenuError::NoSelection)
         }
     }
-    if command_vector.len() == 0 {
-        println!("Error: No selection made.");
-        return Err(());
+}
+
+// Ask which side the human takes and who moves first, returns the computer's side and
+// the player that moves first
+fn select_sides(
+    p1_side: &str,
+    p2_side: &str,
+    rng: &mut StdRng,
+) -> Result<(Player, Player), MenuError> {
+    println!("Which side do you want to play?");
+    println!("1) {}", p1_side);
+    println!("2) {}", p2_side);
+
+    let ai_player = match retrieve_user_input() {
+        Ok(x) => match x.as_str() {
+            "2" => Player::One,
+            _ => Player::Two,
+        },
+        Err(MenuError::NoSelection) => Player::Two,
+        Err(err) => return Err(err),
+    };
+
+    println!("Who moves first?");
+    println!("1) You");
+    println!("2) Computer");
+    println!("3) Random");
+
+    let first_player = match retrieve_user_input() {
+        Ok(x) => match x.as_str() {
+            "2" => ai_player,
+            "3" => first_player(First::Random, ai_player, rng),
+            _ => ai_player.other(),
+        },
+        Err(MenuError::NoSelection) => ai_player.other(),
+        Err(err) => return Err(err),
+    };
+
+    if first_player == ai_player {
+        println!("Computer moves first");
     } else {
-        return Ok(command_vector[0].to_string());
+        println!("You move first");
     }
+
+    Ok((ai_player, first_player))
 }
 
-struct ConnectFourCliInterface {}
+struct ConnectFourCliInterface {
+    verbose: bool,
+}
 
 impl ConnectFourGameEvents for ConnectFourCliInterface {
     fn introduction(&self) {}
@@ -85,13 +668,12 @@ impl ConnectFourGameEvents for ConnectFourCliInterface {
         }
     }
 
-    fn player_turn(&self, col_size: usize) -> Result<usize, ()> {
-        println!("Please select a column (0-{})", col_size - 1);
-        let col = retrieve_user_input();
-        if col.is_ok() {
-            return Ok(col.unwrap().parse().unwrap());
+    fn player_turn(&self, col_size: usize) -> Turn<usize> {
+        let message = format!("Please select a column (0-{})", col_size - 1);
+        match input::prompt(&message, |text| input::parse_column(text, col_size)) {
+            Ok(col) => Turn::Move(col),
+            Err(turn) => turn,
         }
-        return Err(());
     }
 
     fn selected_column(&self, player: String, col: usize) {
@@ -100,16 +682,43 @@ impl ConnectFourGameEvents for ConnectFourCliInterface {
 
     fn animate_chip(&self) {}
 
+    fn search_stats(&self, stats: &SearchStats<usize>) {
+        if self.verbose {
+            let line = stats
+                .principal_variation
+                .iter()
+                .map(|col| col.to_string())
+                .collect::<Vec<String>>();
+            println!("[AI] {}", stats);
+            println!("[AI] Principal variation: {}", line.join(" "));
+        }
+    }
+
+    fn hint(&self, col: usize, stats: &SearchStats<usize>) {
+        println!("Hint: Column {}", col);
+        self.search_stats(stats);
+    }
+
+    fn moves_undone(&self, count: usize) {
+        print_undone(count);
+    }
+
+    fn save_game(&self, path: &str, game: &ConnectFourGame) {
+        print_saved(path, SavedGame::from_connect_four(game).write(path));
+    }
+
     fn invalid_move(&self) {
         println!("Column is full. Please try again with different column");
     }
 
     fn game_over(&self, winner: String) {
-        println!("{} has won! Congratulations!", winner);
+        print_result(&winner);
     }
 }
 
-struct TootOttoCliInterface {}
+struct TootOttoCliInterface {
+    verbose: bool,
+}
 
 impl TootOttoGameEvents for TootOttoCliInterface {
     fn introduction(&self) {}
@@ -131,61 +740,105 @@ impl TootOttoGameEvents for TootOttoCliInterface {
         }
     }
 
-    fn player_turn(&self, col_size: usize) -> Result<(TootOttoChipType, usize), ()> {
-        let chip_type;
-
-        println!("Please select a chip type (T or O)");
-        let chip = retrieve_user_input();
-        if chip.is_ok() {
-            let chip_str = chip.unwrap();
-            if chip_str == "T" {
-                chip_type = TootOttoChipType::T;
-            } else if chip_str == "O" {
-                chip_type = TootOttoChipType::O;
-            } else {
-                println!("Invalid input");
-                return Err(());
-            }
-        } else {
-            return Err(());
-        }
+    fn player_turn(&self, col_size: usize, chips: TootOttoInventory) -> Turn<TootOttoMove> {
+        let letters = chips
+            .counts
+            .iter()
+            .map(|(letter, _)| letter.to_string())
+            .collect::<Vec<String>>();
+        println!("Tiles left: {}", chips);
+        let message = format!("Please select a chip type ({})", letters.join(" or "));
+        let chip_type = match input::prompt(&message, |text| input::parse_letter(text, &chips)) {
+            Ok(chip_type) => chip_type,
+            Err(turn) => return turn,
+        };
 
-        println!("Please select a column (0-{})", col_size - 1);
-        let col = retrieve_user_input();
-        if col.is_ok() {
-            return Ok((chip_type, col.unwrap().parse().unwrap()));
+        let message = format!("Please select a column (0-{})", col_size - 1);
+        match input::prompt(&message, |text| input::parse_column(text, col_size)) {
+            Ok(col) => Turn::Move((chip_type, col)),
+            Err(turn) => turn,
         }
-        return Err(());
     }
 
     fn selected_column(&self, player: String, chip_type: TootOttoChipType, col: usize) {
-        let chip_str;
-        match chip_type {
-            TootOttoChipType::T => {
-                chip_str = "T";
-            }
-            TootOttoChipType::O => {
-                chip_str = "O";
-            }
-        };
         println!(
             "{} Selected Chip Type {} and Column {}",
-            player, chip_str, col
+            player, chip_type, col
         );
     }
 
     fn animate_chip(&self) {}
 
+    fn search_stats(&self, stats: &SearchStats<TootOttoMove>) {
+        if self.verbose {
+            let line = stats
+                .principal_variation
+                .iter()
+                .map(|(chip_type, col)| format!("{}{}", chip_type, col))
+                .collect::<Vec<String>>();
+            println!("[AI] {}", stats);
+            println!("[AI] Principal variation: {}", line.join(" "));
+        }
+    }
+
+    fn hint(&self, chip_type: TootOttoChipType, col: usize, stats: &SearchStats<TootOttoMove>) {
+        println!("Hint: Chip Type {} and Column {}", chip_type, col);
+        self.search_stats(stats);
+    }
+
+    fn moves_undone(&self, count: usize) {
+        print_undone(count);
+    }
+
+    fn save_game(&self, path: &str, game: &TootOttoGame) {
+        print_saved(path, SavedGame::from_toot_otto(game).write(path));
+    }
+
     fn invalid_move(&self) {
         println!("Column is full. Please try again with different column");
     }
 
+    fn out_of_chips(&self, chip_type: TootOttoChipType) {
+        println!("No {} tiles left. Please play another letter", chip_type);
+    }
+
+    fn words_spelled(&self, words: &[TootOttoSpelledWord]) {
+        for word in words {
+            println!(
+                "{} spelled from row {} column {} to row {} column {}",
+                word.word, word.from.0, word.from.1, word.to.0, word.to.1
+            );
+        }
+    }
+
     fn game_over(&self, winner: String) {
-        println!("{} has won! Congratulations!", winner);
+        print_result(&winner);
     }
 }
 
-fn start_connect_four() {
+fn print_undone(count: usize) {
+    match count {
+        0 => println!("No moves to take back"),
+        1 => println!("Took back 1 move"),
+        _ => println!("Took back {} moves", count),
+    }
+}
+
+fn print_saved(path: &str, result: Result<(), String>) {
+    match result {
+        Ok(_) => println!(
+            "Saved the game to {}, continue it with resume {}",
+            path, path
+        ),
+        Err(msg) => println!("{}", msg),
+    }
+}
+
+fn start_connect_four(
+    verbose: bool,
+    evaluator: ConnectFourEvaluator,
+    rng: &mut StdRng,
+) -> Result<Option<ConnectFourGame>, MenuError> {
     println!("Select a game board size for Connect-4: ");
     // Size variations include 5×4, 6×5, 8×7, 9×7, 10×7, 8×8,
     println!("1) 6 x 7");
@@ -232,13 +885,14 @@ fn start_connect_four() {
 
             _ => {
                 println!("Invalid input");
-                return;
+                return Ok(None);
             }
         },
-        Err(_) => {
+        Err(MenuError::NoSelection) => {
             println!("Invalid input");
-            return;
+            return Ok(None);
         }
+        Err(err) => return Err(err),
     }
 
     println!("Do you want to play against an AI or another human?");
@@ -274,9 +928,12 @@ fn start_connect_four() {
                         "3" => 4,
                         _ => 4,
                     },
-                    Err(_) => 4,
+                    Err(MenuError::NoSelection) => 4,
+                    Err(err) => return Err(err),
                 };
 
+                let (ai_player, first_player) = select_sides("Red", "Yellow", rng)?;
+
                 game = ConnectFourGame::new(
                     num_rows,
                     num_cols,
@@ -285,23 +942,34 @@ fn start_connect_four() {
                     "Computer".to_string(),
                     max_ai_depth,
                 );
+                game.set_ai_player(ai_player);
+                game.first_player = first_player;
             }
             _ => {
                 println!("Invalid input");
-                return;
+                return Ok(None);
             }
         },
-        Err(_) => {
+        Err(MenuError::NoSelection) => {
             println!("Invalid input");
-            return;
+            return Ok(None);
         }
+        Err(err) => return Err(err),
     }
 
-    let handler: ConnectFourCliInterface = ConnectFourCliInterface {};
+    game.evaluator = evaluator;
+
+    let handler: ConnectFourCliInterface = ConnectFourCliInterface { verbose };
     game.start_game_cli(handler);
+    Ok(Some(game))
 }
 
-fn start_toot_and_otto() {
+fn start_toot_and_otto(
+    verbose: bool,
+    evaluator: TootOttoEvaluator,
+    variant: TootOttoVariant,
+    rng: &mut StdRng,
+) -> Result<Option<TootOttoGame>, MenuError> {
     println!("Select a game board size for Toot-Otto: ");
     // Size variations include 5×4, 6×5, 8×7, 9×7, 10×7, 8×8,
     println!("1) 6 x 7");
@@ -348,13 +1016,18 @@ fn start_toot_and_otto() {
 
             _ => {
                 println!("Invalid input");
-                return;
+                return Ok(None);
             }
         },
-        Err(_) => {
+        Err(MenuError::NoSelection) => {
             println!("Invalid input");
-            return;
+            return Ok(None);
         }
+        Err(err) => return Err(err),
+    }
+    if let Err(msg) = variant.check_board(num_rows, num_cols) {
+        println!("{}", msg);
+        return Ok(None);
     }
 
     println!("Do you want to play against an AI or another human?");
@@ -374,6 +1047,7 @@ fn start_toot_and_otto() {
                     "P1".to_string(),
                     "P2".to_string(),
                     3,
+                    variant,
                 );
             }
             "2" => {
@@ -391,8 +1065,15 @@ fn start_toot_and_otto() {
                         "3" => 3,
                         _ => 3,
                     },
-                    Err(_) => 3,
+                    Err(MenuError::NoSelection) => 3,
+                    Err(err) => return Err(err),
                 };
+                let (ai_player, first_player) = select_sides(
+                    &variant.word_string(Player::One),
+                    &variant.word_string(Player::Two),
+                    rng,
+                )?;
+
                 game = TootOttoGame::new(
                     num_rows,
                     num_cols,
@@ -400,19 +1081,26 @@ fn start_toot_and_otto() {
                     "Player".to_string(),
                     "Computer".to_string(),
                     max_ai_depth,
+                    variant,
                 );
+                game.set_ai_player(ai_player);
+                game.first_player = first_player;
             }
             _ => {
                 println!("Invalid input");
-                return;
+                return Ok(None);
             }
         },
-        Err(_) => {
+        Err(MenuError::NoSelection) => {
             println!("Invalid input");
-            return;
+            return Ok(None);
         }
+        Err(err) => return Err(err),
     }
 
-    let handler: TootOttoCliInterface = TootOttoCliInterface {};
+    game.evaluator = evaluator;
+
+    let handler: TootOttoCliInterface = TootOttoCliInterface { verbose };
     game.start_game_cli(handler);
+    Ok(Some(game))
 }
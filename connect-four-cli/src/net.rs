@@ -0,0 +1,376 @@
+use crate::board::{Player, Turn};
+use crate::connect_four::{
+    self, Game as ConnectFourGame, GameEvents as _, State as ConnectFourState,
+};
+use crate::protocol::{Connection, Message, PROTOCOL_VERSION};
+use crate::save::{self, SavedGame};
+use crate::toot_otto::{self, Game as TootOttoGame, GameEvents as _, State as TootOttoState};
+use crate::{print_saved, ConnectFourCliInterface, TootOttoCliInterface};
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+// Tries of a guest to get back to the host after losing the connection, a second apart
+const RECONNECT_ATTEMPTS: u32 = 10;
+
+// Time a new connection has to say hello before the host moves on
+const HELLO_TIMEOUT: Duration = Duration::from_secs(10);
+
+// The side of the player that joins, the host plays player one
+const GUEST: Player = Player::Two;
+
+// What a network game needs from Connect-4 and TOOT-OTTO, moves travel as text such
+// as "3" or "T3"
+pub trait NetGame: Sized {
+    fn from_saved(saved: &SavedGame) -> Result<Self, String>;
+    fn to_saved(&self) -> SavedGame;
+    fn is_running(&self) -> bool;
+    fn current_player(&self) -> Player;
+    fn show(&self);
+
+    // Turn of the player at this terminal, the move as text
+    fn ask(&self) -> Turn<String>;
+    fn hint(&self);
+
+    // Play and print the move, the reason when it is illegal
+    fn play(&mut self, mv: &str) -> Result<(), String>;
+
+    fn save(&self, path: &str) {
+        print_saved(path, self.to_saved().write(path));
+    }
+}
+
+impl NetGame for ConnectFourGame {
+    fn from_saved(saved: &SavedGame) -> Result<Self, String> {
+        saved.to_connect_four()
+    }
+
+    fn to_saved(&self) -> SavedGame {
+        SavedGame::from_connect_four(self)
+    }
+
+    fn is_running(&self) -> bool {
+        self.state == ConnectFourState::Running
+    }
+
+    fn current_player(&self) -> Player {
+        ConnectFourGame::current_player(self)
+    }
+
+    fn show(&self) {
+        ConnectFourCliInterface { verbose: false }.show_grid(&self.grid);
+    }
+
+    fn ask(&self) -> Turn<String> {
+        as_text(
+            ConnectFourCliInterface { verbose: false }.player_turn(self.grid.num_cols),
+            |col| col.to_string(),
+        )
+    }
+
+    fn hint(&self) {
+        let mut search = self.ai_search(self.player_move_translate() as i64);
+        search.run();
+        let stats = search.into_stats();
+        if let Some(col) = stats.principal_variation.first() {
+            ConnectFourCliInterface { verbose: false }.hint(*col, &stats);
+        }
+    }
+
+    fn play(&mut self, mv: &str) -> Result<(), String> {
+        let col = match connect_four::parse_moves(mv)?.as_slice() {
+            [col] => *col,
+            _ => return Err(format!("{} is not a single move", mv)),
+        };
+        let player = self.player_name(ConnectFourGame::current_player(self));
+        if self.make_move(col).is_err() {
+            return Err(format!("Column {} cannot be played", col));
+        }
+
+        let handler = ConnectFourCliInterface { verbose: false };
+        handler.selected_column(player, col);
+        handler.show_grid(&self.grid);
+        if !self.is_running() {
+            handler.game_over(self.winner.clone());
+        }
+        Ok(())
+    }
+}
+
+impl NetGame for TootOttoGame {
+    fn from_saved(saved: &SavedGame) -> Result<Self, String> {
+        saved.to_toot_otto()
+    }
+
+    fn to_saved(&self) -> SavedGame {
+        SavedGame::from_toot_otto(self)
+    }
+
+    fn is_running(&self) -> bool {
+        self.state == TootOttoState::Running
+    }
+
+    fn current_player(&self) -> Player {
+        TootOttoGame::current_player(self)
+    }
+
+    fn show(&self) {
+        TootOttoCliInterface { verbose: false }.show_grid(&self.grid);
+    }
+
+    fn ask(&self) -> Turn<String> {
+        let turn = TootOttoCliInterface { verbose: false }
+            .player_turn(self.grid.num_cols, self.current_chips());
+        as_text(turn, |(chip_type, col)| format!("{}{}", chip_type, col))
+    }
+
+    fn hint(&self) {
+        let mut search = self.ai_search();
+        search.run();
+        let stats = search.into_stats();
+        if let Some((chip_type, col)) = stats.principal_variation.first() {
+            TootOttoCliInterface { verbose: false }.hint(*chip_type, *col, &stats);
+        }
+    }
+
+    fn play(&mut self, mv: &str) -> Result<(), String> {
+        let (chip_type, col) = match toot_otto::parse_moves(mv)?.as_slice() {
+            [choice] => *choice,
+            _ => return Err(format!("{} is not a single move", mv)),
+        };
+        let player = self.player_name(TootOttoGame::current_player(self));
+        if self.make_move(chip_type, col).is_err() {
+            return Err(format!("{} cannot be played in column {}", chip_type, col));
+        }
+
+        let handler = TootOttoCliInterface { verbose: false };
+        handler.selected_column(player, chip_type, col);
+        handler.show_grid(&self.grid);
+        if !self.is_running() {
+            if !self.words.is_empty() {
+                handler.words_spelled(&self.words);
+            }
+            handler.game_over(self.winner.clone());
+        }
+        Ok(())
+    }
+}
+
+fn as_text<M, F: Fn(M) -> String>(turn: Turn<M>, text: F) -> Turn<String> {
+    match turn {
+        Turn::Move(mv) => Turn::Move(text(mv)),
+        Turn::Undo => Turn::Undo,
+        Turn::Hint => Turn::Hint,
+        Turn::Save(path) => Turn::Save(path),
+        Turn::Quit => Turn::Quit,
+    }
+}
+
+// Run the game for the player at this terminal and one that joins over the network,
+// returns it once it is over or a player left. The moves of the guest are checked
+// here, a guest that loses its connection can join again and continue
+pub fn host<G: NetGame>(addr: &str, mut game: G) -> Result<G, String> {
+    let listener =
+        TcpListener::bind(addr).map_err(|e| format!("Could not listen on {}: {}", addr, e))?;
+    let local = listener.local_addr().map_err(|e| e.to_string())?;
+    println!("Waiting for a player to join on {}", local);
+    let mut conn = accept(&listener, &game)?;
+    game.show();
+
+    while game.is_running() {
+        if game.current_player() != GUEST {
+            match game.ask() {
+                Turn::Move(mv) => {
+                    if let Err(reason) = game.play(&mv) {
+                        println!("{}", reason);
+                        continue;
+                    }
+                    if conn.send(&Message::Move { mv }).is_err() && game.is_running() {
+                        conn = rejoin(&listener, &game)?;
+                    }
+                }
+                Turn::Undo => println!("Moves cannot be taken back in a network game"),
+                Turn::Hint => game.hint(),
+                Turn::Save(path) => game.save(&path),
+                Turn::Quit => {
+                    let _ = conn.send(&Message::Quit);
+                    return Ok(game);
+                }
+            }
+            continue;
+        }
+
+        println!("Waiting for the other player to move");
+        match conn.receive() {
+            Ok(Message::Move { mv }) => {
+                let reply = match game.play(&mv) {
+                    Ok(_) => Message::Move { mv },
+                    Err(reason) => Message::Rejected { reason },
+                };
+                if conn.send(&reply).is_err() && game.is_running() {
+                    conn = rejoin(&listener, &game)?;
+                }
+            }
+            Ok(Message::Quit) => {
+                println!("The other player left the game");
+                return Ok(game);
+            }
+            Ok(_) => {
+                let reason = "Expected a move".to_string();
+                let _ = conn.send(&Message::Rejected { reason });
+            }
+            Err(_) => conn = rejoin(&listener, &game)?,
+        }
+    }
+    Ok(game)
+}
+
+fn rejoin<G: NetGame>(listener: &TcpListener, game: &G) -> Result<Connection, String> {
+    println!("The other player disconnected, waiting for them to join again");
+    accept(listener, game)
+}
+
+// Wait for a guest that speaks this version of the protocol and send it the game
+fn accept<G: NetGame>(listener: &TcpListener, game: &G) -> Result<Connection, String> {
+    loop {
+        let (stream, addr) = listener
+            .accept()
+            .map_err(|e| format!("Could not accept a player: {}", e))?;
+        let mut conn = match handshake(stream) {
+            Ok(Some(conn)) => conn,
+            Ok(None) | Err(_) => continue,
+        };
+        if conn.send(&start(game)).is_ok() {
+            println!("{} joined the game", addr);
+            return Ok(conn);
+        }
+    }
+}
+
+// The connection once it said hello in the right version
+fn handshake(stream: TcpStream) -> io::Result<Option<Connection>> {
+    stream.set_read_timeout(Some(HELLO_TIMEOUT))?;
+    let mut conn = Connection::new(stream.try_clone()?)?;
+    match conn.receive()? {
+        Message::Hello { version } if version == PROTOCOL_VERSION => {}
+        Message::Hello { version } => {
+            let reason = format!(
+                "The host speaks protocol version {}, not {}",
+                PROTOCOL_VERSION, version
+            );
+            conn.send(&Message::Rejected { reason })?;
+            return Ok(None);
+        }
+        _ => {
+            let reason = "Expected a hello".to_string();
+            conn.send(&Message::Rejected { reason })?;
+            return Ok(None);
+        }
+    }
+    stream.set_read_timeout(None)?;
+    Ok(Some(conn))
+}
+
+fn start<G: NetGame>(game: &G) -> Message {
+    Message::Start {
+        game: Box::new(game.to_saved()),
+        guest: save::player_number(GUEST),
+    }
+}
+
+// Join the game hosted at addr
+pub fn join(addr: &str) -> Result<(), String> {
+    let (conn, saved, side) = connect(addr)?;
+    println!("Joined the game at {}", addr);
+    match saved.game.as_str() {
+        save::TOOT_OTTO => guest(addr, conn, TootOttoGame::from_saved(&saved)?, side),
+        _ => guest(addr, conn, ConnectFourGame::from_saved(&saved)?, side),
+    }
+}
+
+// Say hello to the host, returns the game so far and the side of this player
+fn connect(addr: &str) -> Result<(Connection, SavedGame, Player), String> {
+    let stream =
+        TcpStream::connect(addr).map_err(|e| format!("Could not connect to {}: {}", addr, e))?;
+    let lost = |e: io::Error| format!("Lost the connection to {}: {}", addr, e);
+    let mut conn = Connection::new(stream).map_err(lost)?;
+    conn.send(&Message::Hello {
+        version: PROTOCOL_VERSION,
+    })
+    .map_err(lost)?;
+    match conn.receive().map_err(lost)? {
+        Message::Start { game, guest } => Ok((conn, *game, save::number_player(guest)?)),
+        Message::Rejected { reason } => Err(reason),
+        _ => Err("The host sent an unexpected message".to_string()),
+    }
+}
+
+fn guest<G: NetGame>(
+    addr: &str,
+    mut conn: Connection,
+    mut game: G,
+    side: Player,
+) -> Result<(), String> {
+    game.show();
+    while game.is_running() {
+        let received = if game.current_player() == side {
+            match game.ask() {
+                Turn::Move(mv) => conn
+                    .send(&Message::Move { mv })
+                    .and_then(|_| conn.receive()),
+                Turn::Undo => {
+                    println!("Moves cannot be taken back in a network game");
+                    continue;
+                }
+                Turn::Hint => {
+                    game.hint();
+                    continue;
+                }
+                Turn::Save(path) => {
+                    game.save(&path);
+                    continue;
+                }
+                Turn::Quit => {
+                    let _ = conn.send(&Message::Quit);
+                    return Ok(());
+                }
+            }
+        } else {
+            println!("Waiting for the other player to move");
+            conn.receive()
+        };
+
+        match received {
+            // Played by the host already, so it is legal unless the games differ
+            Ok(Message::Move { mv }) => game
+                .play(&mv)
+                .map_err(|reason| format!("The host played {}: {}", mv, reason))?,
+            Ok(Message::Rejected { reason }) => println!("{}", reason),
+            Ok(Message::Quit) => {
+                println!("The other player left the game");
+                return Ok(());
+            }
+            Ok(_) => return Err("The host sent an unexpected message".to_string()),
+            Err(_) => {
+                let (new_conn, saved) = reconnect(addr)?;
+                conn = new_conn;
+                game = G::from_saved(&saved)?;
+                game.show();
+            }
+        }
+    }
+    Ok(())
+}
+
+fn reconnect(addr: &str) -> Result<(Connection, SavedGame), String> {
+    println!("Lost the connection to the host, reconnecting");
+    for _ in 0..RECONNECT_ATTEMPTS {
+        thread::sleep(Duration::from_secs(1));
+        if let Ok((conn, saved, _)) = connect(addr) {
+            println!("Reconnected to {}", addr);
+            return Ok((conn, saved));
+        }
+    }
+    Err(format!("Could not reconnect to {}", addr))
+}
@@ -0,0 +1,75 @@
+use crate::save::SavedGame;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+
+// Version of the messages below, both sides must speak the same one
+pub const PROTOCOL_VERSION: u32 = 1;
+
+// Messages between the host of a network game and the player that joins it, sent as
+// one JSON object per line. The host owns the game: the guest asks for its moves and
+// only plays them once the host sends them back
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    // First message of the guest
+    Hello {
+        version: u32,
+    },
+
+    // Answer to the hello with the game so far, which lets a guest that lost its
+    // connection continue. guest is the side of the guest, 1 or 2
+    Start {
+        game: Box<SavedGame>,
+        guest: u8,
+    },
+
+    // A move such as "3" or "T3", from the guest a request and from the host a move
+    // that was played
+    Move {
+        #[serde(rename = "move")]
+        mv: String,
+    },
+
+    // The host refused the hello or a move
+    Rejected {
+        reason: String,
+    },
+
+    // The player left the game
+    Quit,
+}
+
+pub struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        let writer = stream.try_clone()?;
+        Ok(Connection {
+            reader: BufReader::new(stream),
+            writer,
+        })
+    }
+
+    pub fn send(&mut self, message: &Message) -> io::Result<()> {
+        let mut line = serde_json::to_string(message).unwrap();
+        line.push('\n');
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.flush()
+    }
+
+    // Next message, an error once the other side is gone or sent something else
+    pub fn receive(&mut self) -> io::Result<Message> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Connection closed",
+            ));
+        }
+        serde_json::from_str(&line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
@@ -0,0 +1,241 @@
+use crate::connect_four::{Game as ConnectFourGame, State as ConnectFourState};
+use crate::input;
+use crate::search::SearchStats;
+use crate::toot_otto::{Game as TootOttoGame, Move as TootOttoMove, State as TootOttoState};
+use std::collections::HashMap;
+
+const COMMANDS: &str =
+    "Commands: Enter or n for the next move, p for the previous one, a move number, first, last, quit";
+
+// A position of the recorded game, the start or the board after one of the moves
+struct Position {
+    last_move: Option<String>,
+    board: String,
+    status: String,
+}
+
+// Steps through a recorded game, the engine evaluates each position the first time
+// it is shown
+pub struct Viewer {
+    positions: Vec<Position>,
+    evaluate: Box<dyn Fn(usize) -> Option<String>>,
+    evaluations: HashMap<usize, Option<String>>,
+    current: usize,
+}
+
+impl Viewer {
+    // Viewer for the moves played from game, which is left at its start
+    pub fn connect_four(game: ConnectFourGame, moves: &[usize]) -> Result<Self, String> {
+        let mut games = vec![game];
+        let mut positions = vec![connect_four_position(&games[0], None)];
+        for (idx, col) in moves.iter().enumerate() {
+            let mut game = games[idx].clone();
+            if game.state != ConnectFourState::Running {
+                return Err(format!(
+                    "The game is over before move {} ({})",
+                    idx + 1,
+                    col
+                ));
+            }
+            let player = game.player_name(game.current_player());
+            if game.make_move(*col).is_err() {
+                return Err(format!("Move {} ({}) is illegal", idx + 1, col));
+            }
+            let last_move = format!("{} dropped a chip in column {}", player, col);
+            positions.push(connect_four_position(&game, Some(last_move)));
+            games.push(game);
+        }
+
+        let evaluate = move |idx: usize| {
+            let game: &ConnectFourGame = &games[idx];
+            if game.state != ConnectFourState::Running {
+                return None;
+            }
+            let mut search = game.ai_search(game.player_move_translate() as i64);
+            search.run();
+            let stats = search.into_stats();
+            let line = stats
+                .principal_variation
+                .iter()
+                .map(|col| col.to_string())
+                .collect::<Vec<String>>();
+            let mover = game.player_name(game.current_player());
+            Some(evaluation(&mover, &stats, &line))
+        };
+        Ok(Viewer::new(positions, Box::new(evaluate)))
+    }
+
+    pub fn toot_otto(game: TootOttoGame, moves: &[TootOttoMove]) -> Result<Self, String> {
+        let mut games = vec![game];
+        let mut positions = vec![toot_otto_position(&games[0], None)];
+        for (idx, (chip_type, col)) in moves.iter().enumerate() {
+            let mut game = games[idx].clone();
+            if game.state != TootOttoState::Running {
+                return Err(format!(
+                    "The game is over before move {} ({}{})",
+                    idx + 1,
+                    chip_type,
+                    col
+                ));
+            }
+            let player = game.player_name(game.current_player());
+            if game.make_move(*chip_type, *col).is_err() {
+                return Err(format!(
+                    "Move {} ({}{}) is illegal",
+                    idx + 1,
+                    chip_type,
+                    col
+                ));
+            }
+            let last_move = format!("{} dropped {} in column {}", player, chip_type, col);
+            positions.push(toot_otto_position(&game, Some(last_move)));
+            games.push(game);
+        }
+
+        let evaluate = move |idx: usize| {
+            let game: &TootOttoGame = &games[idx];
+            if game.state != TootOttoState::Running {
+                return None;
+            }
+            let mut search = game.ai_search();
+            search.run();
+            let stats = search.into_stats();
+            let line = stats
+                .principal_variation
+                .iter()
+                .map(|(chip_type, col)| format!("{}{}", chip_type, col))
+                .collect::<Vec<String>>();
+            let mover = game.player_name(game.current_player());
+            Some(evaluation(&mover, &stats, &line))
+        };
+        Ok(Viewer::new(positions, Box::new(evaluate)))
+    }
+
+    fn new(positions: Vec<Position>, evaluate: Box<dyn Fn(usize) -> Option<String>>) -> Self {
+        Viewer {
+            positions,
+            evaluate,
+            evaluations: HashMap::new(),
+            current: 0,
+        }
+    }
+
+    fn last(&self) -> usize {
+        self.positions.len() - 1
+    }
+
+    // Read commands until quit or the end of input
+    pub fn run(&mut self) {
+        println!("{}", COMMANDS);
+        self.show();
+        while let Some(line) = input::read_line() {
+            let next = match line.to_lowercase().as_str() {
+                "" | "n" | "next" => self.current + 1,
+                "p" | "prev" | "b" | "back" => {
+                    if self.current == 0 {
+                        println!("This is the start of the game");
+                        continue;
+                    }
+                    self.current - 1
+                }
+                "first" | "start" => 0,
+                "last" | "end" => self.last(),
+                "q" | "quit" | "exit" => break,
+                "help" => {
+                    println!("{}", COMMANDS);
+                    continue;
+                }
+                x => match x.parse::<usize>() {
+                    Ok(idx) if idx <= self.last() => idx,
+                    _ => {
+                        println!("{} is not a command or a move from 0 to {}", x, self.last());
+                        println!("{}", COMMANDS);
+                        continue;
+                    }
+                },
+            };
+            if next > self.last() {
+                println!("This is the last move");
+                continue;
+            }
+            self.current = next;
+            self.show();
+        }
+    }
+
+    fn show(&mut self) {
+        let idx = self.current;
+        let position = &self.positions[idx];
+        match &position.last_move {
+            Some(text) => println!("Move {} of {}: {}", idx, self.last(), text),
+            None => println!("Start of the game, {} moves", self.last()),
+        }
+        print!("{}", position.board);
+        println!("{}", position.status);
+
+        let evaluate = &self.evaluate;
+        let text = self.evaluations.entry(idx).or_insert_with(|| evaluate(idx));
+        if let Some(text) = text {
+            println!("{}", text);
+        }
+    }
+}
+
+// Evaluation of the position from the side of mover, who is next to play
+fn evaluation<M>(mover: &str, stats: &SearchStats<M>, line: &[String]) -> String {
+    let score = match stats.forced_result() {
+        Some(moves) if moves > 0 => format!("{} wins within {} moves", mover, moves),
+        Some(moves) => format!("{} loses within {} moves", mover, -moves),
+        None => format!("{:+} for {}", stats.value, mover),
+    };
+    format!(
+        "Evaluation: {}\nBest line: {}\nSearch: {}",
+        score,
+        line.join(" "),
+        stats
+    )
+}
+
+fn column_numbers(num_cols: usize) -> String {
+    let mut text = String::new();
+    for i in 0..num_cols {
+        text += &format!("{} ", i);
+    }
+    text
+}
+
+fn connect_four_position(game: &ConnectFourGame, last_move: Option<String>) -> Position {
+    let status = match game.state {
+        ConnectFourState::Running => {
+            format!("{} to move", game.player_name(game.current_player()))
+        }
+        _ => result(&game.winner),
+    };
+    Position {
+        last_move,
+        board: format!("{}\n{}\n", column_numbers(game.grid.num_cols), game.grid),
+        status,
+    }
+}
+
+fn toot_otto_position(game: &TootOttoGame, last_move: Option<String>) -> Position {
+    let status = match game.state {
+        TootOttoState::Running => {
+            format!("{} to move", game.player_name(game.current_player()))
+        }
+        _ => result(&game.winner),
+    };
+    Position {
+        last_move,
+        board: format!("{}\n{}\n", column_numbers(game.grid.num_cols), game.grid),
+        status,
+    }
+}
+
+fn result(winner: &str) -> String {
+    if winner == "Draw" {
+        "The game ended in a draw".to_string()
+    } else {
+        format!("{} has won", winner)
+    }
+}
@@ -0,0 +1,224 @@
+use crate::board::{self, Player};
+use crate::{connect_four, toot_otto};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+// Version of the save format, bumped when old files can no longer be read
+pub const SAVE_VERSION: u32 = 1;
+
+pub const CONNECT_FOUR: &str = "connect4";
+pub const TOOT_OTTO: &str = "toot-otto";
+
+// Words and letters of a TOOT-OTTO game
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SavedVariant {
+    pub p1_word: String,
+    pub p2_word: String,
+    pub letters: String,
+    pub chips_per_letter: u32,
+}
+
+// A game in progress. The board is not stored, it is rebuilt by playing the moves
+// again, which also checks that they are legal. Players are 1 or 2
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SavedGame {
+    pub version: u32,
+    pub game: String,
+    pub rows: usize,
+    pub cols: usize,
+    pub p1: String,
+    pub p2: String,
+    pub with_ai: bool,
+    pub ai_player: u8,
+    pub first_player: u8,
+    pub max_ai_depth: u32,
+    pub evaluator: String,
+
+    // Columns for Connect-4, letter and column such as "T3" for TOOT-OTTO
+    pub moves: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<SavedVariant>,
+}
+
+pub fn player_number(player: Player) -> u8 {
+    match player {
+        Player::One => 1,
+        Player::Two => 2,
+    }
+}
+
+pub fn number_player(number: u8) -> Result<Player, String> {
+    match number {
+        1 => Ok(Player::One),
+        2 => Ok(Player::Two),
+        _ => Err(format!("There is no player {}", number)),
+    }
+}
+
+impl SavedGame {
+    pub fn from_connect_four(game: &connect_four::Game) -> Self {
+        let evaluator = match game.evaluator {
+            connect_four::Evaluator::Chains => "chains",
+            connect_four::Evaluator::Threats => "threats",
+        };
+        SavedGame {
+            version: SAVE_VERSION,
+            game: CONNECT_FOUR.to_string(),
+            rows: game.grid.num_rows,
+            cols: game.grid.num_cols,
+            p1: game.p1.clone(),
+            p2: game.p2.clone(),
+            with_ai: game.with_ai,
+            ai_player: player_number(game.ai_player),
+            first_player: player_number(game.first_player),
+            max_ai_depth: game.max_ai_depth,
+            evaluator: evaluator.to_string(),
+            moves: game.history.iter().map(|col| col.to_string()).collect(),
+            variant: None,
+        }
+    }
+
+    pub fn from_toot_otto(game: &toot_otto::Game) -> Self {
+        let evaluator = match game.evaluator {
+            toot_otto::Evaluator::Chains => "chains",
+            toot_otto::Evaluator::Patterns => "patterns",
+        };
+        let variant = &game.variant;
+        SavedGame {
+            version: SAVE_VERSION,
+            game: TOOT_OTTO.to_string(),
+            rows: game.grid.num_rows,
+            cols: game.grid.num_cols,
+            p1: game.p1.clone(),
+            p2: game.p2.clone(),
+            with_ai: game.with_ai,
+            ai_player: player_number(game.ai_player),
+            first_player: player_number(game.first_player),
+            max_ai_depth: game.max_ai_depth,
+            evaluator: evaluator.to_string(),
+            moves: game
+                .history
+                .iter()
+                .map(|(chip_type, col)| format!("{}{}", chip_type, col))
+                .collect(),
+            variant: Some(SavedVariant {
+                p1_word: variant.word_string(Player::One),
+                p2_word: variant.word_string(Player::Two),
+                letters: variant.letters.iter().map(|letter| letter.0).collect(),
+                chips_per_letter: variant.chips_per_letter,
+            }),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    // Parse a save, older formats are read and newer ones rejected
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let version = match serde_json::from_str::<serde_json::Value>(json) {
+            Ok(value) => value.get("version").and_then(|v| v.as_u64()),
+            Err(e) => return Err(format!("Not a saved game: {}", e)),
+        };
+        match version {
+            Some(v) if v > SAVE_VERSION as u64 => {
+                return Err(format!(
+                    "Saved with format {}, this version reads up to {}",
+                    v, SAVE_VERSION
+                ));
+            }
+            Some(_) => {}
+            None => return Err("Not a saved game: no format version".to_string()),
+        }
+        serde_json::from_str(json).map_err(|e| format!("Not a saved game: {}", e))
+    }
+
+    pub fn write(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_json() + "\n")
+            .map_err(|e| format!("Could not save to {}: {}", path, e))
+    }
+
+    pub fn read(path: &str) -> Result<Self, String> {
+        let json =
+            fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+        SavedGame::from_json(&json).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    fn check_board(&self, game: &str) -> Result<(), String> {
+        if self.game != game {
+            return Err(format!("This is a {} game, not {}", self.game, game));
+        }
+        board::check_size(self.rows, self.cols)?;
+        Ok(())
+    }
+
+    pub fn to_connect_four(&self) -> Result<connect_four::Game, String> {
+        self.check_board(CONNECT_FOUR)?;
+        let mut game = connect_four::Game::new(
+            self.rows,
+            self.cols,
+            self.with_ai,
+            self.p1.clone(),
+            self.p2.clone(),
+            self.max_ai_depth,
+        );
+        game.p2 = self.p2.clone();
+        game.ai_player = number_player(self.ai_player)?;
+        game.first_player = number_player(self.first_player)?;
+        game.evaluator = match self.evaluator.as_str() {
+            "chains" => connect_four::Evaluator::Chains,
+            "threats" => connect_four::Evaluator::Threats,
+            x => return Err(format!("Unknown evaluator {}", x)),
+        };
+
+        let moves = connect_four::parse_moves(&self.moves.join(" "))?;
+        for (idx, col) in moves.into_iter().enumerate() {
+            if game.state != connect_four::State::Running || game.make_move(col).is_err() {
+                return Err(format!("Move {} ({}) is illegal", idx + 1, col));
+            }
+        }
+        Ok(game)
+    }
+
+    pub fn to_toot_otto(&self) -> Result<toot_otto::Game, String> {
+        self.check_board(TOOT_OTTO)?;
+        let variant = match &self.variant {
+            Some(v) => {
+                toot_otto::Variant::new(&v.p1_word, &v.p2_word, &v.letters, v.chips_per_letter)?
+            }
+            None => return Err("The words of the game are missing".to_string()),
+        };
+        variant.check_board(self.rows, self.cols)?;
+        let mut game = toot_otto::Game::new(
+            self.rows,
+            self.cols,
+            self.with_ai,
+            self.p1.clone(),
+            self.p2.clone(),
+            self.max_ai_depth,
+            variant,
+        );
+        game.p2 = self.p2.clone();
+        game.ai_player = number_player(self.ai_player)?;
+        game.first_player = number_player(self.first_player)?;
+        game.evaluator = match self.evaluator.as_str() {
+            "chains" => toot_otto::Evaluator::Chains,
+            "patterns" => toot_otto::Evaluator::Patterns,
+            x => return Err(format!("Unknown evaluator {}", x)),
+        };
+
+        let moves = toot_otto::parse_moves(&self.moves.join(" "))?;
+        for (idx, (chip_type, col)) in moves.into_iter().enumerate() {
+            if game.state != toot_otto::State::Running || game.make_move(chip_type, col).is_err() {
+                return Err(format!(
+                    "Move {} ({}{}) is illegal",
+                    idx + 1,
+                    chip_type,
+                    col
+                ));
+            }
+        }
+        Ok(game)
+    }
+}
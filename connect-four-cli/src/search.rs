@@ -0,0 +1,234 @@
+use std::cmp::{max, min};
+use std::fmt;
+use std::time::Duration;
+use wasm_timer::Instant;
+
+// Statistics collected by the minimax search, reported back along with the move the AI
+// picked, M is the move type of the game (column for Connect-4, chip and column for TOOT-OTTO)
+#[derive(Clone, Debug)]
+pub struct SearchStats<M> {
+    pub nodes: u64,
+    pub cutoffs: u64,
+    pub depth_reached: u32,
+    pub elapsed: Duration,
+    pub principal_variation: Vec<M>,
+
+    // Score of the root for the side to move
+    pub value: i64,
+}
+
+impl<M> SearchStats<M> {
+    pub fn new() -> Self {
+        SearchStats {
+            nodes: 0,
+            cutoffs: 0,
+            depth_reached: 0,
+            elapsed: Duration::from_secs(0),
+            principal_variation: Vec::new(),
+            value: 0,
+        }
+    }
+
+    pub fn visit(&mut self, depth: u32) {
+        self.nodes += 1;
+        if depth > self.depth_reached {
+            self.depth_reached = depth;
+        }
+    }
+
+    pub fn nodes_per_second(&self) -> u64 {
+        let micros = self.elapsed.as_micros();
+        if micros == 0 {
+            return 0;
+        }
+        (self.nodes as u128 * 1_000_000 / micros) as u64
+    }
+
+    // Moves until the side to move wins (positive) or loses (negative) when the search
+    // found a forced result. Wins and losses score 999999 less the square of their depth,
+    // any other large value is not a result the search reached
+    pub fn forced_result(&self) -> Option<i64> {
+        let value = self.value.abs();
+        let reached = self.depth_reached as i64;
+        if value < 900000 || (value - 999999).abs() > reached * reached {
+            return None;
+        }
+        let depth = ((value - 999999).abs() as f64).sqrt().round() as i64;
+        Some(self.value.signum() * (depth + 1))
+    }
+}
+
+impl<M> Default for SearchStats<M> {
+    fn default() -> Self {
+        SearchStats::new()
+    }
+}
+
+impl<M> fmt::Display for SearchStats<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "nodes {}, cutoffs {}, depth {}, time {} ms, nps {}",
+            self.nodes,
+            self.cutoffs,
+            self.depth_reached,
+            self.elapsed.as_millis(),
+            self.nodes_per_second()
+        )
+    }
+}
+
+// Game specific half of the minimax search, Search below walks the tree
+pub trait SearchGame {
+    type Position: Clone;
+    type Move: Copy;
+
+    // Moves to search at a node, most promising first
+    fn moves(&self, position: &Self::Position, depth: u32, maximizing: bool) -> Vec<Self::Move>;
+
+    fn play(&self, position: &Self::Position, mv: Self::Move, maximizing: bool) -> Self::Position;
+
+    // Score of the position mv led to, None if the search should go deeper
+    fn leaf_value(&self, position: &Self::Position, depth: u32, mv: Self::Move) -> Option<i64>;
+
+    // Called when mv refuted the node at depth
+    fn cutoff(&mut self, _depth: u32, _maximizing: bool, _mv: Self::Move) {}
+}
+
+struct Frame<G: SearchGame> {
+    position: G::Position,
+    depth: u32,
+    maximizing: bool,
+    alpha: i64,
+    beta: i64,
+    moves: Vec<G::Move>,
+    next: usize,
+    value: i64,
+    line: Vec<G::Move>,
+}
+
+// Fail-soft alpha-beta search kept on an explicit stack, so that it can be run a
+// number of nodes at a time and resumed later (the web UI must not block)
+pub struct Search<G: SearchGame> {
+    game: G,
+    stack: Vec<Frame<G>>,
+    stats: SearchStats<G::Move>,
+}
+
+impl<G: SearchGame> Search<G> {
+    pub fn new(game: G, root: G::Position) -> Self {
+        let mut search = Search {
+            game,
+            stack: Vec::new(),
+            stats: SearchStats::new(),
+        };
+        search.push(root, 0, true, -100000000007, 100000000007);
+        search
+    }
+
+    fn push(&mut self, position: G::Position, depth: u32, maximizing: bool, alpha: i64, beta: i64) {
+        let moves = self.game.moves(&position, depth, maximizing);
+        let value = if maximizing {
+            -100000000007
+        } else {
+            100000000007
+        };
+        self.stack.push(Frame {
+            position,
+            depth,
+            maximizing,
+            alpha,
+            beta,
+            moves,
+            next: 0,
+            value,
+            line: Vec::new(),
+        });
+    }
+
+    // Search at most budget more nodes, returns true once the search is complete
+    pub fn step(&mut self, budget: u64) -> bool {
+        let start = Instant::now();
+        let mut visited = 0;
+
+        while visited < budget {
+            let top = match self.stack.last_mut() {
+                Some(frame) => frame,
+                None => break,
+            };
+
+            if top.next >= top.moves.len() {
+                let frame = self.stack.pop().unwrap();
+                if self.stack.is_empty() {
+                    self.stats.value = frame.value;
+                    self.stats.principal_variation = frame.line;
+                } else {
+                    self.backup(frame.value, frame.line);
+                }
+                continue;
+            }
+
+            let mv = top.moves[top.next];
+            top.next += 1;
+            let child = self.game.play(&top.position, mv, top.maximizing);
+            let (depth, maximizing, alpha, beta) = (top.depth, top.maximizing, top.alpha, top.beta);
+
+            self.stats.visit(depth);
+            visited += 1;
+
+            match self.game.leaf_value(&child, depth, mv) {
+                Some(value) => self.backup(value, Vec::new()),
+                None => self.push(child, depth + 1, !maximizing, alpha, beta),
+            }
+        }
+
+        self.stats.elapsed += start.elapsed();
+        self.is_done()
+    }
+
+    // Report the value of the last move searched to the frame on top of the stack
+    fn backup(&mut self, value: i64, line: Vec<G::Move>) {
+        let frame = self.stack.last_mut().unwrap();
+        let mv = frame.moves[frame.next - 1];
+
+        let better = if frame.maximizing {
+            value > frame.value
+        } else {
+            value < frame.value
+        };
+        if better {
+            frame.value = value;
+
+            // Line of play starting with this move
+            frame.line = vec![mv];
+            frame.line.extend(line);
+        }
+
+        let refuted = if frame.maximizing {
+            frame.value >= frame.beta
+        } else {
+            frame.value <= frame.alpha
+        };
+        if refuted {
+            self.stats.cutoffs += 1;
+            self.game.cutoff(frame.depth, frame.maximizing, mv);
+            frame.next = frame.moves.len();
+        } else if frame.maximizing {
+            frame.alpha = max(frame.alpha, frame.value);
+        } else {
+            frame.beta = min(frame.beta, frame.value);
+        }
+    }
+
+    pub fn run(&mut self) {
+        while !self.step(u64::MAX) {}
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    pub fn into_stats(self) -> SearchStats<G::Move> {
+        self.stats
+    }
+}
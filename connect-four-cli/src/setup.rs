@@ -0,0 +1,116 @@
+use crate::args::{First, Options};
+use crate::board::Player;
+use crate::connect_four::{Evaluator as ConnectFourEvaluator, Game as ConnectFourGame};
+use crate::toot_otto::{
+    Evaluator as TootOttoEvaluator, Game as TootOttoGame, Variant as TootOttoVariant,
+    CHIPS_PER_LETTER as TOOT_OTTO_CHIPS,
+};
+use rand::rngs::StdRng;
+use rand::Rng;
+
+// Evaluator used by the AI, "chains" (default) or "threats" for Connect-4
+pub fn connect_four_evaluator(options: &Options) -> Result<ConnectFourEvaluator, String> {
+    match options.evaluator.as_deref() {
+        Some("chains") | None => Ok(ConnectFourEvaluator::Chains),
+        Some("threats") => Ok(ConnectFourEvaluator::Threats),
+        Some(x) => Err(format!("Unknown evaluator {}", x)),
+    }
+}
+
+// Evaluator used by the AI, "patterns" (default) or "chains" for TOOT-OTTO
+pub fn toot_otto_evaluator(options: &Options) -> Result<TootOttoEvaluator, String> {
+    match options.evaluator.as_deref() {
+        Some("patterns") | None => Ok(TootOttoEvaluator::Patterns),
+        Some("chains") => Ok(TootOttoEvaluator::Chains),
+        Some(x) => Err(format!("Unknown evaluator {}", x)),
+    }
+}
+
+// Custom words as "--words P1WORD,P2WORD", letters default to the ones they use
+pub fn toot_otto_variant(options: &Options) -> Result<TootOttoVariant, String> {
+    match &options.words {
+        Some(x) => {
+            let words = x.split(',').collect::<Vec<&str>>();
+            if words.len() != 2 {
+                return Err("Expected two words separated by a comma".to_string());
+            }
+            let letters = options.letters.clone().unwrap_or_default();
+            TootOttoVariant::new(words[0], words[1], &letters, TOOT_OTTO_CHIPS)
+        }
+        None => Ok(TootOttoVariant::toot_otto()),
+    }
+}
+
+// Player that moves first when the computer plays ai_player
+pub fn first_player(first: First, ai_player: Player, rng: &mut StdRng) -> Player {
+    match first {
+        First::Human => ai_player.other(),
+        First::Computer => ai_player,
+        First::Random => {
+            if rng.gen() {
+                ai_player
+            } else {
+                ai_player.other()
+            }
+        }
+    }
+}
+
+// Game described by the options, against the computer when --ai-depth is given
+pub fn connect_four_game(options: &Options, rng: &mut StdRng) -> Result<ConnectFourGame, String> {
+    let mut game = match options.ai_depth {
+        Some(depth) => {
+            let mut game = ConnectFourGame::new(
+                options.rows,
+                options.cols,
+                true,
+                "Player".to_string(),
+                "Computer".to_string(),
+                depth,
+            );
+            game.first_player = first_player(options.first, game.ai_player, rng);
+            game
+        }
+        None => ConnectFourGame::new(
+            options.rows,
+            options.cols,
+            false,
+            "P1".to_string(),
+            "P2".to_string(),
+            4,
+        ),
+    };
+    game.evaluator = connect_four_evaluator(options)?;
+    Ok(game)
+}
+
+pub fn toot_otto_game(options: &Options, rng: &mut StdRng) -> Result<TootOttoGame, String> {
+    let variant = toot_otto_variant(options)?;
+    variant.check_board(options.rows, options.cols)?;
+    let mut game = match options.ai_depth {
+        Some(depth) => {
+            let mut game = TootOttoGame::new(
+                options.rows,
+                options.cols,
+                true,
+                "Player".to_string(),
+                "Computer".to_string(),
+                depth,
+                variant,
+            );
+            game.first_player = first_player(options.first, game.ai_player, rng);
+            game
+        }
+        None => TootOttoGame::new(
+            options.rows,
+            options.cols,
+            false,
+            "P1".to_string(),
+            "P2".to_string(),
+            3,
+            variant,
+        ),
+    };
+    game.evaluator = toot_otto_evaluator(options)?;
+    Ok(game)
+}
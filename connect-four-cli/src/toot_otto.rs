@@ -1,21 +1,227 @@
+use crate::board::{diagram_rows, is_empty_cell, Board, Player, Turn};
+use crate::search::{Search, SearchGame, SearchStats};
+use rand::seq::SliceRandom;
 use rand::Rng;
-use std::cmp::{max, min};
 use std::fmt;
 
+// A letter tile, upper case
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ChipType(pub char);
+
+impl fmt::Display for ChipType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// A move is a chip type dropped into a column
+pub type Move = (ChipType, usize);
+
+// Who played the chip and its letter, None for an empty cell
+pub type Cell = Option<(Player, ChipType)>;
+
+pub type Grid = Board<Cell>;
+
+// Tiles of each letter a player starts with
+pub const CHIPS_PER_LETTER: u32 = 6;
+
+// Words and letters of the game, TOOT-OTTO by default. Player one wins by spelling
+// p1_word and player two by spelling p2_word, read in either direction along a line
+#[derive(Clone, PartialEq, Debug)]
+pub struct Variant {
+    pub p1_word: Vec<ChipType>,
+    pub p2_word: Vec<ChipType>,
+    pub letters: Vec<ChipType>,
+    pub chips_per_letter: u32,
+}
+
+impl Variant {
+    pub fn toot_otto() -> Self {
+        Variant::new("TOOT", "OTTO", "TO", CHIPS_PER_LETTER).unwrap()
+    }
+
+    // Both words must have the same length, only use the given letters from A to Z and
+    // not be each other read backwards, an empty letter set is every letter of the words
+    pub fn new(
+        p1_word: &str,
+        p2_word: &str,
+        letters: &str,
+        chips_per_letter: u32,
+    ) -> Result<Self, String> {
+        // Letters are typed and shown in ASCII, see input::parse_letter
+        let to_chips = |word: &str| {
+            word.trim()
+                .chars()
+                .map(|letter| {
+                    if letter.is_ascii_alphabetic() {
+                        Ok(ChipType(letter.to_ascii_uppercase()))
+                    } else {
+                        Err(format!("{} is not a letter from A to Z", letter))
+                    }
+                })
+                .collect::<Result<Vec<ChipType>, String>>()
+        };
+        let p1_word = to_chips(p1_word)?;
+        let p2_word = to_chips(p2_word)?;
+
+        if p1_word.len() < 2 || p1_word.len() != p2_word.len() {
+            return Err("Both words must have the same length, at least 2".to_string());
+        }
+        if p1_word == p2_word {
+            return Err("The words must be different".to_string());
+        }
+        // Read backwards one word is the other, every line would spell both
+        if p1_word.iter().eq(p2_word.iter().rev()) {
+            return Err("One word must not be the other backwards".to_string());
+        }
+
+        let all_letters = if letters.trim().is_empty() {
+            p1_word.iter().chain(p2_word.iter()).cloned().collect()
+        } else {
+            to_chips(letters)?
+        };
+        let mut letter_set = Vec::new();
+        for letter in all_letters {
+            if !letter_set.contains(&letter) {
+                letter_set.push(letter);
+            }
+        }
+        for letter in p1_word.iter().chain(p2_word.iter()) {
+            if !letter_set.contains(letter) {
+                return Err(format!("{} is not one of the letters", letter));
+            }
+        }
+
+        Ok(Variant {
+            p1_word,
+            p2_word,
+            letters: letter_set,
+            chips_per_letter,
+        })
+    }
+
+    // The words must fit along a row or a column of the board, no diagonal is longer
+    pub fn check_board(&self, rows: usize, cols: usize) -> Result<(), String> {
+        if self.p1_word.len() > rows.max(cols) {
+            return Err(format!(
+                "The words are longer than any line of a {} x {} board",
+                rows, cols
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn word(&self, player: Player) -> &[ChipType] {
+        match player {
+            Player::One => &self.p1_word,
+            Player::Two => &self.p2_word,
+        }
+    }
+
+    pub fn word_string(&self, player: Player) -> String {
+        self.word(player).iter().map(|chip| chip.0).collect()
+    }
+}
+
+// A word on the board, from its first to its last letter as (row, col)
+#[derive(Clone, PartialEq, Debug)]
+pub struct SpelledWord {
+    pub player: Player,
+    pub word: String,
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+}
+
+// Tiles a player has left to play, per letter
+#[derive(Clone, PartialEq, Debug)]
+pub struct Inventory {
+    pub counts: Vec<(ChipType, u32)>,
+}
+
+impl Inventory {
+    pub fn new(letters: &[ChipType], count: u32) -> Self {
+        Inventory {
+            counts: letters.iter().map(|letter| (*letter, count)).collect(),
+        }
+    }
+
+    pub fn remaining(&self, chip_type: ChipType) -> u32 {
+        match self.counts.iter().find(|(letter, _)| *letter == chip_type) {
+            Some((_, count)) => *count,
+            None => 0,
+        }
+    }
+
+    pub fn total(&self) -> u32 {
+        self.counts.iter().map(|(_, count)| count).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+
+    // Use up one tile, fails if none of that letter are left
+    pub fn take(&mut self, chip_type: ChipType) -> Result<(), ()> {
+        match self
+            .counts
+            .iter_mut()
+            .find(|(letter, _)| *letter == chip_type)
+        {
+            Some((_, count)) if *count > 0 => {
+                *count -= 1;
+                Ok(())
+            }
+            _ => Err(()),
+        }
+    }
+
+    // Hand back a tile of a move that was taken back
+    pub fn put_back(&mut self, chip_type: ChipType) {
+        if let Some((_, count)) = self
+            .counts
+            .iter_mut()
+            .find(|(letter, _)| *letter == chip_type)
+        {
+            *count += 1;
+        }
+    }
+}
+
+impl fmt::Display for Inventory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let counts = self
+            .counts
+            .iter()
+            .map(|(letter, count)| format!("{} {}", count, letter))
+            .collect::<Vec<String>>();
+        write!(f, "{}", counts.join(", "))
+    }
+}
+
+// Position evaluators the AI can use at the leaves of the search
 #[derive(Clone, Copy, PartialEq)]
-pub enum ChipType {
-    T,
-    O,
+pub enum Evaluator {
+    // Sum of cubed letter matches of the AI's word over every window
+    Chains,
+
+    // Partial matches of either word per window, from the side the AI plays
+    Patterns,
 }
 
 pub trait GameEvents {
     fn introduction(&self);
-    fn show_grid(&self, grid: &DummyGrid);
+    fn show_grid(&self, grid: &Grid);
     fn player_turn_message(&self, p1_turn: bool);
-    fn player_turn(&self, col_size: usize) -> Result<(ChipType, usize), ()>;
+    fn player_turn(&self, col_size: usize, chips: Inventory) -> Turn<Move>;
     fn selected_column(&self, player: String, chip_type: ChipType, col: usize);
     fn animate_chip(&self);
+    fn search_stats(&self, stats: &SearchStats<Move>);
+    fn hint(&self, chip_type: ChipType, col: usize, stats: &SearchStats<Move>);
+    fn moves_undone(&self, count: usize);
+    fn save_game(&self, path: &str, game: &Game);
     fn invalid_move(&self);
+    fn out_of_chips(&self, chip_type: ChipType);
+    fn words_spelled(&self, words: &[SpelledWord]);
     fn game_over(&self, winner: String);
 }
 
@@ -34,7 +240,6 @@ pub enum State {
 #[derive(Clone)]
 pub struct Game {
     pub grid: Grid,
-    pub dummy_grid: DummyGrid,
     pub p1: String,
     pub p2: String,
     pub with_ai: bool,
@@ -42,6 +247,22 @@ pub struct Game {
     pub winner: String,
     pub p_move: i64,
     pub max_ai_depth: u32,
+    pub p1_chips: Inventory,
+    pub p2_chips: Inventory,
+    pub evaluator: Evaluator,
+    pub variant: Variant,
+
+    // Words spelled by the last move
+    pub words: Vec<SpelledWord>,
+
+    // Player that drops the first tile, player one always spells the first word
+    pub first_player: Player,
+
+    // Player the computer plays when with_ai is set
+    pub ai_player: Player,
+
+    // Moves played so far, in order
+    pub history: Vec<Move>,
 }
 
 impl Game {
@@ -52,12 +273,11 @@ impl Game {
         p1_name: String,
         p2_name: String,
         max_depth: u32,
+        variant: Variant,
     ) -> Game {
         let grid = Grid::new(row_size, col_size);
-        let dummy_grid = DummyGrid::new(row_size, col_size);
         let mut game = Game {
             grid,
-            dummy_grid,
             p1: p1_name,
             p2: p2_name,
             with_ai: false,
@@ -65,6 +285,14 @@ impl Game {
             winner: "".to_string(),
             p_move: 0,
             max_ai_depth: max_depth,
+            p1_chips: Inventory::new(&variant.letters, variant.chips_per_letter),
+            p2_chips: Inventory::new(&variant.letters, variant.chips_per_letter),
+            evaluator: Evaluator::Patterns,
+            variant,
+            words: Vec::new(),
+            first_player: Player::One,
+            ai_player: Player::Two,
+            history: Vec::new(),
         };
         if with_ai {
             game.p2 = "Computer".to_string();
@@ -73,6 +301,21 @@ impl Game {
         game
     }
 
+    // Let the computer play player instead of player two, the names follow the sides
+    pub fn set_ai_player(&mut self, player: Player) {
+        if player != self.ai_player {
+            std::mem::swap(&mut self.p1, &mut self.p2);
+            self.ai_player = player;
+        }
+    }
+
+    pub fn player_name(&self, player: Player) -> String {
+        match player {
+            Player::One => self.p1.clone(),
+            Player::Two => self.p2.clone(),
+        }
+    }
+
     #[allow(dead_code)] // Used by web
     pub fn start_game(&mut self) {
         self.state = State::Running;
@@ -80,48 +323,76 @@ impl Game {
 
     pub fn start_game_cli<H: GameEvents>(&mut self, handler: H) {
         handler.introduction();
-        let mut p1_turn = true;
         let col_size = self.grid.num_cols;
         while self.state == State::Running {
-            handler.show_grid(&self.dummy_grid);
-            handler.player_turn_message(p1_turn);
-            if !p1_turn && self.with_ai {
-                let (chip_type, col_num) = self.ai_move_val();
-                let grid_val = self.player_move_translate();
-                if self.grid.insert_chip(col_num, grid_val).is_err() {
+            let player = self.current_player();
+            handler.show_grid(&self.grid);
+            handler.player_turn_message(player == Player::One);
+            let (row, col_num) = if self.with_ai && player == self.ai_player {
+                let (chip_type, col_num, stats) = self.ai_move_val();
+                handler.search_stats(&stats);
+                if self.current_chips().remaining(chip_type) == 0 {
                     continue;
                 }
-                let chip_value = self.player_move_dummy_translate(chip_type);
-                self.dummy_grid.insert_chip(col_num, chip_value).unwrap();
+                let cell = Some((player, chip_type));
+                let row = match self.grid.insert_chip(col_num, cell) {
+                    Ok(r) => r,
+                    Err(_) => continue,
+                };
+                self.current_chips_mut().take(chip_type).unwrap();
                 self.p_move += 1;
-                handler.selected_column(self.p1.clone(), chip_type, col_num);
-                p1_turn = !p1_turn;
+                self.history.push((chip_type, col_num));
+                handler.selected_column(self.player_name(player), chip_type, col_num);
+                (row, col_num)
             } else {
-                let sel_col = handler.player_turn(col_size);
-                if sel_col.is_ok() {
-                    let (chip_type, col_num) = sel_col.unwrap();
-                    let grid_val = self.player_move_translate();
-                    let insert_result = self.grid.insert_chip(col_num, grid_val);
-                    if insert_result.is_err() {
-                        handler.invalid_move();
+                let (chip_type, col_num) = match handler.player_turn(col_size, self.current_chips())
+                {
+                    Turn::Move(choice) => choice,
+                    Turn::Undo => {
+                        // Against the computer take back its reply as well
+                        let mut count = 0;
+                        while self.undo_move().is_ok() {
+                            count += 1;
+                            if !self.with_ai || self.current_player() != self.ai_player {
+                                break;
+                            }
+                        }
+                        handler.moves_undone(count);
                         continue;
                     }
-                    let chip_value = self.player_move_dummy_translate(chip_type);
-                    self.dummy_grid.insert_chip(col_num, chip_value).unwrap();
-                    self.p_move += 1;
-                    if p1_turn {
-                        handler.selected_column(self.p1.clone(), chip_type, col_num);
-                    } else {
-                        handler.selected_column(self.p2.clone(), chip_type, col_num);
+                    Turn::Hint => {
+                        let (chip_type, col_num, stats) = self.ai_move_val();
+                        handler.hint(chip_type, col_num, &stats);
+                        continue;
                     }
-                } else {
+                    Turn::Save(path) => {
+                        handler.save_game(&path, self);
+                        continue;
+                    }
+                    Turn::Quit => return,
+                };
+                if self.current_chips().remaining(chip_type) == 0 {
+                    handler.out_of_chips(chip_type);
                     continue;
                 }
-                p1_turn = !p1_turn;
-            }
-            let result = self.check_win();
+                let cell = Some((player, chip_type));
+                let insert_result = self.grid.insert_chip(col_num, cell);
+                if insert_result.is_err() {
+                    handler.invalid_move();
+                    continue;
+                }
+                self.current_chips_mut().take(chip_type).unwrap();
+                self.p_move += 1;
+                self.history.push((chip_type, col_num));
+                handler.selected_column(self.player_name(player), chip_type, col_num);
+                (insert_result.unwrap(), col_num)
+            };
+            let result = self.check_win(row, col_num);
             if result.is_some() {
-                handler.show_grid(&self.dummy_grid);
+                handler.show_grid(&self.grid);
+                if !self.words.is_empty() {
+                    handler.words_spelled(&self.words);
+                }
                 let winner = result.unwrap();
                 if winner >= 1 {
                     self.winner = self.p1.clone();
@@ -131,7 +402,7 @@ impl Game {
                     handler.game_over(self.winner.clone());
                 } else if winner == 0 {
                     self.winner = "Draw".to_string();
-                    println!("Draw");
+                    handler.game_over(self.winner.clone());
                 }
                 self.state = State::Done;
                 self.post_game();
@@ -141,18 +412,50 @@ impl Game {
 
     fn post_game(&self) {}
 
-    pub fn player_move_translate(&self) -> i32 {
+    pub fn current_player(&self) -> Player {
         if (self.p_move % 2) == 0 {
+            return self.first_player;
+        }
+        return self.first_player.other();
+    }
+
+    // Tiles left for the player whose turn it is
+    pub fn current_chips(&self) -> Inventory {
+        match self.current_player() {
+            Player::One => self.p1_chips.clone(),
+            Player::Two => self.p2_chips.clone(),
+        }
+    }
+
+    fn current_chips_mut(&mut self) -> &mut Inventory {
+        match self.current_player() {
+            Player::One => &mut self.p1_chips,
+            Player::Two => &mut self.p2_chips,
+        }
+    }
+
+    // Sign of a letter for the chains evaluator, 1 for the first letter and -1 otherwise
+    fn chip_value(&self, chip_type: ChipType) -> i64 {
+        if chip_type == self.variant.letters[0] {
             return 1;
         }
         return -1;
     }
 
-    pub fn player_move_dummy_translate(&self, chip_type: ChipType) -> i32 {
-        match chip_type {
-            ChipType::T => 1,
-            ChipType::O => -1,
+    // Whether the chips along line spell word, read from its end when backwards is set
+    fn reads(line: &[Cell], word: &[ChipType], backwards: bool) -> bool {
+        if line.len() != word.len() {
+            return false;
         }
+        let len = word.len();
+        (0..len).all(|k| {
+            let letter = if backwards {
+                word[len - 1 - k]
+            } else {
+                word[k]
+            };
+            line[k].map(|(_, chip_type)| chip_type) == Some(letter)
+        })
     }
 
     #[allow(dead_code)] // Used by web
@@ -160,19 +463,23 @@ impl Game {
         &mut self,
         chip_type: ChipType,
         col_num: usize,
-    ) -> Result<(usize, usize, i32), ()> {
-        let grid_val = self.player_move_translate();
+    ) -> Result<(usize, usize, ChipType), ()> {
+        if col_num >= self.grid.num_cols || self.current_chips().remaining(chip_type) == 0 {
+            return Err(());
+        }
 
-        let insert_result = self.grid.insert_chip(col_num, grid_val);
+        let cell = Some((self.current_player(), chip_type));
+
+        let insert_result = self.grid.insert_chip(col_num, cell);
         if insert_result.is_err() {
             return Err(());
         }
-        let chip_value = self.player_move_dummy_translate(chip_type);
-        self.dummy_grid.insert_chip(col_num, chip_value).unwrap();
+        self.current_chips_mut().take(chip_type).unwrap();
 
         self.p_move += 1;
+        self.history.push((chip_type, col_num));
 
-        let result = self.check_win();
+        let result = self.check_win(insert_result.unwrap(), col_num);
         if result.is_some() {
             let winner = result.unwrap();
             if winner > 0 {
@@ -189,100 +496,181 @@ impl Game {
         return Ok((
             insert_result.unwrap(),
             (self.p_move - 1) as usize,
-            chip_value,
+            chip_type,
         ));
     }
 
-    fn check_win(&self) -> Option<i64> {
-        #[allow(non_snake_case)]
-        let T = self.player_move_dummy_translate(ChipType::T);
-        #[allow(non_snake_case)]
-        let O = self.player_move_dummy_translate(ChipType::O);
-
-        let mut temp_r1 = [0; 4];
-        let mut temp_b1 = [0; 4];
-        let mut temp_br1 = [0; 4];
-        let mut temp_br2 = [0; 4];
-
-        for i in 0..self.dummy_grid.num_rows {
-            for j in 0..self.dummy_grid.num_cols {
-                temp_r1[0] = 0;
-                temp_r1[1] = 0;
-                temp_r1[2] = 0;
-                temp_r1[3] = 0;
-                temp_b1[0] = 0;
-                temp_b1[1] = 0;
-                temp_b1[2] = 0;
-                temp_b1[3] = 0;
-                temp_br1[0] = 0;
-                temp_br1[1] = 0;
-                temp_br1[2] = 0;
-                temp_br1[3] = 0;
-                temp_br2[0] = 0;
-                temp_br2[1] = 0;
-                temp_br2[2] = 0;
-                temp_br2[3] = 0;
-
-                for k in 0..4 {
-                    // From (i,j) to right
-                    if j + k < self.dummy_grid.num_cols {
-                        temp_r1[k] = self.dummy_grid.get(i, j + k);
-                    }
+    // Take back the last move and return its tile, a game that move ended goes on
+    pub fn undo_move(&mut self) -> Result<Move, ()> {
+        let (chip_type, col_num) = match self.history.pop() {
+            Some(choice) => choice,
+            None => return Err(()),
+        };
+        self.grid.remove_chip(col_num)?;
+        self.p_move -= 1;
+        self.current_chips_mut().put_back(chip_type);
+        self.words.clear();
+        self.winner = "".to_string();
+        self.state = State::Running;
+        Ok((chip_type, col_num))
+    }
 
-                    // From (i,j) to bottom
-                    if i + k < self.dummy_grid.num_rows {
-                        temp_b1[k] = self.dummy_grid.get(i + k, j);
-                    }
+    // Words spelled through the chip at (row, col), every line of the word length in
+    // every direction is checked so that a move completing several words reports all of them
+    pub fn spelled_words(&self, row: usize, col: usize) -> Vec<SpelledWord> {
+        let length = self.variant.p1_word.len() as i64;
+        let num_rows = self.grid.num_rows as i64;
+        let num_cols = self.grid.num_cols as i64;
+        let in_bounds = |i: i64, j: i64| i >= 0 && i < num_rows && j >= 0 && j < num_cols;
+
+        let mut words = Vec::new();
+
+        // Right, bottom, bottom-right and top-right
+        let directions: [(i64, i64); 4] = [(0, 1), (1, 0), (1, 1), (-1, 1)];
+        for (di, dj) in directions.iter() {
+            // Lines covering (row, col), starting up to a word length before it
+            for offset in 0..length {
+                let start = (row as i64 - offset * di, col as i64 - offset * dj);
+                let end = (start.0 + (length - 1) * di, start.1 + (length - 1) * dj);
+                if !in_bounds(start.0, start.1) || !in_bounds(end.0, end.1) {
+                    continue;
+                }
 
-                    // From (i,j) to bottom-right
-                    if i + k < self.dummy_grid.num_rows && j + k < self.dummy_grid.num_cols {
-                        temp_br1[k] = self.dummy_grid.get(i + k, j + k);
-                    }
+                let line = (0..length)
+                    .map(|k| {
+                        self.grid
+                            .get((start.0 + k * di) as usize, (start.1 + k * dj) as usize)
+                    })
+                    .collect::<Vec<Cell>>();
+
+                for player in [Player::One, Player::Two].iter() {
+                    let word = self.variant.word(*player);
+                    let (from, to) = if Game::reads(&line, word, false) {
+                        (start, end)
+                    } else if Game::reads(&line, word, true) {
+                        (end, start)
+                    } else {
+                        continue;
+                    };
+                    words.push(SpelledWord {
+                        player: *player,
+                        word: self.variant.word_string(*player),
+                        from: (from.0 as usize, from.1 as usize),
+                        to: (to.0 as usize, to.1 as usize),
+                    });
+                }
+            }
+        }
 
-                    // From (i,j) to top-right
-                    if i as i64 - k as i64 >= 0 && j + k < self.dummy_grid.num_cols {
-                        temp_br2[k] = self.dummy_grid.get(i - k, j + k);
-                    }
+        words
+    }
+
+    // Set up the position of a board diagram, see board::diagram_rows. Upper case letters
+    // are tiles of player one and lower case ones tiles of player two, they come out of
+    // the players' tiles. The player with fewer tiles on the board moves next, player one
+    // when both have as many. The moves that led there are not known
+    pub fn load_diagram(&mut self, diagram: &str) -> Result<(), String> {
+        let rows = diagram_rows(diagram)?;
+        self.variant.check_board(rows.len(), rows[0].len())?;
+        let mut grid = Grid::new(rows.len(), rows[0].len());
+        let mut p1_chips = Inventory::new(&self.variant.letters, self.variant.chips_per_letter);
+        let mut p2_chips = p1_chips.clone();
+        for (r, row) in rows.iter().enumerate() {
+            for (c, cell) in row.iter().enumerate() {
+                if is_empty_cell(*cell) {
+                    continue;
+                }
+                let chip_type = ChipType(cell.to_ascii_uppercase());
+                let (player, chips) = if cell.is_uppercase() {
+                    (Player::One, &mut p1_chips)
+                } else {
+                    (Player::Two, &mut p2_chips)
+                };
+                if chips.take(chip_type).is_err() {
+                    return Err(format!(
+                        "{} is not one of the letters or there are too many of it",
+                        cell
+                    ));
                 }
+                grid.set(r, c, Some((player, chip_type)));
+            }
+        }
 
-                if temp_r1[0] == T && temp_r1[1] == O && temp_r1[2] == O && temp_r1[3] == T {
-                    return Some(1);
-                } else if temp_r1[0] == O && temp_r1[1] == T && temp_r1[2] == T && temp_r1[3] == O {
-                    return Some(-1);
-                } else if temp_b1[0] == T && temp_b1[1] == O && temp_b1[2] == O && temp_b1[3] == T {
-                    return Some(1);
-                } else if temp_b1[0] == O && temp_b1[1] == T && temp_b1[2] == T && temp_b1[3] == O {
-                    return Some(-1);
-                } else if temp_br1[0] == T
-                    && temp_br1[1] == O
-                    && temp_br1[2] == O
-                    && temp_br1[3] == T
-                {
-                    return Some(1);
-                } else if temp_br1[0] == O
-                    && temp_br1[1] == T
-                    && temp_br1[2] == T
-                    && temp_br1[3] == O
-                {
-                    return Some(-1);
-                } else if temp_br2[0] == T
-                    && temp_br2[1] == O
-                    && temp_br2[2] == O
-                    && temp_br2[3] == T
-                {
-                    return Some(1);
-                } else if temp_br2[0] == O
-                    && temp_br2[1] == T
-                    && temp_br2[2] == T
-                    && temp_br2[3] == O
-                {
-                    return Some(-1);
+        let total = self.variant.chips_per_letter * self.variant.letters.len() as u32;
+        let p1_count = (total - p1_chips.total()) as i64;
+        let p2_count = (total - p2_chips.total()) as i64;
+        self.first_player = if p1_count == p2_count || p1_count == p2_count + 1 {
+            Player::One
+        } else if p2_count == p1_count + 1 {
+            Player::Two
+        } else {
+            return Err(format!(
+                "Player one has {} tiles and player two {}, that is not a position of a game",
+                p1_count, p2_count
+            ));
+        };
+        self.grid = grid;
+        self.p1_chips = p1_chips;
+        self.p2_chips = p2_chips;
+        self.p_move = p1_count + p2_count;
+        self.history.clear();
+        self.words.clear();
+        self.state = State::Running;
+        self.winner = "".to_string();
+
+        // Words anywhere on the board, the last move is not known
+        let mut result = None;
+        for r in 0..self.grid.num_rows {
+            for c in 0..self.grid.num_cols {
+                if self.grid.is_empty(r, c) {
+                    continue;
+                }
+                let words = self.words.clone();
+                let found = self.check_win(r, c);
+                for word in words {
+                    if !self.words.contains(&word) {
+                        self.words.push(word);
+                    }
                 }
+                result = match (result, found) {
+                    (Some(a), Some(b)) if a != b => Some(0),
+                    (None, found) => found,
+                    (result, _) => result,
+                };
             }
         }
+        if let Some(winner) = result {
+            self.winner = if winner > 0 {
+                self.p1.clone()
+            } else if winner < 0 {
+                self.p2.clone()
+            } else {
+                "Draw".to_string()
+            };
+            self.state = State::Done;
+        }
+        Ok(())
+    }
+
+    // Result after the chip at (row, col) was played, the words it spelled are kept in
+    // self.words. Spelling both players' words with the same chip is a draw
+    fn check_win(&mut self, row: usize, col: usize) -> Option<i64> {
+        self.words = self.spelled_words(row, col);
+        let p1_word = self.words.iter().any(|w| w.player == Player::One);
+        let p2_word = self.words.iter().any(|w| w.player == Player::Two);
+
+        if p1_word && p2_word {
+            return Some(0);
+        } else if p1_word {
+            return Some(1);
+        } else if p2_word {
+            return Some(-1);
+        }
 
-        // Draw
-        if self.p_move == (self.dummy_grid.num_rows * self.dummy_grid.num_cols) as i64 {
+        // Draw, the board is full or both players are out of tiles
+        if self.p_move == (self.grid.num_rows * self.grid.num_cols) as i64
+            || (self.p1_chips.is_empty() && self.p2_chips.is_empty())
+        {
             match self.state {
                 State::Done => {}
                 _ => {
@@ -295,24 +683,69 @@ impl Game {
     }
 
     #[allow(dead_code)] // Used by web
-    pub fn ai_make_move(&mut self) -> Result<(usize, usize, usize, i32), ()> {
-        let (chip_type, mut col_num) = self.ai_move_val();
-        let grid_val = self.player_move_translate();
+    pub fn ai_make_move(
+        &mut self,
+    ) -> Result<(usize, usize, usize, ChipType, SearchStats<Move>), ()> {
+        let mut search = self.ai_search();
+        search.run();
+        self.ai_apply_move(search)
+    }
+
+    // Search for the AI move without running it, the caller steps the search a few
+    // nodes at a time and hands it to ai_apply_move once it is done
+    pub fn ai_search(&self) -> Search<SearchContext> {
+        let (ai_chips, opponent_chips) = match self.current_player() {
+            Player::One => (self.p1_chips.clone(), self.p2_chips.clone()),
+            Player::Two => (self.p2_chips.clone(), self.p1_chips.clone()),
+        };
+        let position = SearchPosition {
+            grid: self.grid.clone(),
+            ai_chips,
+            opponent_chips,
+        };
+        Search::new(SearchContext::new(self.clone()), position)
+    }
+
+    #[allow(dead_code)] // Used by web
+    pub fn ai_apply_move(
+        &mut self,
+        search: Search<SearchContext>,
+    ) -> Result<(usize, usize, usize, ChipType, SearchStats<Move>), ()> {
+        let stats = search.into_stats();
+
+        // No legal move, fall back to a random column
+        let (mut chip_type, mut col_num) = match stats.principal_variation.first() {
+            Some(choice) => *choice,
+            None => (self.variant.letters[0], 0),
+        };
+        if self.current_chips().remaining(chip_type) == 0 {
+            let chips = self.current_chips();
+            chip_type = match self
+                .variant
+                .letters
+                .iter()
+                .find(|letter| chips.remaining(**letter) > 0)
+            {
+                Some(letter) => *letter,
+                None => return Err(()),
+            };
+        }
+        let cell = Some((self.current_player(), chip_type));
 
-        let mut insert_result = self.grid.insert_chip(col_num, grid_val);
+        let mut insert_result = self.grid.insert_chip(col_num, cell);
 
         // Fall back to random agent
         while insert_result.is_err() {
             let mut rng = rand::thread_rng();
             col_num = rng.gen_range(0, self.grid.num_cols);
-            insert_result = self.grid.insert_chip(col_num, grid_val);
+            insert_result = self.grid.insert_chip(col_num, cell);
         }
-        let chip_value = self.player_move_dummy_translate(chip_type);
-        self.dummy_grid.insert_chip(col_num, chip_value).unwrap();
+        self.current_chips_mut().take(chip_type).unwrap();
 
         self.p_move += 1;
+        self.history.push((chip_type, col_num));
 
-        let result = self.check_win();
+        let result = self.check_win(insert_result.unwrap(), col_num);
         if result.is_some() {
             let winner = result.unwrap();
             if winner > 0 {
@@ -330,171 +763,248 @@ impl Game {
             insert_result.unwrap(),
             (self.p_move - 1) as usize,
             col_num,
-            chip_value,
+            chip_type,
+            stats,
         ));
     }
 
-    fn ai_move_val(&self) -> (ChipType, usize) {
-        let state = &self.dummy_grid.clone();
-
-        // Play T
-        let (t_val, t_move) = self.ai_max_state(
-            &state,
-            0,
-            -100000000007,
-            100000000007,
-            self.player_move_dummy_translate(ChipType::T) as i64,
-        );
-        // Play O
-        let (o_val, o_move) = self.ai_max_state(
-            &state,
-            0,
-            -100000000007,
-            100000000007,
-            self.player_move_dummy_translate(ChipType::O) as i64,
-        );
+    fn ai_move_val(&self) -> (ChipType, usize, SearchStats<Move>) {
+        let mut search = self.ai_search();
+        search.run();
+        let stats = search.into_stats();
 
-        println!(
-            "[DEBUG] ChipType => (value, column) ;; T => ({}, {}) ;; O => ({}, {})",
-            t_val, t_move, o_val, o_move
-        );
-
-        if t_val > o_val {
-            return (ChipType::T, t_move as usize);
-        } else if t_val < o_val {
-            return (ChipType::O, o_move as usize);
-        } else {
-            // Play T and O have same value? Choose a random one
-            let mut rng = rand::thread_rng();
-            if rng.gen() {
-                return (ChipType::T, t_move as usize);
-            } else {
-                return (ChipType::O, o_move as usize);
-            }
-        }
+        // No legal move, let the caller fall back to a random column
+        let (chip_type, col_num) = match stats.principal_variation.first() {
+            Some(choice) => *choice,
+            None => (self.variant.letters[0], 0),
+        };
+        return (chip_type, col_num, stats);
     }
 
-    fn ai_check_state(&self, state: &DummyGrid) -> (i64, i64) {
-        #[allow(non_snake_case)]
-        let T = self.player_move_dummy_translate(ChipType::T);
-        #[allow(non_snake_case)]
-        let O = self.player_move_dummy_translate(ChipType::O);
+    // Returns the win value (4 for the AI's word, -4 for the opponent's), the chain
+    // value and whether both words are on the board, which ends the game in a draw
+    fn ai_check_state(&self, state: &Grid) -> (i64, i64, bool) {
+        // The game was cloned on the AI's turn, a letter adds 1 to a chain when it
+        // matches the AI's word and takes 1 away when it does not
+        let ai_player = self.current_player();
+        let target = self.variant.word(ai_player);
+        let length = target.len() as i64;
 
-        let mut win_val: i64 = 0;
+        let mut spelled = [false, false];
         let mut chain_val: i64 = 0;
+        let mut line = Vec::with_capacity(target.len());
 
-        let mut temp_r1 = [0; 4];
-        let mut temp_b1 = [0; 4];
-        let mut temp_br1 = [0; 4];
-        let mut temp_br2 = [0; 4];
+        let num_rows = state.num_rows as i64;
+        let num_cols = state.num_cols as i64;
 
-        let num_rows = state.num_rows;
-        let num_cols = state.num_cols;
+        // Right, down, down-right and up-right, lines are cut short at the edges
+        let directions: [(i64, i64); 4] = [(0, 1), (1, 0), (1, 1), (-1, 1)];
 
         for i in 0..num_rows {
             for j in 0..num_cols {
-                temp_r1[0] = 0;
-                temp_r1[1] = 0;
-                temp_r1[2] = 0;
-                temp_r1[3] = 0;
-                temp_b1[0] = 0;
-                temp_b1[1] = 0;
-                temp_b1[2] = 0;
-                temp_b1[3] = 0;
-                temp_br1[0] = 0;
-                temp_br1[1] = 0;
-                temp_br1[2] = 0;
-                temp_br1[3] = 0;
-                temp_br2[0] = 0;
-                temp_br2[1] = 0;
-                temp_br2[2] = 0;
-                temp_br2[3] = 0;
-
-                for k in 0..4 {
-                    if j + k < num_cols {
-                        temp_r1[k] = state.get(i, j + k);
-                    }
-                    if i + k < num_rows {
-                        temp_b1[k] = state.get(i + k, j);
-                    }
-                    if i + k < num_rows && j + k < num_cols {
-                        temp_br1[k] = state.get(i + k, j + k);
+                for (di, dj) in directions.iter() {
+                    line.clear();
+                    let mut chain: i64 = 0;
+                    for k in 0..length {
+                        let row = i + k * di;
+                        let col = j + k * dj;
+                        if row < 0 || row >= num_rows || col >= num_cols {
+                            break;
+                        }
+                        let cell = state.get(row as usize, col as usize);
+                        if let Some((_, chip_type)) = cell {
+                            if chip_type == target[k as usize] {
+                                chain += 1;
+                            } else {
+                                chain -= 1;
+                            }
+                        }
+                        line.push(cell);
                     }
-                    if i as i64 - k as i64 >= 0 && j + k < num_cols {
-                        temp_br2[k] = state.get(i - k, j + k);
+
+                    chain_val += chain * chain * chain;
+
+                    for (idx, player) in [ai_player, ai_player.other()].iter().enumerate() {
+                        let word = self.variant.word(*player);
+                        if Game::reads(&line, word, false) || Game::reads(&line, word, true) {
+                            spelled[idx] = true;
+                        }
                     }
                 }
+            }
+        }
 
-                // AI wants OTTO, check to see how many matches
-                let temp_r =
-                    (temp_r1[0] * O + temp_r1[1] * T + temp_r1[2] * T + temp_r1[3] * O) as i64;
-                let temp_b =
-                    (temp_b1[0] * O + temp_b1[1] * T + temp_b1[2] * T + temp_b1[3] * O) as i64;
-                let temp_br =
-                    (temp_br1[0] * O + temp_br1[1] * T + temp_br1[2] * T + temp_br1[3] * O) as i64;
-                let temp_tr =
-                    (temp_br2[0] * O + temp_br2[1] * T + temp_br2[2] * T + temp_br2[3] * O) as i64;
-
-                chain_val += temp_r * temp_r * temp_r;
-                chain_val += temp_b * temp_b * temp_b;
-                chain_val += temp_br * temp_br * temp_br;
-                chain_val += temp_tr * temp_tr * temp_tr;
-
-                // Player wants TOOT, but AI hates it (-4)
-                // AI wants OTTO (+4)
-                if temp_r1[0] == T && temp_r1[1] == O && temp_r1[2] == O && temp_r1[3] == T {
-                    win_val = -4;
-                } else if temp_r1[0] == O && temp_r1[1] == T && temp_r1[2] == T && temp_r1[3] == O {
-                    win_val = 4;
-                } else if temp_b1[0] == T && temp_b1[1] == O && temp_b1[2] == O && temp_b1[3] == T {
-                    win_val = -4;
-                } else if temp_b1[0] == O && temp_b1[1] == T && temp_b1[2] == T && temp_b1[3] == O {
-                    win_val = 4;
-                } else if temp_br1[0] == T
-                    && temp_br1[1] == O
-                    && temp_br1[2] == O
-                    && temp_br1[3] == T
-                {
-                    win_val = -4;
-                } else if temp_br1[0] == O
-                    && temp_br1[1] == T
-                    && temp_br1[2] == T
-                    && temp_br1[3] == O
-                {
-                    win_val = 4;
-                } else if temp_br2[0] == T
-                    && temp_br2[1] == O
-                    && temp_br2[2] == O
-                    && temp_br2[3] == T
-                {
-                    win_val = -4;
-                } else if temp_br2[0] == O
-                    && temp_br2[1] == T
-                    && temp_br2[2] == T
-                    && temp_br2[3] == O
-                {
-                    win_val = 4;
+        // Both words on the board at once is a draw, not a win
+        let mut win_val: i64 = 0;
+        if spelled[0] && !spelled[1] {
+            win_val = 4;
+        } else if spelled[1] && !spelled[0] {
+            win_val = -4;
+        }
+
+        return (win_val, chain_val, spelled[0] && spelled[1]);
+    }
+
+    // Score of a line still missing that many letters of a word
+    fn ai_window_score(missing: usize) -> i64 {
+        match missing {
+            1 => 32,
+            2 => 8,
+            3 => 1,
+            _ => 0,
+        }
+    }
+
+    // Scores every line of the word length by how much of the AI's word or the
+    // opponent's word it already spells. Returns the win value (4 for the AI's word, -4
+    // for the opponent's), the score from the AI's side and whether both words are on
+    // the board
+    fn ai_check_patterns(&self, position: &SearchPosition, ai_to_move: bool) -> (i64, i64, bool) {
+        let state = &position.grid;
+        let num_rows = state.num_rows;
+        let num_cols = state.num_cols;
+        let length = self.variant.p1_word.len() as i64;
+
+        // Index 0 for the AI, the game is cloned on its turn, and 1 for the opponent
+        let ai_player = self.current_player();
+        let players = [ai_player, ai_player.other()];
+        let chips = [&position.ai_chips, &position.opponent_chips];
+
+        let mut spelled = [false, false];
+        let mut score: i64 = 0;
+        let mut gaps = Vec::with_capacity(length as usize);
+
+        // Right, down, down-right and up-right
+        let directions: [(i64, i64); 4] = [(0, 1), (1, 0), (1, 1), (-1, 1)];
+
+        for i in 0..num_rows as i64 {
+            for j in 0..num_cols as i64 {
+                for (di, dj) in directions.iter() {
+                    let end_i = i + (length - 1) * di;
+                    let end_j = j + (length - 1) * dj;
+                    if end_i < 0 || end_i >= num_rows as i64 || end_j >= num_cols as i64 {
+                        continue;
+                    }
+
+                    for (side, player) in players.iter().enumerate() {
+                        let word = self.variant.word(*player);
+
+                        // A palindrome reads the same both ways, count it once
+                        let palindrome = word.iter().eq(word.iter().rev());
+                        let readings: &[bool] = if palindrome { &[false] } else { &[false, true] };
+
+                        for backwards in readings.iter() {
+                            let mut alive = true;
+                            gaps.clear();
+                            for k in 0..length {
+                                let row = (i + k * di) as usize;
+                                let col = (j + k * dj) as usize;
+                                let letter = if *backwards {
+                                    word[(length - 1 - k) as usize]
+                                } else {
+                                    word[k as usize]
+                                };
+                                match state.get(row, col) {
+                                    Some((_, chip_type)) if chip_type == letter => {}
+                                    Some(_) => {
+                                        alive = false;
+                                        break;
+                                    }
+                                    None => gaps.push((row, col, letter)),
+                                }
+                            }
+
+                            if !alive {
+                                continue;
+                            }
+                            if gaps.is_empty() {
+                                spelled[side] = true;
+                                continue;
+                            }
+
+                            // Either player can supply a missing letter, lines there are
+                            // not enough tiles left for can never be finished
+                            let finishable = self.variant.letters.iter().all(|letter| {
+                                let needed = gaps.iter().filter(|gap| gap.2 == *letter).count();
+                                needed as u32
+                                    <= chips[0].remaining(*letter) + chips[1].remaining(*letter)
+                            });
+                            if !finishable {
+                                continue;
+                            }
+
+                            let sign = if side == 0 { 1 } else { -1 };
+                            score += sign * Game::ai_window_score(gaps.len());
+
+                            if gaps.len() == 1 {
+                                let (row, col, letter) = gaps[0];
+                                let playable = row == num_rows - 1 || !state.is_empty(row + 1, col);
+                                let to_move = (side == 0) == ai_to_move;
+                                let opponent = chips[1 - side];
+
+                                if playable && to_move && chips[side].remaining(letter) > 0 {
+                                    // Wins with the next move
+                                    score += sign * 50000;
+                                } else if opponent.remaining(letter) > 0
+                                    && opponent.remaining(letter) == opponent.total()
+                                {
+                                    // The opponent only holds the missing letter, once the
+                                    // other cells fill up they are forced to supply it
+                                    score += sign * 500;
+                                } else if playable {
+                                    score += sign * 60;
+                                }
+                            }
+                        }
+                    }
                 }
             }
         }
 
-        return (win_val, chain_val);
+        let mut win_val: i64 = 0;
+        if spelled[0] && !spelled[1] {
+            win_val = 4;
+        } else if spelled[1] && !spelled[0] {
+            win_val = -4;
+        }
+
+        return (win_val, score, spelled[0] && spelled[1]);
+    }
+
+    fn ai_evaluate(&self, position: &SearchPosition, ai_to_move: bool) -> (i64, i64, bool) {
+        match self.evaluator {
+            Evaluator::Chains => self.ai_check_state(&position.grid),
+            Evaluator::Patterns => self.ai_check_patterns(position, ai_to_move),
+        }
     }
 
-    fn ai_value(
+    // Score of a position the search reached, None if it has to look further.
+    // ai_move_val is the letter the AI side of the tree is playing, game_over is set
+    // when the next player has nothing left to play
+    fn ai_leaf_value(
         &self,
-        state: &DummyGrid,
+        position: &SearchPosition,
         depth: u32,
-        alpha: i64,
-        beta: i64,
         ai_move_val: i64,
-    ) -> (i64, i64) {
-        let val = self.ai_check_state(&state);
+        game_over: bool,
+    ) -> Option<i64> {
+        // The AI moves at even depths, so it is next after an odd one
+        let val = self.ai_evaluate(position, depth % 2 == 1);
+        if val.2 {
+            return Some(-((depth * depth) as i64));
+        }
+
         // TOOT-OTTO is significantly more complicated than Connect4, reduce depth to 3
-        if depth >= self.max_ai_depth {
+        if depth >= self.max_ai_depth || game_over {
             let mut ret_value;
             let win_val = val.0;
-            let chain_val = val.1 * ai_move_val;
+            let chain_val = match self.evaluator {
+                Evaluator::Chains => val.1 * ai_move_val,
+
+                // Already from the AI's side, with nothing left to play it is a draw
+                Evaluator::Patterns if game_over => 0,
+                Evaluator::Patterns => val.1,
+            };
             ret_value = chain_val;
 
             if win_val == 4 {
@@ -504,191 +1014,29 @@ impl Game {
             }
             ret_value -= (depth * depth) as i64;
 
-            return (ret_value, -1);
+            return Some(ret_value);
         }
 
         let win = val.0;
         if win == 4 {
-            return ((999999 - depth * depth) as i64, -1);
+            return Some((999999 - depth * depth) as i64);
         }
         if win == 4 * -1 {
-            return (999999 * -1 - ((depth * depth) as i64), -1);
-        }
-
-        if depth % 2 == 0 {
-            // Play T
-            let (t_val, t_move) = self.ai_min_state(
-                state,
-                depth + 1,
-                alpha,
-                beta,
-                self.player_move_dummy_translate(ChipType::T) as i64,
-            );
-            // Play O
-            let (o_val, o_move) = self.ai_min_state(
-                state,
-                depth + 1,
-                alpha,
-                beta,
-                self.player_move_dummy_translate(ChipType::O) as i64,
-            );
-
-            // AI wants player to lose, so choose the minimum value
-            if t_val > o_val {
-                return (o_val, o_move);
-            } else if t_val < o_val {
-                return (t_val, t_move);
-            } else {
-                // Play T and O have same value? Choose a random one
-                let mut rng = rand::thread_rng();
-                if rng.gen() {
-                    return (t_val, t_move);
-                } else {
-                    return (o_val, o_move);
-                }
-            }
-        } else {
-            // Play T
-            let (t_val, t_move) = self.ai_max_state(
-                state,
-                depth + 1,
-                alpha,
-                beta,
-                self.player_move_dummy_translate(ChipType::T) as i64,
-            );
-            // Play O
-            let (o_val, o_move) = self.ai_max_state(
-                state,
-                depth + 1,
-                alpha,
-                beta,
-                self.player_move_dummy_translate(ChipType::O) as i64,
-            );
-
-            // AI wants to win, so choose the maximum value
-            if t_val > o_val {
-                return (t_val, t_move);
-            } else if t_val < o_val {
-                return (o_val, o_move);
-            } else {
-                // Play T and O have same value? Choose a random one
-                let mut rng = rand::thread_rng();
-                if rng.gen() {
-                    return (t_val, t_move);
-                } else {
-                    return (o_val, o_move);
-                }
-            }
+            return Some(999999 * -1 - ((depth * depth) as i64));
         }
-    }
 
-    fn ai_max_state(
-        &self,
-        state: &DummyGrid,
-        depth: u32,
-        alpha: i64,
-        beta: i64,
-        ai_move_val: i64,
-    ) -> (i64, i64) {
-        let mut v: i64 = -100000000007;
-        let mut _move: i64 = -1;
-        let mut temp_val: (i64, i64);
-        let mut temp_state: DummyGrid;
-        let mut move_queue: Vec<usize> = Vec::new();
-        let mut alpha = alpha;
-
-        for j in 0..self.grid.num_cols {
-            let temp_state_opt = self.ai_fill_map(state, j, ai_move_val);
-            if temp_state_opt.is_some() {
-                temp_state = temp_state_opt.unwrap();
-                temp_val = self.ai_value(&temp_state, depth, alpha, beta, ai_move_val);
-
-                if temp_val.0 > v {
-                    v = temp_val.0;
-                    _move = j as i64;
-                    move_queue.clear();
-                    move_queue.push(j);
-                } else if temp_val.0 == v {
-                    move_queue.push(j);
-                }
-
-                if v > beta {
-                    _move = Game::choose(move_queue) as i64;
-                    return (v, _move as i64);
-                }
-                alpha = max(alpha, v);
-            }
-        }
-
-        if move_queue.len() == 0 {
-            (v, -1)
-        } else {
-            _move = Game::choose(move_queue) as i64;
-            (v, _move as i64)
-        }
-    }
-
-    fn choose(choice: Vec<usize>) -> usize {
-        let mut rng = rand::thread_rng();
-        let rand_idx = rng.gen_range(0, choice.len());
-        return choice[rand_idx as usize];
-    }
-
-    fn ai_min_state(
-        &self,
-        state: &DummyGrid,
-        depth: u32,
-        alpha: i64,
-        beta: i64,
-        ai_move_val: i64,
-    ) -> (i64, i64) {
-        let mut v: i64 = 100000000007;
-        let mut _move: i64 = -1;
-        let mut temp_val: (i64, i64);
-        let mut temp_state: DummyGrid;
-        let mut move_queue: Vec<usize> = Vec::new();
-        let mut beta = beta;
-
-        for j in 0..self.grid.num_cols {
-            let temp_state_opt = self.ai_fill_map(state, j, ai_move_val * -1);
-            if temp_state_opt.is_some() {
-                temp_state = temp_state_opt.unwrap();
-                temp_val = self.ai_value(&temp_state, depth, alpha, beta, ai_move_val);
-
-                if temp_val.0 < v {
-                    v = temp_val.0;
-                    _move = j as i64;
-                    move_queue.clear();
-                    move_queue.push(j);
-                } else if temp_val.0 == v {
-                    move_queue.push(j);
-                }
-
-                if v < alpha {
-                    _move = Game::choose(move_queue) as i64;
-                    return (v, _move as i64);
-                }
-                beta = min(beta, v);
-            }
-        }
-
-        if move_queue.len() == 0 {
-            (v, -1)
-        } else {
-            _move = Game::choose(move_queue) as i64;
-            return (v, _move as i64);
-        }
+        None
     }
 
-    fn ai_fill_map(&self, state: &DummyGrid, column: usize, value: i64) -> Option<DummyGrid> {
+    fn ai_fill_map(&self, state: &Grid, column: usize, cell: Cell) -> Option<Grid> {
         let mut temp_map = state.clone();
-        if temp_map.get(0, column) != 0 || /* column < 0 || */ column >= self.grid.num_cols {
+        if !temp_map.is_empty(0, column) || /* column < 0 || */ column >= self.grid.num_cols {
             return None;
         }
         let mut done = false;
         let mut row = 0;
         for i in 0..self.grid.num_rows - 1 {
-            if temp_map.get(i + 1, column) != 0 {
+            if !temp_map.is_empty(i + 1, column) {
                 done = true;
                 row = i;
                 break;
@@ -697,48 +1045,127 @@ impl Game {
         if !done {
             row = self.grid.num_rows - 1;
         }
-        temp_map.set(row, column, value as i32);
+        temp_map.set(row, column, cell);
         return Some(temp_map);
     }
 }
 
+// Moves of a move list such as "T3 O4" or "T3,O4", a letter followed by its column
+pub fn parse_moves(moves: &str) -> Result<Vec<Move>, String> {
+    moves
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            let mut chars = token.chars();
+            let letter = match chars.next() {
+                Some(letter) if letter.is_alphabetic() => letter.to_ascii_uppercase(),
+                _ => return Err(format!("{} does not start with a letter", token)),
+            };
+            match chars.as_str().parse() {
+                Ok(col) => Ok((ChipType(letter), col)),
+                Err(_) => Err(format!("{} does not end with a column", token)),
+            }
+        })
+        .collect()
+}
+
+// Game side of the AI search. Both players pick a letter at every node, the
+// AI plays its letter and the opponent the other one
+pub struct SearchContext {
+    game: Game,
+}
+
+// Board and tiles left during the AI search
 #[derive(Clone)]
-pub struct Grid {
-    pub items: [i32; 80],
-    pub num_rows: usize,
-    pub num_cols: usize,
+pub struct SearchPosition {
+    grid: Grid,
+    ai_chips: Inventory,
+    opponent_chips: Inventory,
 }
 
-impl Grid {
-    pub fn new(row_size: usize, col_size: usize) -> Self {
-        let mut grid = Grid {
-            items: [0; 80],
-            num_rows: row_size,
-            num_cols: col_size,
-        };
-        for x in 0..(row_size * col_size) {
-            grid.items[x] = 0;
+impl SearchPosition {
+    fn chips(&self, maximizing: bool) -> &Inventory {
+        if maximizing {
+            return &self.ai_chips;
         }
-        grid
+        return &self.opponent_chips;
     }
+}
+
+impl SearchContext {
+    fn new(game: Game) -> Self {
+        SearchContext { game }
+    }
+}
 
-    pub fn insert_chip(&mut self, col: usize, grid_val: i32) -> Result<usize, ()> {
-        for r in (0..self.num_rows).rev() {
-            match self.get(r, col) {
-                0 => {
-                    self.set(r, col, grid_val as i32);
-                    return Ok(r);
+impl SearchGame for SearchContext {
+    type Position = SearchPosition;
+    type Move = Move;
+
+    fn moves(&self, position: &SearchPosition, depth: u32, maximizing: bool) -> Vec<Move> {
+        let chips = position.chips(maximizing);
+        let mut moves = Vec::new();
+        for chip_type in self.game.variant.letters.iter() {
+            if chips.remaining(*chip_type) == 0 {
+                continue;
+            }
+            for j in 0..self.game.grid.num_cols {
+                if position.grid.is_empty(0, j) {
+                    moves.push((*chip_type, j));
                 }
-                _ => {}
             }
         }
-        return Err(());
+
+        // Moves of equal value are picked in search order, vary the AI's play
+        if depth == 0 {
+            moves.shuffle(&mut rand::thread_rng());
+        }
+        moves
     }
-    pub fn get(&self, row: usize, col: usize) -> i32 {
-        self.items[col * self.num_rows + (self.num_rows - 1 - row)]
+
+    fn play(
+        &self,
+        position: &SearchPosition,
+        (chip_type, col): Move,
+        maximizing: bool,
+    ) -> SearchPosition {
+        // The game was cloned on the AI's turn
+        let mut player = self.game.current_player();
+        if !maximizing {
+            player = player.other();
+        }
+        let mut next = position.clone();
+        next.grid = self
+            .game
+            .ai_fill_map(&position.grid, col, Some((player, chip_type)))
+            .unwrap();
+        if maximizing {
+            next.ai_chips.take(chip_type).unwrap();
+        } else {
+            next.opponent_chips.take(chip_type).unwrap();
+        }
+        next
     }
-    pub fn set(&mut self, row: usize, col: usize, val: i32) {
-        self.items[col * self.num_rows + (self.num_rows - 1 - row)] = val;
+
+    fn leaf_value(
+        &self,
+        position: &SearchPosition,
+        depth: u32,
+        (chip_type, _col): Move,
+    ) -> Option<i64> {
+        // The AI moves at even depths, otherwise the AI plays the other letter
+        let mut ai_move_val = self.game.chip_value(chip_type);
+        if depth % 2 == 1 {
+            ai_move_val *= -1;
+        }
+
+        // Nothing left for the player to move next, either no tiles or a full board
+        let next_chips = position.chips(depth % 2 == 1);
+        let board_full = (0..position.grid.num_cols).all(|j| !position.grid.is_empty(0, j));
+        let game_over = next_chips.is_empty() || board_full;
+
+        self.game
+            .ai_leaf_value(position, depth, ai_move_val, game_over)
     }
 }
 
@@ -746,12 +1173,9 @@ impl fmt::Display for Grid {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         for r in 0..self.num_rows {
             for c in 0..self.num_cols {
-                let chip = self.get(r, c);
-                match chip {
-                    0 => write!(f, "_"),
-                    1 => write!(f, "R"),
-                    -1 => write!(f, "Y"),
-                    _ => Err(std::fmt::Error),
+                match self.get(r, c) {
+                    None => write!(f, "_"),
+                    Some((_, chip_type)) => write!(f, "{}", chip_type),
                 }?;
                 write!(f, " ")?;
             }
@@ -761,63 +1185,88 @@ impl fmt::Display for Grid {
     }
 }
 
-#[derive(Clone)]
-pub struct DummyGrid {
-    pub items: [i32; 80],
-    pub num_rows: usize,
-    pub num_cols: usize,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Set up a board diagram and play one move of the player to move on it
+    fn play(diagram: &str, mv: &str) -> Game {
+        let mut game = Game::new(
+            6,
+            7,
+            false,
+            "P1".to_string(),
+            "P2".to_string(),
+            1,
+            Variant::toot_otto(),
+        );
+        game.load_diagram(diagram).unwrap();
+        assert!(game.state == State::Running, "{} is already over", diagram);
+        let (chip_type, col) = parse_moves(mv).unwrap()[0];
+        game.make_move(chip_type, col).unwrap();
+        game
+    }
 
-impl DummyGrid {
-    pub fn new(row_size: usize, col_size: usize) -> Self {
-        let mut grid = DummyGrid {
-            items: [0; 80],
-            num_rows: row_size,
-            num_cols: col_size,
-        };
-        for x in 0..(row_size * col_size) {
-            grid.items[x] = 0;
+    fn word(player: Player, from: (usize, usize), to: (usize, usize)) -> SpelledWord {
+        SpelledWord {
+            player,
+            word: Variant::toot_otto().word_string(player),
+            from,
+            to,
         }
-        grid
     }
 
-    pub fn insert_chip(&mut self, col: usize, grid_val: i32) -> Result<usize, ()> {
-        for r in (0..self.num_rows).rev() {
-            match self.get(r, col) {
-                0 => {
-                    self.set(r, col, grid_val as i32);
-                    return Ok(r);
-                }
-                _ => {}
-            }
-        }
-        return Err(());
+    #[test]
+    fn rejects_words_that_cannot_be_told_apart_or_placed() {
+        assert!(Variant::new("AB", "BA", "", CHIPS_PER_LETTER).is_err());
+        assert!(Variant::new("ÄB", "AB", "", CHIPS_PER_LETTER).is_err());
+        assert!(Variant::new("TOOT", "OTTO", "TOß", CHIPS_PER_LETTER).is_err());
+        assert_eq!(
+            Variant::new("toot", "otto", "", CHIPS_PER_LETTER).unwrap(),
+            Variant::toot_otto()
+        );
+        assert!(Variant::new("TOOT", "OTTO", "", CHIPS_PER_LETTER).is_ok());
+
+        let variant = Variant::new("TOOTT", "OTTOO", "", CHIPS_PER_LETTER).unwrap();
+        assert!(variant.check_board(4, 4).is_err());
+        assert!(variant.check_board(5, 4).is_ok());
+        assert!(variant.check_board(4, 5).is_ok());
     }
 
-    pub fn get(&self, row: usize, col: usize) -> i32 {
-        self.items[col * self.num_rows + (self.num_rows - 1 - row)]
+    #[test]
+    fn horizontal_word() {
+        let game = play("..../..../oo../OTT.", "O3");
+        assert_eq!(game.winner, "P2");
+        assert_eq!(game.words, vec![word(Player::Two, (3, 0), (3, 3))]);
     }
 
-    pub fn set(&mut self, row: usize, col: usize, val: i32) {
-        self.items[col * self.num_rows + (self.num_rows - 1 - row)] = val;
+    #[test]
+    fn vertical_word() {
+        let game = play("..../o.../O.../T...", "T0");
+        assert_eq!(game.winner, "P1");
+        assert_eq!(game.words, vec![word(Player::One, (0, 0), (3, 0))]);
     }
-}
 
-impl fmt::Display for DummyGrid {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for r in 0..self.num_rows {
-            for c in 0..self.num_cols {
-                let chip = self.get(r, c);
-                match chip {
-                    0 => write!(f, "_"),
-                    1 => write!(f, "T"),
-                    -1 => write!(f, "O"),
-                    _ => Err(std::fmt::Error),
-                }?;
-                write!(f, " ")?;
-            }
-            write!(f, "\n")?;
-        }
-        Ok(())
+    #[test]
+    fn diagonal_words() {
+        // Down to the right, then up to the right
+        let game = play("..../tO../tTo./TtTT", "T0");
+        assert_eq!(game.winner, "P1");
+        assert_eq!(game.words, vec![word(Player::One, (0, 0), (3, 3))]);
+
+        let game = play("..../..Ot/.oTt/TTtT", "T3");
+        assert_eq!(game.winner, "P1");
+        assert_eq!(game.words, vec![word(Player::One, (3, 0), (0, 3))]);
+    }
+
+    #[test]
+    fn both_words_at_once_is_a_draw() {
+        // The O completes TOOT along its row and OTTO down its column
+        let game = play("..../TO.t/OtTt/TtTt/TtoT", "O2");
+        assert_eq!(game.winner, "Draw");
+        assert!(game.state == State::Done);
+        assert_eq!(game.words.len(), 2);
+        assert!(game.words.contains(&word(Player::One, (1, 0), (1, 3))));
+        assert!(game.words.contains(&word(Player::Two, (1, 2), (4, 2))));
     }
 }
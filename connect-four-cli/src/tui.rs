@@ -0,0 +1,670 @@
+use crate::board::{Player, Turn};
+use crate::connect_four::{Game as ConnectFourGame, GameEvents as ConnectFourGameEvents};
+use crate::save::SavedGame;
+use crate::search::SearchStats;
+use crate::toot_otto::{
+    ChipType, Game as TootOttoGame, GameEvents as TootOttoGameEvents, Inventory, Move, SpelledWord,
+};
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor};
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use crossterm::{execute, queue};
+use std::cell::RefCell;
+use std::io::{self, Stdout, Write};
+use std::thread;
+use std::time::Duration;
+
+// File the s key saves the game to
+const SAVE_FILE: &str = "saved_game.txt";
+
+// Delay between the rows a dropped chip falls through
+const DROP_FRAME: Duration = Duration::from_millis(35);
+
+// Who played a chip and, in TOOT-OTTO, its letter
+type Disc = Option<(Player, Option<char>)>;
+
+// Everything on screen, the games feed it through their events
+struct Screen {
+    out: Stdout,
+    title: String,
+    names: (String, String),
+    ai_player: Option<Player>,
+    cells: Vec<Vec<Disc>>,
+    cursor: usize,
+    turn: Player,
+
+    // Letters to pick from in TOOT-OTTO and the one picked
+    letters: Vec<char>,
+    letter: usize,
+    chips: String,
+
+    moves: Vec<String>,
+    last_move: Option<(usize, usize)>,
+    winning: Vec<(usize, usize)>,
+    ai_info: Vec<String>,
+    status: String,
+    keys: String,
+}
+
+impl Screen {
+    fn new(title: String, names: (String, String), ai_player: Option<Player>) -> io::Result<Self> {
+        let mut out = io::stdout();
+        enable_raw_mode()?;
+        execute!(out, EnterAlternateScreen, Hide)?;
+        Ok(Screen {
+            out,
+            title,
+            names,
+            ai_player,
+            cells: Vec::new(),
+            cursor: 0,
+            turn: Player::One,
+            letters: Vec::new(),
+            letter: 0,
+            chips: String::new(),
+            moves: Vec::new(),
+            last_move: None,
+            winning: Vec::new(),
+            ai_info: Vec::new(),
+            status: String::new(),
+            keys: String::new(),
+        })
+    }
+
+    fn num_cols(&self) -> usize {
+        self.cells.first().map_or(0, |row| row.len())
+    }
+
+    fn name(&self, player: Player) -> &str {
+        match player {
+            Player::One => &self.names.0,
+            Player::Two => &self.names.1,
+        }
+    }
+
+    fn set_cells(&mut self, cells: Vec<Vec<Disc>>) {
+        if self.cursor >= cells.first().map_or(0, |row| row.len()) {
+            self.cursor = 0;
+        }
+        self.cells = cells;
+    }
+
+    // Letter of the chip being played, None in Connect-4
+    fn current_letter(&self) -> Option<char> {
+        self.letters.get(self.letter).cloned()
+    }
+
+    // Let the chip fall down col from the top to the row it lands in
+    fn drop_chip(&mut self, col: usize, disc: Disc) -> io::Result<()> {
+        let landing = match (0..self.cells.len())
+            .rev()
+            .find(|r| self.cells[*r][col].is_none())
+        {
+            Some(row) => row,
+            None => return Ok(()),
+        };
+        for row in 0..=landing {
+            self.cells[row][col] = disc;
+            self.draw()?;
+            thread::sleep(DROP_FRAME);
+            if row < landing {
+                self.cells[row][col] = None;
+            }
+        }
+        self.last_move = Some((landing, col));
+        Ok(())
+    }
+
+    fn draw(&mut self) -> io::Result<()> {
+        let num_rows = self.cells.len();
+        let num_cols = self.num_cols();
+        queue!(self.out, Clear(ClearType::All), MoveTo(2, 0))?;
+        queue!(
+            self.out,
+            SetAttribute(Attribute::Bold),
+            Print(&self.title),
+            SetAttribute(Attribute::Reset)
+        )?;
+
+        // Cursor above the board, with the letter to play in TOOT-OTTO
+        let turn = self.turn;
+        let human_turn = self.ai_player != Some(turn);
+        queue!(self.out, MoveTo(3 + 3 * self.cursor as u16, 2))?;
+        if human_turn && self.winning.is_empty() {
+            let marker = match self.current_letter() {
+                Some(letter) => letter.to_string(),
+                None => "●".to_string(),
+            };
+            queue!(
+                self.out,
+                SetForegroundColor(color(turn)),
+                SetAttribute(Attribute::Bold),
+                Print(marker),
+                SetAttribute(Attribute::Reset),
+                ResetColor
+            )?;
+        }
+
+        for row in 0..num_rows {
+            queue!(self.out, MoveTo(2, 3 + row as u16), Print("|"))?;
+            for col in 0..num_cols {
+                queue!(self.out, MoveTo(3 + 3 * col as u16, 3 + row as u16))?;
+                if self.winning.contains(&(row, col)) {
+                    queue!(self.out, SetAttribute(Attribute::Reverse))?;
+                } else if self.last_move == Some((row, col)) {
+                    queue!(self.out, SetAttribute(Attribute::Underlined))?;
+                }
+                match self.cells[row][col] {
+                    Some((player, letter)) => {
+                        let text = match letter {
+                            Some(letter) => letter.to_string(),
+                            None => "●".to_string(),
+                        };
+                        queue!(
+                            self.out,
+                            SetForegroundColor(color(player)),
+                            SetAttribute(Attribute::Bold),
+                            Print(text),
+                            ResetColor
+                        )?;
+                    }
+                    None => queue!(self.out, SetForegroundColor(Color::DarkGrey), Print("·"))?,
+                }
+                queue!(
+                    self.out,
+                    SetAttribute(Attribute::Reset),
+                    ResetColor,
+                    Print("  ")
+                )?;
+            }
+            queue!(
+                self.out,
+                MoveTo(2 + 3 * num_cols as u16, 3 + row as u16),
+                Print("|")
+            )?;
+        }
+        let bottom = 3 + num_rows as u16;
+        queue!(
+            self.out,
+            MoveTo(2, bottom),
+            Print(format!("+{}+", "-".repeat(3 * num_cols - 1)))
+        )?;
+        for col in 0..num_cols {
+            queue!(self.out, MoveTo(3 + 3 * col as u16, bottom + 1), Print(col))?;
+        }
+
+        let turn_text = format!("{}'s turn", self.name(turn));
+        queue!(
+            self.out,
+            MoveTo(2, bottom + 3),
+            SetForegroundColor(color(turn)),
+            Print(turn_text),
+            ResetColor,
+            MoveTo(2, bottom + 4),
+            Print(&self.status),
+            MoveTo(2, bottom + 5),
+            Print(&self.chips),
+            MoveTo(2, bottom + 7),
+            SetForegroundColor(Color::DarkGrey),
+            Print(&self.keys),
+            ResetColor
+        )?;
+
+        // Side panel with the moves and what the computer was thinking
+        let panel = 8 + 3 * num_cols as u16;
+        queue!(
+            self.out,
+            MoveTo(panel, 2),
+            SetAttribute(Attribute::Bold),
+            Print("Moves"),
+            SetAttribute(Attribute::Reset)
+        )?;
+        let shown = num_rows.max(6);
+        let skip = self.moves.len().saturating_sub(shown);
+        for (idx, text) in self.moves.iter().enumerate().skip(skip) {
+            queue!(
+                self.out,
+                MoveTo(panel, 3 + (idx - skip) as u16),
+                Print(format!("{:>3}. {}", idx + 1, text))
+            )?;
+        }
+        let info_top = 4 + shown as u16;
+        if !self.ai_info.is_empty() {
+            queue!(
+                self.out,
+                MoveTo(panel, info_top),
+                SetAttribute(Attribute::Bold),
+                Print("Computer"),
+                SetAttribute(Attribute::Reset)
+            )?;
+            for (idx, line) in self.ai_info.iter().enumerate() {
+                queue!(
+                    self.out,
+                    MoveTo(panel, info_top + 1 + idx as u16),
+                    Print(line)
+                )?;
+            }
+        }
+
+        self.out.flush()
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(self.out, Show, LeaveAlternateScreen);
+        let _ = disable_raw_mode();
+    }
+}
+
+fn color(player: Player) -> Color {
+    match player {
+        Player::One => Color::Red,
+        Player::Two => Color::Yellow,
+    }
+}
+
+// Next key pressed, Ctrl-C reads as Esc
+fn read_key() -> io::Result<KeyCode> {
+    loop {
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+                return Ok(KeyCode::Esc);
+            }
+            return Ok(key.code);
+        }
+    }
+}
+
+// Move the cursor with the arrow keys until the player drops a chip or asks for
+// a command, switch_letter picks the next letter in TOOT-OTTO
+fn choose_column(screen: &RefCell<Screen>, switch_letter: bool) -> Turn<usize> {
+    loop {
+        if screen.borrow_mut().draw().is_err() {
+            return Turn::Quit;
+        }
+        let key = match read_key() {
+            Ok(key) => key,
+            Err(_) => return Turn::Quit,
+        };
+        let mut screen = screen.borrow_mut();
+        let num_cols = screen.num_cols();
+        match key {
+            KeyCode::Left => screen.cursor = (screen.cursor + num_cols - 1) % num_cols,
+            KeyCode::Right => screen.cursor = (screen.cursor + 1) % num_cols,
+            KeyCode::Up | KeyCode::Tab if switch_letter => {
+                screen.letter = (screen.letter + 1) % screen.letters.len();
+            }
+            KeyCode::Down | KeyCode::Enter | KeyCode::Char(' ') => {
+                return Turn::Move(screen.cursor);
+            }
+            KeyCode::Char(c) if c.is_ascii_digit() => {
+                let col = c.to_digit(10).unwrap() as usize;
+                if col < num_cols {
+                    screen.cursor = col;
+                }
+            }
+            KeyCode::Char('u') => return Turn::Undo,
+            KeyCode::Char('h') => return Turn::Hint,
+            KeyCode::Char('s') => return Turn::Save(SAVE_FILE.to_string()),
+            KeyCode::Char('q') | KeyCode::Esc => return Turn::Quit,
+            _ => {}
+        }
+    }
+}
+
+fn stats_lines<M>(stats: &SearchStats<M>, line: Vec<String>) -> Vec<String> {
+    vec![
+        format!("Depth {}, {} nodes", stats.depth_reached, stats.nodes),
+        format!(
+            "{} cutoffs, {} ms",
+            stats.cutoffs,
+            stats.elapsed.as_millis()
+        ),
+        format!("Line: {}", line.join(" ")),
+    ]
+}
+
+fn saved_status(path: &str, result: Result<(), String>) -> String {
+    match result {
+        Ok(_) => format!("Saved the game to {}", path),
+        Err(msg) => msg,
+    }
+}
+
+fn undone_status(screen: &mut Screen, count: usize) {
+    let len = screen.moves.len();
+    screen.moves.truncate(len.saturating_sub(count));
+    screen.last_move = None;
+    screen.status = match count {
+        0 => "No moves to take back".to_string(),
+        1 => "Took back 1 move".to_string(),
+        _ => format!("Took back {} moves", count),
+    };
+}
+
+// Show the result and wait for a key before the screen is put back
+fn finish(screen: &RefCell<Screen>, winner: String) {
+    {
+        let mut screen = screen.borrow_mut();
+        screen.status = if winner == "Draw" {
+            "It's a draw".to_string()
+        } else {
+            format!("{} has won! Congratulations!", winner)
+        };
+        screen.keys = "Press any key to leave".to_string();
+        if screen.draw().is_err() {
+            return;
+        }
+    }
+    let _ = read_key();
+}
+
+// Full screen Connect-4 with coloured discs and a column cursor
+pub struct ConnectFourTui {
+    screen: RefCell<Screen>,
+}
+
+impl ConnectFourTui {
+    pub fn new(game: &ConnectFourGame) -> io::Result<Self> {
+        let ai_player = if game.with_ai {
+            Some(game.ai_player)
+        } else {
+            None
+        };
+        let mut screen = Screen::new(
+            "Connect-4".to_string(),
+            (game.p1.clone(), game.p2.clone()),
+            ai_player,
+        )?;
+        screen.keys = "←/→ or 0-9 move, Enter drop, u undo, h hint, s save, q quit".to_string();
+        Ok(ConnectFourTui {
+            screen: RefCell::new(screen),
+        })
+    }
+
+    // Four chips of one player in a line, the whole line when there are more
+    fn winning_line(cells: &[Vec<Disc>]) -> Vec<(usize, usize)> {
+        let num_rows = cells.len() as i64;
+        let num_cols = cells.first().map_or(0, |row| row.len()) as i64;
+        let mut line = Vec::new();
+        for row in 0..num_rows {
+            for col in 0..num_cols {
+                let disc = cells[row as usize][col as usize];
+                if disc.is_none() {
+                    continue;
+                }
+                for (dr, dc) in [(0, 1), (1, 0), (1, 1), (1, -1)].iter() {
+                    let cells_in_line = (0..4)
+                        .map(|i| (row + dr * i, col + dc * i))
+                        .collect::<Vec<(i64, i64)>>();
+                    let same = cells_in_line.iter().all(|(r, c)| {
+                        *r >= 0
+                            && *r < num_rows
+                            && *c >= 0
+                            && *c < num_cols
+                            && cells[*r as usize][*c as usize] == disc
+                    });
+                    if same {
+                        for (r, c) in cells_in_line {
+                            if !line.contains(&(r as usize, c as usize)) {
+                                line.push((r as usize, c as usize));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        line
+    }
+}
+
+impl ConnectFourGameEvents for ConnectFourTui {
+    fn introduction(&self) {}
+
+    fn show_grid(&self, grid: &crate::connect_four::Grid) {
+        let cells = (0..grid.num_rows)
+            .map(|row| {
+                (0..grid.num_cols)
+                    .map(|col| match grid.get(row, col) {
+                        0 => None,
+                        x if x > 0 => Some((Player::One, None)),
+                        _ => Some((Player::Two, None)),
+                    })
+                    .collect()
+            })
+            .collect::<Vec<Vec<Disc>>>();
+        let mut screen = self.screen.borrow_mut();
+        screen.winning = ConnectFourTui::winning_line(&cells);
+        screen.set_cells(cells);
+        let _ = screen.draw();
+    }
+
+    fn player_turn_message(&self, p1_turn: bool) {
+        let mut screen = self.screen.borrow_mut();
+        screen.turn = if p1_turn { Player::One } else { Player::Two };
+        if screen.ai_player == Some(screen.turn) {
+            screen.status = "Computer is thinking...".to_string();
+            let _ = screen.draw();
+        }
+    }
+
+    fn player_turn(&self, _col_size: usize) -> Turn<usize> {
+        let turn = choose_column(&self.screen, false);
+        self.screen.borrow_mut().status.clear();
+        turn
+    }
+
+    fn selected_column(&self, player: String, col: usize) {
+        let mut screen = self.screen.borrow_mut();
+        let disc = Some((screen.turn, None));
+        let _ = screen.drop_chip(col, disc);
+        screen.moves.push(format!("{} {}", player, col));
+        screen.status = format!("{} played column {}", player, col);
+    }
+
+    fn animate_chip(&self) {}
+
+    fn search_stats(&self, stats: &SearchStats<usize>) {
+        let line = stats
+            .principal_variation
+            .iter()
+            .map(|col| col.to_string())
+            .collect();
+        self.screen.borrow_mut().ai_info = stats_lines(stats, line);
+    }
+
+    fn hint(&self, col: usize, stats: &SearchStats<usize>) {
+        self.search_stats(stats);
+        let mut screen = self.screen.borrow_mut();
+        screen.cursor = col;
+        screen.status = format!("Hint: column {}", col);
+    }
+
+    fn moves_undone(&self, count: usize) {
+        undone_status(&mut self.screen.borrow_mut(), count);
+    }
+
+    fn save_game(&self, path: &str, game: &ConnectFourGame) {
+        let result = SavedGame::from_connect_four(game).write(path);
+        self.screen.borrow_mut().status = saved_status(path, result);
+    }
+
+    fn invalid_move(&self) {
+        self.screen.borrow_mut().status = "Column is full, pick another one".to_string();
+    }
+
+    fn game_over(&self, winner: String) {
+        finish(&self.screen, winner);
+    }
+}
+
+// Full screen TOOT-OTTO, letters are drawn in the colour of the player who played them
+pub struct TootOttoTui {
+    screen: RefCell<Screen>,
+}
+
+impl TootOttoTui {
+    pub fn new(game: &TootOttoGame) -> io::Result<Self> {
+        let ai_player = if game.with_ai {
+            Some(game.ai_player)
+        } else {
+            None
+        };
+        let title = format!(
+            "{} - {}, {} - {}",
+            game.p1,
+            game.variant.word_string(Player::One),
+            game.p2,
+            game.variant.word_string(Player::Two)
+        );
+        let mut screen = Screen::new(title, (game.p1.clone(), game.p2.clone()), ai_player)?;
+        screen.letters = game.variant.letters.iter().map(|letter| letter.0).collect();
+        screen.keys =
+            "←/→ or 0-9 move, Tab letter, Enter drop, u undo, h hint, s save, q quit".to_string();
+        Ok(TootOttoTui {
+            screen: RefCell::new(screen),
+        })
+    }
+}
+
+impl TootOttoGameEvents for TootOttoTui {
+    fn introduction(&self) {}
+
+    fn show_grid(&self, grid: &crate::toot_otto::Grid) {
+        let cells = (0..grid.num_rows)
+            .map(|row| {
+                (0..grid.num_cols)
+                    .map(|col| {
+                        grid.get(row, col)
+                            .map(|(player, chip_type)| (player, Some(chip_type.0)))
+                    })
+                    .collect()
+            })
+            .collect::<Vec<Vec<Disc>>>();
+        let mut screen = self.screen.borrow_mut();
+        screen.set_cells(cells);
+        let _ = screen.draw();
+    }
+
+    fn player_turn_message(&self, p1_turn: bool) {
+        let mut screen = self.screen.borrow_mut();
+        screen.turn = if p1_turn { Player::One } else { Player::Two };
+        if screen.ai_player == Some(screen.turn) {
+            screen.status = "Computer is thinking...".to_string();
+            let _ = screen.draw();
+        }
+    }
+
+    fn player_turn(&self, _col_size: usize, chips: Inventory) -> Turn<Move> {
+        {
+            let mut screen = self.screen.borrow_mut();
+            screen.chips = format!("Tiles left: {}", chips);
+
+            // Start from a letter the player still has
+            let letter = screen.current_letter().map(ChipType);
+            if letter.is_some_and(|letter| chips.remaining(letter) == 0) {
+                let letters = screen.letters.clone();
+                if let Some(idx) = letters
+                    .iter()
+                    .position(|letter| chips.remaining(ChipType(*letter)) > 0)
+                {
+                    screen.letter = idx;
+                }
+            }
+        }
+        let turn = match choose_column(&self.screen, true) {
+            Turn::Move(col) => {
+                let letter = self.screen.borrow().current_letter().unwrap();
+                Turn::Move((ChipType(letter), col))
+            }
+            Turn::Undo => Turn::Undo,
+            Turn::Hint => Turn::Hint,
+            Turn::Save(path) => Turn::Save(path),
+            Turn::Quit => Turn::Quit,
+        };
+        self.screen.borrow_mut().status.clear();
+        turn
+    }
+
+    fn selected_column(&self, player: String, chip_type: ChipType, col: usize) {
+        let mut screen = self.screen.borrow_mut();
+        let disc = Some((screen.turn, Some(chip_type.0)));
+        let _ = screen.drop_chip(col, disc);
+        screen
+            .moves
+            .push(format!("{} {}{}", player, chip_type, col));
+        screen.status = format!("{} played {} in column {}", player, chip_type, col);
+    }
+
+    fn animate_chip(&self) {}
+
+    fn search_stats(&self, stats: &SearchStats<Move>) {
+        let line = stats
+            .principal_variation
+            .iter()
+            .map(|(chip_type, col)| format!("{}{}", chip_type, col))
+            .collect();
+        self.screen.borrow_mut().ai_info = stats_lines(stats, line);
+    }
+
+    fn hint(&self, chip_type: ChipType, col: usize, stats: &SearchStats<Move>) {
+        self.search_stats(stats);
+        let mut screen = self.screen.borrow_mut();
+        screen.cursor = col;
+        if let Some(idx) = screen
+            .letters
+            .iter()
+            .position(|letter| *letter == chip_type.0)
+        {
+            screen.letter = idx;
+        }
+        screen.status = format!("Hint: {} in column {}", chip_type, col);
+    }
+
+    fn moves_undone(&self, count: usize) {
+        undone_status(&mut self.screen.borrow_mut(), count);
+    }
+
+    fn save_game(&self, path: &str, game: &TootOttoGame) {
+        let result = SavedGame::from_toot_otto(game).write(path);
+        self.screen.borrow_mut().status = saved_status(path, result);
+    }
+
+    fn invalid_move(&self) {
+        self.screen.borrow_mut().status = "Column is full, pick another one".to_string();
+    }
+
+    fn out_of_chips(&self, chip_type: ChipType) {
+        self.screen.borrow_mut().status = format!("No {} tiles left", chip_type);
+    }
+
+    // Highlight every cell of the words the last tile spelled
+    fn words_spelled(&self, words: &[SpelledWord]) {
+        let mut screen = self.screen.borrow_mut();
+        for word in words {
+            let (from, to) = (word.from, word.to);
+            let steps = word.word.len() as i64 - 1;
+            let dr = (to.0 as i64 - from.0 as i64) / steps.max(1);
+            let dc = (to.1 as i64 - from.1 as i64) / steps.max(1);
+            for i in 0..=steps {
+                let cell = (
+                    (from.0 as i64 + dr * i) as usize,
+                    (from.1 as i64 + dc * i) as usize,
+                );
+                screen.winning.push(cell);
+            }
+        }
+        let _ = screen.draw();
+    }
+
+    fn game_over(&self, winner: String) {
+        finish(&self.screen, winner);
+    }
+}
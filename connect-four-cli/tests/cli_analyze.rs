@@ -0,0 +1,146 @@
+mod common;
+
+use common::run;
+
+// Evaluation of each move in the table the analysis prints
+fn evaluations(out: &str) -> Vec<(String, String)> {
+    out.lines()
+        .skip_while(|line| !line.starts_with("Move"))
+        .skip(1)
+        .take_while(|line| !line.starts_with("Best move"))
+        .map(|line| (line[..7].trim().to_string(), line[7..24].trim().to_string()))
+        .collect()
+}
+
+#[test]
+fn finds_the_winning_moves() {
+    let (success, out) = run(&["analyze", "3 3 4 4", "--ai-depth", "6"], "");
+    assert!(success);
+    assert!(out.contains("P1 to move, searched to depth 6"));
+    let moves = evaluations(&out);
+    assert_eq!(moves.len(), 7);
+    assert_eq!(moves[0].1, "wins in 3");
+    assert_eq!(moves[1].1, "wins in 3");
+    let mut winning = vec![moves[0].0.clone(), moves[1].0.clone()];
+    winning.sort();
+    assert_eq!(winning, vec!["2", "5"]);
+    assert!(!moves[2].1.starts_with("wins"));
+}
+
+#[test]
+fn every_move_loses_against_an_open_three() {
+    let (success, out) = run(&["analyze", "3 3 4 4 5", "--ai-depth", "2"], "");
+    assert!(success);
+    assert!(out.contains("P2 to move"));
+    let moves = evaluations(&out);
+    assert_eq!(moves.len(), 7);
+    assert!(moves.iter().all(|(_, outcome)| outcome == "loses in 2"));
+}
+
+#[test]
+fn diagram_gives_the_position_of_the_moves() {
+    let (_, from_moves) = run(&["analyze", "3 3 4 4", "--ai-depth", "3"], "");
+    let (success, from_diagram) = run(
+        &[
+            "analyze",
+            "......./......./......./......./...YY../...RR..",
+            "--ai-depth",
+            "3",
+        ],
+        "",
+    );
+    assert!(success);
+    assert_eq!(evaluations(&from_moves), evaluations(&from_diagram));
+}
+
+#[test]
+fn diagram_from_a_file() {
+    let path = std::env::temp_dir().join(format!("cli_analyze_{}.txt", std::process::id()));
+    std::fs::write(&path, "_ _ _ _ _ _ _\n_ _ _ _ _ _ _\n_ _ _ _ _ _ _\n_ _ _ _ _ _ _\n_ _ _ Y _ _ _\n_ _ _ R R _ _\n").unwrap();
+    let (success, out) = run(&["analyze", path.to_str().unwrap(), "--ai-depth", "2"], "");
+    std::fs::remove_file(&path).unwrap();
+    assert!(success);
+    assert!(out.contains("P2 to move"));
+    assert_eq!(evaluations(&out).len(), 7);
+}
+
+#[test]
+fn time_limit_deepens_the_search() {
+    let (success, out) = run(&["analyze", "3", "--time", "0.3"], "");
+    assert!(success);
+    let depth = out
+        .lines()
+        .find_map(|line| line.strip_prefix("P2 to move, searched to depth "))
+        .unwrap();
+    assert!(depth.parse::<u32>().unwrap() >= 2);
+}
+
+#[test]
+fn toot_otto_diagram_shows_the_losing_letters() {
+    let (success, out) = run(
+        &[
+            "analyze",
+            "--game",
+            "toot-otto",
+            "....../....../....../...O../..Tot.",
+            "--ai-depth",
+            "1",
+        ],
+        "",
+    );
+    assert!(success);
+    assert!(out.contains("Tiles left for P1: 5 T, 5 O"));
+    let moves = evaluations(&out);
+    assert_eq!(moves.len(), 12);
+    assert!(moves.contains(&("T1".to_string(), "loses in 2".to_string())));
+    assert!(moves.contains(&("O0".to_string(), "loses in 2".to_string())));
+}
+
+#[test]
+fn finished_and_invalid_positions() {
+    let (success, out) = run(&["analyze", "0 1 0 1 0 1 0"], "");
+    assert!(success);
+    assert!(out.contains("P1 has won!"));
+
+    let (success, out) = run(
+        &["analyze", "......./......./......./.....R./...Y.../..RRY.."],
+        "",
+    );
+    assert!(!success);
+    assert!(out.contains("The chip in column 5 is floating"));
+
+    let (success, out) = run(
+        &["analyze", "......./......./......./......./...Y.../..RRR.."],
+        "",
+    );
+    assert!(!success);
+    assert!(out.contains("not a position of a game"));
+
+    let (success, out) = run(&["analyze", "3 3", "--ai-depth", "0"], "");
+    assert!(!success);
+    assert!(out.contains("at least 1"));
+}
+
+#[test]
+fn a_line_to_a_full_board_is_a_draw() {
+    let (success, out) = run(
+        &[
+            "analyze",
+            "--rows",
+            "4",
+            "--cols",
+            "4",
+            "--ai-depth",
+            "6",
+            "..../RYRY/YRYR/RYRY",
+        ],
+        "",
+    );
+    assert!(success);
+    let moves = evaluations(&out);
+    assert_eq!(moves.len(), 4);
+    assert_eq!(moves[0], ("3".to_string(), "wins in 1".to_string()));
+    assert_eq!(moves[1], ("0".to_string(), "+0".to_string()));
+    assert_eq!(moves[2].1, "loses in 2");
+    assert_eq!(moves[3].1, "loses in 2");
+}
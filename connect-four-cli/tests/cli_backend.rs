@@ -0,0 +1,170 @@
+mod common;
+
+use common::run;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+// A request the stand-in backend received
+struct Request {
+    method: String,
+    path: String,
+    body: String,
+}
+
+// Stand-in for connect_four_backend answering every request with answer, returns
+// its URL and the requests it receives
+fn backend(answer: &str) -> (String, Receiver<Request>) {
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        answer.len(),
+        answer
+    );
+    backend_response(response.into_bytes())
+}
+
+// Stand-in sending the whole HTTP response as given
+fn backend_response(response: Vec<u8>) -> (String, Receiver<Request>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let mut words = line.split_whitespace();
+            let method = words.next().unwrap().to_string();
+            let path = words.next().unwrap().to_string();
+
+            let mut length = 0;
+            loop {
+                line.clear();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                let lower = line.to_lowercase();
+                if let Some(value) = lower.strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+
+            stream.write_all(&response).unwrap();
+            let body = String::from_utf8(body).unwrap();
+            sender.send(Request { method, path, body }).unwrap();
+        }
+    });
+    (url, receiver)
+}
+
+#[test]
+fn finished_games_are_posted() {
+    let (url, requests) = backend(r#"{"$oid":"5e9b2a1f0000000000000000"}"#);
+    let (success, out) = run(&["play", "--backend", &url], "0\n1\n0\n1\n0\n1\n0\n");
+    assert!(success);
+    assert!(out.contains("Recorded the game"));
+
+    let request = requests.recv().unwrap();
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path, "/games");
+    let game: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+    assert_eq!(game["gameType"], "Connect4 with Human");
+    assert_eq!(game["Player1Name"], "P1");
+    assert_eq!(game["Player2Name"], "P2");
+    assert_eq!(game["WinnerName"], "P1");
+    assert_eq!(game["rows"], 6);
+    assert_eq!(game["cols"], 7);
+    assert_eq!(
+        game["moves"],
+        serde_json::json!(["0", "1", "0", "1", "0", "1", "0"])
+    );
+    assert_eq!(game["first_player"], 1);
+}
+
+#[test]
+fn unfinished_games_are_not_posted() {
+    let (url, requests) = backend("{}");
+    let (_, out) = run(&["play", "--backend", &url], "3\nquit\n");
+    assert!(!out.contains("Recorded the game"));
+    assert!(requests.try_recv().is_err());
+}
+
+#[test]
+fn history_lists_the_latest_game_first() {
+    let (url, requests) = backend(
+        r#"[
+            {"_id":{"$oid":"1"},"gameType":"Connect4 with Computer","Player1Name":"Ann",
+             "Player2Name":"Computer","WinnerName":"Computer","GameDate":1586000000000},
+            {"_id":{"$oid":"2"},"gameType":"TOOT-OTTO with Human","Player1Name":"Ann",
+             "Player2Name":"Bob","WinnerName":"Bob","GameDate":1587000000000}
+        ]"#,
+    );
+    let (success, out) = run(&["history", "--backend", &url], "");
+    assert!(success);
+    assert_eq!(requests.recv().unwrap().path, "/games");
+
+    let lines = out.lines().collect::<Vec<&str>>();
+    assert!(lines[0].starts_with("Date"));
+    assert!(lines[1].starts_with("2020-04-16"));
+    assert!(lines[1].contains("TOOT-OTTO with Human"));
+    assert!(lines[1].ends_with("Bob"));
+    assert!(lines[2].starts_with("2020-04-04"));
+    assert!(lines[2].contains("Connect4 with Computer"));
+}
+
+#[test]
+fn leaderboard_ranks_by_wins() {
+    let (url, requests) = backend(
+        r#"[{"_id":"Bob","count":2},{"_id":"Draw","count":7},{"_id":"Computer","count":5}]"#,
+    );
+    let (success, out) = run(&["leaderboard", "--backend", &url], "");
+    assert!(success);
+    assert_eq!(requests.recv().unwrap().path, "/wins");
+
+    let lines = out.lines().collect::<Vec<&str>>();
+    assert!(lines[1].starts_with("1") && lines[1].contains("Computer") && lines[1].ends_with("5"));
+    assert!(lines[2].starts_with("2") && lines[2].contains("Bob") && lines[2].ends_with("2"));
+    assert!(!out.contains("Draw"));
+}
+
+#[test]
+fn chunks_may_split_a_character() {
+    // The first chunk ends in the middle of the ë
+    let answer = r#"[{"_id":"Zoë","count":3}]"#.as_bytes();
+    let split = answer.iter().position(|byte| *byte >= 0x80).unwrap() + 1;
+    let mut response =
+        b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\n\r\n"
+            .to_vec();
+    for chunk in [&answer[..split], &answer[split..]].iter() {
+        response.extend(format!("{:x}\r\n", chunk.len()).into_bytes());
+        response.extend_from_slice(chunk);
+        response.extend_from_slice(b"\r\n");
+    }
+    response.extend_from_slice(b"0\r\n\r\n");
+
+    let (url, _requests) = backend_response(response);
+    let (success, out) = run(&["leaderboard", "--backend", &url], "");
+    assert!(success, "{}", out);
+    assert!(out.contains("Zoë"));
+}
+
+#[test]
+fn unreachable_backend_is_reported() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let (success, out) = run(&["history", "--backend", &url], "");
+    assert!(!success);
+    assert!(out.contains("Could not reach"));
+
+    let (success, out) = run(&["leaderboard", "--backend", "https://example.com"], "");
+    assert!(!success);
+    assert!(out.contains("is not an http:// URL"));
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use serde_json::Value;
+
+// Run the CLI with the arguments and moves on stdin, returns the JSON it printed
+fn run(args: &[&str], moves: &str) -> (bool, Value) {
+    let (success, out) = common::run(args, moves);
+    (
+        success,
+        serde_json::from_str(&out).expect("batch did not print JSON"),
+    )
+}
+
+#[test]
+fn plays_a_game_from_stdin() {
+    let (success, result) = run(&["batch"], "3 3 4 4 # the opening\n5, 5\n6\n");
+    assert!(success);
+    assert_eq!(result["game"], "connect4");
+    assert_eq!(result["moves"].as_array().unwrap().len(), 7);
+    assert_eq!(result["board"][4], "___YYY_");
+    assert_eq!(result["board"][5], "___RRRR");
+    assert_eq!(result["state"], "won");
+    assert_eq!(result["winner"], "P1");
+    assert_eq!(result["to_move"], Value::Null);
+    assert_eq!(result["error"], Value::Null);
+}
+
+#[test]
+fn reports_the_position_while_running() {
+    let (success, result) = run(&["batch", "--rows", "4", "--cols", "5"], "2 1");
+    assert!(success);
+    assert_eq!(result["state"], "running");
+    assert_eq!(result["to_move"], "P1");
+    assert_eq!(result["board"][3], "_YR__");
+}
+
+#[test]
+fn stops_at_an_illegal_move() {
+    let (success, result) = run(&["batch"], "3 3 3 3 3 3 3 4");
+    assert!(!success);
+    assert_eq!(result["moves"].as_array().unwrap().len(), 6);
+    assert_eq!(result["error"]["index"], 7);
+    assert_eq!(result["error"]["move"], "3");
+    assert_eq!(result["error"]["message"], "Column 3 is full");
+
+    let (success, result) = run(&["batch"], "1 x");
+    assert!(!success);
+    assert_eq!(result["error"]["index"], 2);
+    assert_eq!(result["moves"][0], "1");
+
+    let (success, result) = run(&["batch"], "0 1 0 1 0 1 0 2");
+    assert!(!success);
+    assert_eq!(result["winner"], "P1");
+    assert_eq!(result["error"]["message"], "The game is over");
+}
+
+#[test]
+fn plays_toot_otto_from_a_file() {
+    let path = std::env::temp_dir().join(format!("connect_four_batch_{}", std::process::id()));
+    std::fs::write(&path, "T0 o1\nO2 T3\n").unwrap();
+    let args = [
+        "batch",
+        path.to_str().unwrap(),
+        "--game",
+        "toot-otto",
+        "--rows",
+        "4",
+        "--cols",
+        "4",
+    ];
+    let (success, result) = run(&args, "");
+    std::fs::remove_file(&path).unwrap();
+    assert!(success);
+    assert_eq!(result["board"][3], "ToOt");
+    assert_eq!(result["winner"], "P1");
+    assert_eq!(result["words"][0]["word"], "TOOT");
+    assert_eq!(result["words"][0]["player"], 1);
+
+    let (success, result) = run(&["batch", "--game", "toot-otto"], "T0 X1");
+    assert!(!success);
+    assert_eq!(result["error"]["message"], "X is not a letter of this game");
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use connect_four_cli::connect_four;
+use connect_four_cli::save::{self, SavedGame};
+
+// Run the CLI, which must exit successfully, and return what it printed
+fn run(args: &[&str], script: &str) -> String {
+    let (success, out) = common::run(args, script);
+    assert!(success, "CLI failed:\n{}", out);
+    out
+}
+
+fn temp_path(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("{}_{}.json", name, std::process::id()));
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn reprompts_on_bad_columns() {
+    let out = run(&["play"], "x\n9\n\n3\nquit\n");
+    assert!(out.contains("x is not a column"));
+    assert!(out.contains("9 is not a column"));
+    assert!(out.contains("No selection made"));
+    assert!(out.contains("P1 Selected Column 3"));
+    assert!(out.contains("Quitting the game"));
+}
+
+#[test]
+fn end_of_input_quits() {
+    let out = run(&["play"], "3\n");
+    assert!(out.contains("P1 Selected Column 3"));
+    assert!(out.contains("End of input"));
+}
+
+#[test]
+fn rejects_boards_too_large_to_count() {
+    let huge = (usize::MAX / 2).to_string();
+    let (success, out) = common::run(&["play", "--rows", &huge, "--cols", "4"], "");
+    assert!(!success);
+    assert!(out.contains("at most 80 cells"));
+}
+
+#[test]
+fn end_of_input_leaves_every_menu_the_same_way() {
+    for script in ["", "1\n", "1\n1\n", "2\n1\n2\n", "2\n1\n2\n3\n1\n"].iter() {
+        let (success, out) = common::run(&[], script);
+        assert!(!success, "menu exited successfully on {:?}", script);
+        assert_eq!(out.matches("End of input").count(), 1, "{}", out);
+        assert!(!out.contains("Invalid input"), "{}", out);
+    }
+}
+
+#[test]
+fn full_column_is_rejected() {
+    let out = run(&["play", "--rows", "4"], "0\n0\n0\n0\n0\nquit\n");
+    assert!(out.contains("Column is full"));
+}
+
+#[test]
+fn plays_a_game_to_the_end() {
+    let out = run(&["play"], "0\n1\n0\n1\n0\n1\n0\n");
+    assert!(out.contains("P1 has won!"));
+    assert!(!out.contains("End of input"));
+}
+
+#[test]
+fn undo_takes_back_the_computer_reply() {
+    let out = run(&["play", "--ai-depth", "1"], "3\nundo\nundo\nquit\n");
+    assert!(out.contains("Took back 2 moves"));
+    assert!(out.contains("No moves to take back"));
+}
+
+#[test]
+fn hint_suggests_a_column() {
+    let out = run(&["play", "--ai-depth", "2"], "hint\nquit\n");
+    assert!(out.contains("Hint: Column"));
+}
+
+#[test]
+fn save_writes_the_moves() {
+    let path = temp_path("cli_input_save");
+    let out = run(&["play"], &format!("save\n3\n4\nsave {}\nquit\n", path));
+    assert!(out.contains("Usage: save <file>"));
+    assert!(out.contains("Saved the game"));
+    let saved = SavedGame::read(&path).unwrap();
+    assert_eq!(saved.version, save::SAVE_VERSION);
+    assert_eq!(saved.game, save::CONNECT_FOUR);
+    assert_eq!(saved.moves, vec!["3", "4"]);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn resume_continues_a_saved_game() {
+    let path = temp_path("cli_input_resume");
+    run(&["play"], &format!("0\n1\n0\n1\nsave {}\nquit\n", path));
+    let out = run(&["resume", &path], "0\n1\n0\n");
+    assert!(out.contains("P1 has won!"));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn resume_keeps_the_computer_side() {
+    let path = temp_path("cli_input_resume_ai");
+    run(
+        &["play", "--ai-depth", "2", "--first", "computer"],
+        &format!("save {}\nquit\n", path),
+    );
+    let saved = SavedGame::read(&path).unwrap();
+    assert!(saved.with_ai);
+    assert_eq!(saved.moves.len(), 1);
+    let out = run(&["resume", &path], "quit\n");
+    assert!(!out.contains("Computer Selected"));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn resume_toot_otto_keeps_the_words() {
+    let path = temp_path("cli_input_resume_toot");
+    run(
+        &["play", "--game", "toot-otto"],
+        &format!("t\n2\nsave {}\nquit\n", path),
+    );
+    let saved = SavedGame::read(&path).unwrap();
+    assert_eq!(saved.moves, vec!["T2"]);
+    assert_eq!(saved.variant.unwrap().p1_word, "TOOT");
+    let out = run(&["resume", &path], "quit\n");
+    assert!(out.contains("Quitting the game"));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn resume_rejects_bad_saves() {
+    let path = temp_path("cli_input_resume_bad");
+
+    std::fs::write(&path, "3 4\n").unwrap();
+    let (success, out) = common::run(&["resume", &path], "");
+    assert!(!success);
+    assert!(out.contains("Not a saved game"));
+
+    let mut saved = SavedGame::from_connect_four(&connect_four::Game::new(
+        6,
+        7,
+        false,
+        "P1".to_string(),
+        "P2".to_string(),
+        4,
+    ));
+    saved.moves = vec!["9".to_string()];
+    saved.write(&path).unwrap();
+    let (success, out) = common::run(&["resume", &path], "");
+    assert!(!success);
+    assert!(out.contains("Move 1 (9) is illegal"));
+
+    saved.moves.clear();
+    saved.version = save::SAVE_VERSION + 1;
+    saved.write(&path).unwrap();
+    let (success, out) = common::run(&["resume", &path], "");
+    assert!(!success);
+    assert!(out.contains("this version reads up to"));
+
+    saved.version = save::SAVE_VERSION;
+    saved.rows = usize::MAX / 2;
+    saved.cols = 4;
+    saved.write(&path).unwrap();
+    let (success, out) = common::run(&["resume", &path], "");
+    assert!(!success);
+    assert!(out.contains("at most 80 cells"));
+
+    std::fs::remove_file(&path).unwrap();
+    let (success, out) = common::run(&["resume", &path], "");
+    assert!(!success);
+    assert!(out.contains("Could not read"));
+}
+
+#[test]
+fn toot_otto_reprompts_on_bad_letters() {
+    let out = run(
+        &["play", "--game", "toot-otto"],
+        "A\ntt\no\nundo\nt\n7\n2\nquit\n",
+    );
+    assert!(out.contains("A is not one of the letters"));
+    assert!(out.contains("tt is not a letter"));
+    assert!(out.contains("No moves to take back"));
+    assert!(out.contains("7 is not a column"));
+    assert!(out.contains("P1 Selected Chip Type T and Column 2"));
+}
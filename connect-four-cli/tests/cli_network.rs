@@ -0,0 +1,152 @@
+use connect_four_cli::protocol::{Connection, Message, PROTOCOL_VERSION};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::time::Duration;
+
+struct Process {
+    child: Child,
+    stdout: BufReader<ChildStdout>,
+}
+
+// Start the CLI with the arguments and type script into it
+fn spawn(args: &[&str], script: &str) -> Process {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_connect_four_cli"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to start the CLI");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(script.as_bytes())
+        .unwrap();
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    Process { child, stdout }
+}
+
+// Host a game on a free port, returns the process and the address it listens on
+fn host(args: &[&str], script: &str) -> (Process, String) {
+    let mut args = args.to_vec();
+    args.insert(0, "127.0.0.1:0");
+    args.insert(0, "host");
+    let mut host = spawn(&args, script);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        assert!(host.stdout.read_line(&mut line).unwrap() > 0, "host exited");
+        if let Some(addr) = line.trim().strip_prefix("Waiting for a player to join on ") {
+            return (host, addr.to_string());
+        }
+    }
+}
+
+// Everything else the process prints until it exits
+fn finish(mut process: Process) -> (bool, String) {
+    let mut out = String::new();
+    process.stdout.read_to_string(&mut out).unwrap();
+    (process.child.wait().unwrap().success(), out)
+}
+
+fn connect(addr: &str) -> Connection {
+    let stream = TcpStream::connect(addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    Connection::new(stream).unwrap()
+}
+
+fn hello(conn: &mut Connection) -> Vec<String> {
+    conn.send(&Message::Hello {
+        version: PROTOCOL_VERSION,
+    })
+    .unwrap();
+    match conn.receive().unwrap() {
+        Message::Start { game, guest } => {
+            assert_eq!(guest, 2);
+            assert_eq!(game.p1, "Host");
+            game.moves
+        }
+        x => panic!("expected the game, got {:?}", x),
+    }
+}
+
+fn play(conn: &mut Connection, mv: &str) -> Message {
+    conn.send(&Message::Move { mv: mv.to_string() }).unwrap();
+    conn.receive().unwrap()
+}
+
+fn played(mv: &str) -> Message {
+    Message::Move { mv: mv.to_string() }
+}
+
+#[test]
+fn host_and_join_play_a_game() {
+    let (host, addr) = host(&[], "0\n0\n0\n0\n");
+    let guest = spawn(&["join", &addr], "1\n1\n1\n");
+
+    let (success, out) = finish(guest);
+    assert!(success);
+    assert!(out.contains("Joined the game"));
+    assert!(out.contains("Guest Selected Column 1"));
+    assert!(out.contains("Host has won!"));
+
+    let (success, out) = finish(host);
+    assert!(success);
+    assert!(out.contains("joined the game"));
+    assert!(out.contains("Host has won!"));
+}
+
+#[test]
+fn guest_that_quits_ends_the_game() {
+    let (host, addr) = host(&["--game", "toot-otto"], "t\n2\n");
+    let guest = spawn(&["join", &addr], "quit\n");
+
+    let (_, out) = finish(guest);
+    assert!(out.contains("Host Selected Chip Type T and Column 2"));
+
+    let (success, out) = finish(host);
+    assert!(success);
+    assert!(out.contains("The other player left the game"));
+}
+
+#[test]
+fn host_checks_moves_and_takes_the_guest_back() {
+    let (host, addr) = host(&[], "3\n4\n5\n6\n");
+
+    let mut conn = connect(&addr);
+    conn.send(&Message::Hello {
+        version: PROTOCOL_VERSION + 1,
+    })
+    .unwrap();
+    match conn.receive().unwrap() {
+        Message::Rejected { reason } => assert!(reason.contains("protocol version")),
+        x => panic!("expected a rejection, got {:?}", x),
+    }
+
+    let mut conn = connect(&addr);
+    assert!(hello(&mut conn).is_empty());
+    assert_eq!(conn.receive().unwrap(), played("3"));
+    match play(&mut conn, "9") {
+        Message::Rejected { reason } => assert!(reason.contains("cannot be played")),
+        x => panic!("expected a rejection, got {:?}", x),
+    }
+    assert_eq!(play(&mut conn, "0"), played("0"));
+    assert_eq!(conn.receive().unwrap(), played("4"));
+    drop(conn);
+
+    // The game continues where it was after joining again
+    let mut conn = connect(&addr);
+    assert_eq!(hello(&mut conn), vec!["3", "0", "4"]);
+    assert_eq!(play(&mut conn, "0"), played("0"));
+    assert_eq!(conn.receive().unwrap(), played("5"));
+    assert_eq!(play(&mut conn, "0"), played("0"));
+    assert_eq!(conn.receive().unwrap(), played("6"));
+
+    let (success, out) = finish(host);
+    assert!(success);
+    assert!(out.contains("The other player disconnected"));
+    assert!(out.contains("Host has won!"));
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn steps_through_the_moves() {
+    let (success, out) = run(
+        &["replay", "3 3 4 4 5 5 6"],
+        "n\n5\np\nlast\nn\nfirst\np\nq\n",
+    );
+    assert!(success);
+    assert!(out.contains("Start of the game, 7 moves"));
+    assert!(out.contains("Move 1 of 7: P1 dropped a chip in column 3"));
+    assert!(out.contains("Move 5 of 7: P1 dropped a chip in column 5"));
+    assert!(out.contains("Move 4 of 7: P2 dropped a chip in column 4"));
+    assert!(out.contains("P1 has won"));
+    assert!(out.contains("This is the last move"));
+    assert!(out.contains("This is the start of the game"));
+}
+
+#[test]
+fn evaluates_each_position() {
+    let (_, out) = run(&["replay", "3 3 4 4 5", "--ai-depth", "2"], "last\n4\nq\n");
+    assert!(out.contains("Evaluation: P2 loses within 2 moves"));
+    assert!(out.contains("Evaluation: P1 wins within 3 moves"));
+    assert!(out.contains("Best line: "));
+}
+
+#[test]
+fn rejects_bad_commands_and_moves() {
+    let (_, out) = run(&["replay", "3 3"], "x\n9\nq\n");
+    assert!(out.contains("x is not a command or a move from 0 to 2"));
+    assert!(out.contains("9 is not a command"));
+
+    let (success, out) = run(&["replay", "3 3 9"], "");
+    assert!(!success);
+    assert!(out.contains("Move 3 (9) is illegal"));
+}
+
+#[test]
+fn replays_a_saved_game() {
+    let path = std::env::temp_dir().join(format!("cli_replay_{}.json", std::process::id()));
+    let path = path.to_str().unwrap().to_string();
+    run(
+        &["play", "--game", "toot-otto"],
+        &format!("t\n2\no\n3\nsave {}\nquit\n", path),
+    );
+    let (success, out) = run(&["replay", &path, "--ai-depth", "1"], "last\nq\n");
+    assert!(success);
+    assert!(out.contains("Move 2 of 2: P2 dropped O in column 3"));
+    assert!(out.contains("P1 to move"));
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,25 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// Run the CLI with the arguments, type script into it and return whether it exited
+// successfully and what it printed. A backend set in the environment is ignored
+pub fn run(args: &[&str], script: &str) -> (bool, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_connect_four_cli"))
+        .args(args)
+        .env_remove("CONNECT_FOUR_BACKEND")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to start the CLI");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(script.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.success(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
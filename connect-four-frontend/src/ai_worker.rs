@@ -0,0 +1,226 @@
+use connect_four_cli::board::Player;
+use connect_four_cli::toot_otto::{Cell, ChipType, Inventory, Variant};
+use connect_four_cli::{connect_four, toot_otto};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::rc::Rc;
+use yew::agent::{Agent, AgentLink, Bridge, HandlerId, Public};
+
+// Bridge to the AI worker, shared with the free functions that drive the canvas
+pub type AiBridge = Rc<RefCell<Box<dyn Bridge<AiWorker>>>>;
+
+// Position sent to the worker, grid holds the raw items of the cli grid (TOOT-OTTO
+// cells encoded by encode_cell), chips the tiles left of each letter and p1_first
+// whether player one dropped the first chip
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum AiRequest {
+    ConnectFour {
+        num_rows: usize,
+        num_cols: usize,
+        grid: Vec<i32>,
+        p_move: i64,
+        max_ai_depth: u32,
+        p1_first: bool,
+    },
+    TootOtto {
+        num_rows: usize,
+        num_cols: usize,
+        grid: Vec<i32>,
+        p_move: i64,
+        max_ai_depth: u32,
+        p1_first: bool,
+        p1_word: String,
+        p2_word: String,
+        letters: String,
+        chips_per_letter: u32,
+        p1_chips: Vec<u32>,
+        p2_chips: Vec<u32>,
+    },
+}
+
+// Move picked by the computer
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum AiResponse {
+    ConnectFour { col: usize },
+    TootOtto { letter: char, col: usize },
+}
+
+impl AiRequest {
+    pub fn connect_four(game: &connect_four::Game) -> Self {
+        AiRequest::ConnectFour {
+            num_rows: game.grid.num_rows,
+            num_cols: game.grid.num_cols,
+            grid: game.grid.items.to_vec(),
+            p_move: game.p_move,
+            max_ai_depth: game.max_ai_depth,
+            p1_first: game.first_player == Player::One,
+        }
+    }
+
+    pub fn toot_otto(game: &toot_otto::Game) -> Self {
+        let letters = &game.variant.letters;
+        let counts = |chips: &Inventory| {
+            letters
+                .iter()
+                .map(|letter| chips.remaining(*letter))
+                .collect()
+        };
+        AiRequest::TootOtto {
+            num_rows: game.grid.num_rows,
+            num_cols: game.grid.num_cols,
+            grid: game
+                .grid
+                .items
+                .iter()
+                .map(|cell| encode_cell(*cell, letters))
+                .collect(),
+            p_move: game.p_move,
+            max_ai_depth: game.max_ai_depth,
+            p1_first: game.first_player == Player::One,
+            p1_word: game.variant.word_string(Player::One),
+            p2_word: game.variant.word_string(Player::Two),
+            letters: letters.iter().map(|letter| letter.0).collect(),
+            chips_per_letter: game.variant.chips_per_letter,
+            p1_chips: counts(&game.p1_chips),
+            p2_chips: counts(&game.p2_chips),
+        }
+    }
+}
+
+// TOOT-OTTO cell as a number, 0 when empty and otherwise one more than the index of
+// its letter, negated for player two
+fn encode_cell(cell: Cell, letters: &[ChipType]) -> i32 {
+    let (player, chip_type) = match cell {
+        Some(chip) => chip,
+        None => return 0,
+    };
+    let letter = match letters.iter().position(|letter| *letter == chip_type) {
+        Some(idx) => idx as i32 + 1,
+        None => return 0,
+    };
+    match player {
+        Player::One => letter,
+        Player::Two => -letter,
+    }
+}
+
+fn decode_cell(value: i32, letters: &[ChipType]) -> Cell {
+    let player = if value > 0 { Player::One } else { Player::Two };
+    match letters.get((value.abs() - 1) as usize) {
+        Some(letter) if value != 0 => Some((player, *letter)),
+        _ => None,
+    }
+}
+
+fn first_player(p1_first: bool) -> Player {
+    if p1_first {
+        Player::One
+    } else {
+        Player::Two
+    }
+}
+
+// Runs the AI search in a Web Worker so that the page keeps animating and taking
+// input while the computer thinks. The worker loads this same script, see main
+pub struct AiWorker {
+    link: AgentLink<AiWorker>,
+}
+
+impl Agent for AiWorker {
+    type Reach = Public;
+    type Message = ();
+    type Input = AiRequest;
+    type Output = AiResponse;
+
+    fn create(link: AgentLink<Self>) -> Self {
+        AiWorker { link }
+    }
+
+    fn update(&mut self, _msg: Self::Message) {}
+
+    fn handle_input(&mut self, msg: Self::Input, who: HandlerId) {
+        let response = match msg {
+            AiRequest::ConnectFour {
+                num_rows,
+                num_cols,
+                grid,
+                p_move,
+                max_ai_depth,
+                p1_first,
+            } => {
+                let mut game = connect_four::Game::new(
+                    num_rows,
+                    num_cols,
+                    true,
+                    "".to_string(),
+                    "".to_string(),
+                    max_ai_depth,
+                );
+                game.grid.items.copy_from_slice(&grid);
+                game.p_move = p_move;
+                game.first_player = first_player(p1_first);
+
+                match game.ai_make_move() {
+                    Ok((_row, _p_move, col, _stats)) => AiResponse::ConnectFour { col },
+                    Err(_) => return,
+                }
+            }
+            AiRequest::TootOtto {
+                num_rows,
+                num_cols,
+                grid,
+                p_move,
+                max_ai_depth,
+                p1_first,
+                p1_word,
+                p2_word,
+                letters,
+                chips_per_letter,
+                p1_chips,
+                p2_chips,
+            } => {
+                let variant = match Variant::new(&p1_word, &p2_word, &letters, chips_per_letter) {
+                    Ok(variant) => variant,
+                    Err(_) => return,
+                };
+                if variant.check_board(num_rows, num_cols).is_err() {
+                    return;
+                }
+                let mut game = toot_otto::Game::new(
+                    num_rows,
+                    num_cols,
+                    true,
+                    "".to_string(),
+                    "".to_string(),
+                    max_ai_depth,
+                    variant,
+                );
+                let letters = game.variant.letters.clone();
+                for (cell, value) in game.grid.items.iter_mut().zip(grid) {
+                    *cell = decode_cell(value, &letters);
+                }
+                game.p_move = p_move;
+                game.first_player = first_player(p1_first);
+                game.p1_chips = Inventory {
+                    counts: letters.iter().cloned().zip(p1_chips).collect(),
+                };
+                game.p2_chips = Inventory {
+                    counts: letters.iter().cloned().zip(p2_chips).collect(),
+                };
+
+                match game.ai_make_move() {
+                    Ok((_row, _p_move, col, chip_type, _stats)) => AiResponse::TootOtto {
+                        letter: chip_type.0,
+                        col,
+                    },
+                    Err(_) => return,
+                }
+            }
+        };
+        self.link.respond(who, response);
+    }
+
+    fn name_of_resource() -> &'static str {
+        "connect-four-frontend.js"
+    }
+}
@@ -1,4 +1,7 @@
-use connect_four_cli::connect_four::{Game, Grid, State};
+use crate::ai_worker::{AiBridge, AiRequest, AiResponse, AiWorker};
+use connect_four_cli::board::Player;
+use connect_four_cli::connect_four::{Evaluator, Game, Grid, State};
+use connect_four_cli::save::SavedGame;
 use serde_json::json;
 use std::cell::RefCell;
 use std::f64::consts::PI;
@@ -8,6 +11,7 @@ use stdweb::unstable::TryInto;
 use stdweb::web::event::{ClickEvent, ResizeEvent};
 use stdweb::web::html_element::{CanvasElement, SelectElement};
 use stdweb::web::{document, window, CanvasRenderingContext2d, FillRule};
+use yew::agent::{Bridge, Bridged};
 use yew::format::Json;
 use yew::services::fetch::{FetchService, FetchTask, Request, Response};
 use yew::{prelude::*, virtual_dom::VNode, Properties};
@@ -18,6 +22,7 @@ pub struct Connect4ComputerModel {
     player1Name: String,
     player2Name: String,
     game: Rc<RefCell<Game>>,
+    worker: AiBridge,
     fetch_service: FetchService,
     fetch_task: Option<FetchTask>,
 }
@@ -40,6 +45,8 @@ pub enum Msg {
     gotPlayer2Name(String),
     startGame,
     clicked(Option<usize>),
+    cancel,
+    aiMoved(AiResponse),
     PostGameSuccess,
     PostGameFailed,
 }
@@ -133,6 +140,21 @@ fn print_win(winner: String) {
     context.restore();
 }
 
+fn print_thinking() {
+    let canvas: CanvasElement = document()
+        .query_selector("#gameboard_c4_ai")
+        .unwrap()
+        .unwrap()
+        .try_into()
+        .unwrap();
+    let context: CanvasRenderingContext2d = canvas.get_context().unwrap();
+    context.save();
+    context.set_font("14pt sans-serif");
+    context.set_fill_style_color("#111");
+    context.fill_text("Computer is thinking...", 130.0, 20.0, None);
+    context.restore();
+}
+
 fn animate(
     column: i64,
     move_val: i64,
@@ -140,15 +162,25 @@ fn animate(
     cur_pos: i64,
     grid: Grid,
     game: Rc<RefCell<Game>>,
+    worker: AiBridge,
 ) {
-    let cur_pos = cur_pos;
-    let mut fg_color = "transparent";
-    if move_val % 2 == 0 {
-        fg_color = "#ff4136";
-    } else if move_val % 2 == 1 {
-        fg_color = "#ffff00";
+    // Game was cancelled
+    if game.borrow().state == State::NonStarted {
+        return;
     }
 
+    let cur_pos = cur_pos;
+    let first_player = game.borrow().first_player;
+    let player = if move_val % 2 == 0 {
+        first_player
+    } else {
+        first_player.other()
+    };
+    let fg_color = match player {
+        Player::One => "#ff4136",
+        Player::Two => "#ffff00",
+    };
+
     if to_row * 75 >= cur_pos {
         clear_canvas();
         draw(
@@ -172,6 +204,7 @@ fn animate(
                 cur_pos + 25.0 as i64,
                 grid.clone(),
                 game,
+                worker,
             )
         });
     } else {
@@ -181,26 +214,18 @@ fn animate(
             game.borrow().grid.num_cols,
         );
         check_for_win(game.clone());
-        let_ai_move(game.clone());
+        let_ai_move(game.clone(), worker);
     }
 }
 
-fn let_ai_move(game: Rc<RefCell<Game>>) {
-    if game.borrow().state == State::Running && game.borrow().player_move_translate() == -1 {
+fn let_ai_move(game: Rc<RefCell<Game>>, worker: AiBridge) {
+    let ai_turn = game.borrow().current_player() == game.borrow().ai_player;
+    if game.borrow().state == State::Running && ai_turn {
         game.borrow_mut().state = State::Busy;
-        // let the computer make move
-        let prev_grid = game.clone().borrow().grid.clone();
-        let insert_result = game.borrow_mut().ai_make_move();
-        if insert_result.is_ok() {
-            animate(
-                insert_result.unwrap().2 as i64,
-                insert_result.unwrap().1 as i64,
-                insert_result.unwrap().0 as i64,
-                0,
-                prev_grid,
-                game.clone(),
-            );
-        }
+        print_thinking();
+        // let the computer make move, the worker answers with Msg::aiMoved
+        let request = AiRequest::connect_four(&game.borrow());
+        worker.borrow_mut().send(request);
     } else if game.borrow().state == State::Busy {
         game.borrow_mut().state = State::Running;
     }
@@ -218,6 +243,43 @@ fn check_for_win(game: Rc<RefCell<Game>>) {
     }
 }
 
+// Side of the computer and the player that moves first, read from the setup form
+fn selected_sides() -> (Player, Player) {
+    let side_box: SelectElement = document()
+        .query_selector("#side_dropdown")
+        .unwrap()
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+    let ai_player = match side_box.value().unwrap().as_str() {
+        "p2" => Player::One,
+        _ => Player::Two,
+    };
+
+    let first_box: SelectElement = document()
+        .query_selector("#first_player_dropdown")
+        .unwrap()
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+    let first_player = match first_box.value().unwrap().as_str() {
+        "computer" => ai_player,
+        "random" => {
+            let heads: bool = js! { return Math.random() < 0.5; }.try_into().unwrap();
+            if heads {
+                ai_player
+            } else {
+                ai_player.other()
+            }
+        }
+        _ => ai_player.other(),
+    };
+
+    return (ai_player, first_player);
+}
+
 fn clear_canvas() {
     let canvas: CanvasElement = document()
         .query_selector("#gameboard_c4_ai")
@@ -230,6 +292,10 @@ fn clear_canvas() {
 }
 
 impl Connect4ComputerModel {
+    fn connect_worker(link: &ComponentLink<Self>) -> Box<dyn Bridge<AiWorker>> {
+        AiWorker::bridge(link.callback(Msg::aiMoved))
+    }
+
     fn is_started(&self) -> bool {
         let state = self.game.clone().borrow().state.clone();
         return match state {
@@ -253,13 +319,19 @@ impl Component for Connect4ComputerModel {
             winner: "".to_string(),
             p_move: 0,
             max_ai_depth: 4,
+            evaluator: Evaluator::Chains,
+            first_player: Player::One,
+            ai_player: Player::Two,
+            history: Vec::new(),
         }));
+        let worker = Rc::new(RefCell::new(Connect4ComputerModel::connect_worker(&link)));
         Connect4ComputerModel {
             link,
             gameStarted: false,
             player1Name: "".to_string(),
             player2Name: "Computer".to_string(),
             game: game.clone(),
+            worker,
             fetch_service: FetchService::new(),
             fetch_task: None,
         }
@@ -305,6 +377,7 @@ impl Component for Connect4ComputerModel {
                     "easy" => 1,
                     "medium" => 2,
                     "hard" => 4,
+                    "expert" => 6,
                     _ => 4,
                 };
 
@@ -316,8 +389,14 @@ impl Component for Connect4ComputerModel {
                     self.player2Name.clone(),
                     max_depth,
                 ));
+                let (ai_player, first_player) = selected_sides();
+                self.game.borrow_mut().set_ai_player(ai_player);
+                self.game.borrow_mut().first_player = first_player;
                 draw_board(self.game.clone());
                 self.game.borrow_mut().start_game();
+
+                // The computer may have the first move
+                let_ai_move(self.game.clone(), self.worker.clone());
             }
             Msg::clicked(col) => {
                 let state = self.game.borrow().state.clone();
@@ -329,7 +408,7 @@ impl Component for Connect4ComputerModel {
                     }
                     State::Running => {
                         if col.is_some()
-                            && self.game.clone().borrow().player_move_translate() == 1
+                            && self.game.borrow().current_player() != self.game.borrow().ai_player
                             /* && col.unwrap() >= 0 */
                             && col.unwrap() < self.game.borrow().grid.num_cols
                         {
@@ -344,6 +423,7 @@ impl Component for Connect4ComputerModel {
                                     0,
                                     prev_grid,
                                     self.game.clone(),
+                                    self.worker.clone(),
                                 );
                             }
                             check_for_win(self.game.clone());
@@ -353,6 +433,36 @@ impl Component for Connect4ComputerModel {
                 }
                 check_for_win(self.game.clone());
             }
+            Msg::cancel => {
+                clear_canvas();
+                self.game.borrow_mut().state = State::NonStarted;
+
+                // Drop the search in progress, its answer goes to the old bridge
+                self.worker
+                    .replace(Connect4ComputerModel::connect_worker(&self.link));
+            }
+            Msg::aiMoved(response) => {
+                if let AiResponse::ConnectFour { col } = response {
+                    if self.game.borrow().state != State::Busy {
+                        return false;
+                    }
+                    let prev_grid = self.game.borrow().grid.clone();
+                    let insert_result = self.game.borrow_mut().make_move(col);
+                    if insert_result.is_ok() {
+                        animate(
+                            col as i64,
+                            insert_result.unwrap().1 as i64,
+                            insert_result.unwrap().0 as i64,
+                            0,
+                            prev_grid,
+                            self.game.clone(),
+                            self.worker.clone(),
+                        );
+                    } else {
+                        self.game.borrow_mut().state = State::Running;
+                    }
+                }
+            }
             Msg::PostGameSuccess => {
                 js! {alert("Game was successfully saved.")}
             }
@@ -426,7 +536,8 @@ impl Component for Connect4ComputerModel {
                             html! {
                                 <div>
                                     <h4>{"New Game: "}{&self.player1Name}{" VS "}{&self.player2Name}</h4>
-                                    <small>{"Disc Colors: "}{&self.player1Name}{" - Red and "}{&self.player2Name}{" - Yellow"}</small>
+                                    <small>{"Disc Colors: "}{&self.game.borrow().p1}{" - Red and "}{&self.game.borrow().p2}{" - Yellow"}</small>
+                                    <button style="margin: 5px" onclick=self.link.callback(|_| Msg::cancel)>{ "Cancel" }</button>
                                 </div>
                             }
                         } else {
@@ -441,10 +552,20 @@ impl Component for Connect4ComputerModel {
                                         <option selected=false disabled=false value="9_7">{"9 x 7"}</option>
                                         <option selected=false disabled=false value="10_7">{"10 x 7"}</option>
                                     </select>
+                                    <select id="side_dropdown" style="margin: 5px">
+                                        <option selected=true disabled=false value="p1">{"Play Red"}</option>
+                                        <option selected=false disabled=false value="p2">{"Play Yellow"}</option>
+                                    </select>
+                                    <select id="first_player_dropdown" style="margin: 5px">
+                                        <option selected=true disabled=false value="human">{"You Move First"}</option>
+                                        <option selected=false disabled=false value="computer">{"Computer Moves First"}</option>
+                                        <option selected=false disabled=false value="random">{"Random First Player"}</option>
+                                    </select>
                                     <select id="difficulty_dropdown" style="margin: 5px">
                                         <option selected=true disabled=false value="easy">{"Easy"}</option>
                                         <option selected=false disabled=false value="medium">{"Medium"}</option>
                                         <option selected=false disabled=false value="hard">{"Hard"}</option>
+                                        <option selected=false disabled=false value="expert">{"Expert"}</option>
                                     </select>
                                     <button style="margin: 5px" onclick=self.link.callback(|_| Msg::startGame)>{ "Start Game" }</button>
                                 </div>
@@ -459,12 +580,18 @@ impl Component for Connect4ComputerModel {
 }
 
 impl Connect4ComputerModel {
+    // Send the moves along with the result, the backend plays them again to check it
     fn post_win(&mut self) {
+        let saved = SavedGame::from_connect_four(&self.game.borrow());
         let json_sg = json!({
             "gameType": "Connect4 with Computer",
-            "Player1Name": self.player1Name,
-            "Player2Name": self.player2Name,
+            "Player1Name": saved.p1,
+            "Player2Name": saved.p2,
             "WinnerName": self.game.clone().borrow_mut().winner.clone(),
+            "rows": saved.rows,
+            "cols": saved.cols,
+            "moves": saved.moves,
+            "first_player": saved.first_player,
         });
         let callback = self.link.callback(|response: Response<Result<String, _>>| {
             if response.status().is_success() {
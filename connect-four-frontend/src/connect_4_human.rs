@@ -19,7 +19,9 @@ use stdweb::web::{document, window, CanvasRenderingContext2d, FillRule};
 
 use stdweb::web::event::{ClickEvent, ResizeEvent};
 
-use connect_four_cli::connect_four::{Game, Grid, State};
+use connect_four_cli::board::Player;
+use connect_four_cli::connect_four::{Evaluator, Game, Grid, State};
+use connect_four_cli::save::SavedGame;
 use std::cell::RefCell;
 use std::f64::consts::PI;
 use std::rc::Rc;
@@ -228,6 +230,10 @@ impl Component for Connect4HumanModel {
             winner: "".to_string(),
             p_move: 0,
             max_ai_depth: 4,
+            evaluator: Evaluator::Chains,
+            first_player: Player::One,
+            ai_player: Player::Two,
+            history: Vec::new(),
         }));
         Connect4HumanModel {
             link,
@@ -414,12 +420,18 @@ impl Component for Connect4HumanModel {
 }
 
 impl Connect4HumanModel {
+    // Send the moves along with the result, the backend plays them again to check it
     fn post_win(&mut self) {
+        let saved = SavedGame::from_connect_four(&self.game.borrow());
         let json_sg = json!({
             "gameType": "Connect4 with Human",
-            "Player1Name": self.player1Name,
-            "Player2Name": self.player2Name,
+            "Player1Name": saved.p1,
+            "Player2Name": saved.p2,
             "WinnerName": self.game.clone().borrow_mut().winner.clone(),
+            "rows": saved.rows,
+            "cols": saved.cols,
+            "moves": saved.moves,
+            "first_player": saved.first_player,
         });
         let callback = self.link.callback(|response: Response<Result<String, _>>| {
             if response.status().is_success() {
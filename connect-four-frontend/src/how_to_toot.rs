@@ -35,9 +35,10 @@ impl Component for HowToTootModel {
                 <div><h5>{"To play TOOT-OTTO follow the following steps:"}</h5></div>
                 <ul>
                     <li>{"A new game describes which player is TOOT and which is OTTO"}</li>
-                    <li>{"Select the disc type T or O that you want to place"}</li>
+                    <li>{"Select the disc type T or O that you want to place, each player has six of each"}</li>
                     <li>{"Click on the desired column on the game board to place your disc"}</li>
                     <li>{"Try to spell TOOT or OTTO based on your winning combination, either horizontally or vertically or diagonally"}</li>
+                    <li>{"If both players run out of discs before a word is spelled, the game is a draw"}</li>
                 </ul>
                 <br />
                 {"For More information on TOOT-OTTO click "}<a href="https://boardgamegeek.com/boardgame/19530/toot-and-otto">{"here"}</a>
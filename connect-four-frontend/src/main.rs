@@ -6,6 +6,7 @@
 extern crate stdweb;
 extern crate connect_four_cli;
 
+mod ai_worker;
 mod connect_4_computer;
 mod connect_4_human;
 mod how_to_connect_4;
@@ -16,28 +17,44 @@ mod toot_otto_computer;
 mod toot_otto_human;
 mod welcome;
 
+use yew::agent::Threaded;
 use yew::prelude::*;
+use yew::services::fetch::Response;
 
 use yew_router::{prelude::*, Switch};
 
 use crate::{
-    connect_4_computer::Connect4ComputerModel, connect_4_human::Connect4HumanModel,
-    how_to_connect_4::HowToConnect4Model, how_to_toot::HowToTootModel, scoreboard::ScoreBoardModel,
-    scores::ScoresModel, toot_otto_computer::TootOttoComputerModel,
-    toot_otto_human::TootOttoHumanModel, welcome::WelcomeModel,
+    ai_worker::AiWorker, connect_4_computer::Connect4ComputerModel,
+    connect_4_human::Connect4HumanModel, how_to_connect_4::HowToConnect4Model,
+    how_to_toot::HowToTootModel, scoreboard::ScoreBoardModel, scores::ScoresModel,
+    toot_otto_computer::TootOttoComputerModel, toot_otto_human::TootOttoHumanModel,
+    welcome::WelcomeModel,
 };
 use yew::virtual_dom::VNode;
 use yew_router::switch::Permissive;
 
 use chrono::serde::ts_milliseconds;
 use chrono::{DateTime, Utc};
+use connect_four_cli::save::SavedVariant;
 use serde::{Deserialize, Serialize};
+use stdweb::unstable::TryInto;
 
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
 fn main() {
     yew::initialize();
+
+    // The AI worker loads this same script, it only has to serve AI requests
+    let in_worker: bool = js! { return typeof window === "undefined"; }
+        .try_into()
+        .unwrap();
+    if in_worker {
+        AiWorker::register();
+        yew::run_loop();
+        return;
+    }
+
     web_logger::init();
     js! {
         window.wasmReady();
@@ -55,6 +72,12 @@ pub struct SerializableGame {
     pub Player1Name: String,
     pub Player2Name: String,
     pub WinnerName: String,
+    pub rows: u32,
+    pub cols: u32,
+    pub moves: Vec<String>,
+    pub first_player: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<SavedVariant>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -67,6 +90,28 @@ pub struct Game {
     pub GameDate: DateTime<Utc>,
 }
 
+// Number of games that match a request to /games, whatever page was asked for
+pub fn total_count<T>(response: &Response<T>) -> Option<u64> {
+    response
+        .headers()
+        .get("X-Total-Count")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+}
+
+// Value for a query string, letters and digits stay and everything else is encoded
+pub fn encode_query(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct PlayerWins {
     pub _id: String,
@@ -1,21 +1,41 @@
-use crate::Game;
+use crate::{encode_query, total_count, Game};
+use stdweb::traits::*;
+use stdweb::unstable::TryInto;
+use stdweb::web::document;
+use stdweb::web::html_element::SelectElement;
 use yew::format::{Json, Nothing};
 use yew::services::fetch::{FetchService, FetchTask, Request, Response};
 use yew::{prelude::*, virtual_dom::VNode, Properties};
 
+// Games listed on a page of the history
+const PAGE_SIZE: u64 = 20;
+
 pub struct ScoreBoardModel {
     link: ComponentLink<Self>,
     games: Option<Vec<Game>>,
     fetch_service: FetchService,
     fetch_task: Option<FetchTask>,
+
+    // Page shown counting from 1 and the number of games that match the filters
+    page: u64,
+    total: u64,
+
+    // Filters of the form, empty for all games
+    gameTypeFilter: String,
+    opponentFilter: String,
+    playerFilter: String,
 }
 
 #[derive(Clone, PartialEq, Properties)]
 pub struct Props {}
 
 pub enum Msg {
-    FetchGamesComplete(Vec<Game>),
+    FetchGamesComplete(Vec<Game>, u64),
     FetchGamesFailed,
+    gotPlayerFilter(String),
+    applyFilters,
+    previousPage,
+    nextPage,
 }
 
 impl Component for ScoreBoardModel {
@@ -28,17 +48,45 @@ impl Component for ScoreBoardModel {
             games: None,
             fetch_service: FetchService::new(),
             fetch_task: None,
+            page: 1,
+            total: 0,
+            gameTypeFilter: String::new(),
+            opponentFilter: String::new(),
+            playerFilter: String::new(),
         }
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
-            Msg::FetchGamesComplete(body) => {
+            Msg::FetchGamesComplete(body, total) => {
                 self.games = Some(body);
+                self.total = total;
             }
             Msg::FetchGamesFailed => {
                 js! {alert("Failed to load data...")}
             }
+            Msg::gotPlayerFilter(name) => {
+                self.playerFilter = name;
+                return false;
+            }
+            Msg::applyFilters => {
+                self.gameTypeFilter = selected_value("#game_type_filter");
+                self.opponentFilter = selected_value("#opponent_filter");
+                self.page = 1;
+                self.fetch_games();
+            }
+            Msg::previousPage => {
+                if self.page > 1 {
+                    self.page -= 1;
+                    self.fetch_games();
+                }
+            }
+            Msg::nextPage => {
+                if self.page < self.page_count() {
+                    self.page += 1;
+                    self.fetch_games();
+                }
+            }
         }
         true
     }
@@ -48,30 +96,33 @@ impl Component for ScoreBoardModel {
     }
 
     fn mounted(&mut self) -> ShouldRender {
-        let get_request = Request::get("http://localhost:8000/games")
-            .body(Nothing)
-            .unwrap();
-        let callback = self
-            .link
-            .callback(|response: Response<Json<Result<Vec<Game>, _>>>| {
-                if let (meta, Json(Ok(body))) = response.into_parts() {
-                    if meta.status.is_success() {
-                        return Msg::FetchGamesComplete(body);
-                    }
-                }
-                Msg::FetchGamesFailed
-            });
-
-        let task = self.fetch_service.fetch(get_request, callback);
-        self.fetch_task = Some(task.unwrap());
+        self.fetch_games();
         true
     }
 
     fn view(&self) -> VNode {
+        let first_page = self.page <= 1;
+        let last_page = self.page >= self.page_count();
         html! {
             <div class="w3-container" style="margin-top:75px">
                 <h5 class="w3-xxxlarge w3-text-red"><b>{"Game History"}</b></h5>
                 <hr style="width:50px;border:5px solid red" class="w3-round" />
+                <div>
+                    <select id="game_type_filter" style="margin: 5px">
+                        <option selected=true disabled=false value="">{"All Games"}</option>
+                        <option selected=false disabled=false value="Connect4 with Human">{"Connect4 with Human"}</option>
+                        <option selected=false disabled=false value="Connect4 with Computer">{"Connect4 with Computer"}</option>
+                        <option selected=false disabled=false value="TOOT-OTTO with Human">{"TOOT-OTTO with Human"}</option>
+                        <option selected=false disabled=false value="TOOT-OTTO with Computer">{"TOOT-OTTO with Computer"}</option>
+                    </select>
+                    <select id="opponent_filter" style="margin: 5px">
+                        <option selected=true disabled=false value="">{"Any Opponent"}</option>
+                        <option selected=false disabled=false value="human">{"Against Humans"}</option>
+                        <option selected=false disabled=false value="computer">{"Against the Computer"}</option>
+                    </select>
+                    <input style="margin: 5px" type="text" placeholder="Player Name" oninput=self.link.callback(|e: InputData| Msg::gotPlayerFilter(e.value)) />
+                    <button style="margin: 5px" onclick=self.link.callback(|_| Msg::applyFilters)>{ "Filter" }</button>
+                </div>
                 <div id="game-stream">
                     <table>
                         <tr>
@@ -91,6 +142,11 @@ impl Component for ScoreBoardModel {
                         }
                     </table>
                 </div>
+                <div>
+                    <button style="margin: 5px" disabled=first_page onclick=self.link.callback(|_| Msg::previousPage)>{ "Previous" }</button>
+                    {format!("Page {} of {}, {} games", self.page, self.page_count(), self.total)}
+                    <button style="margin: 5px" disabled=last_page onclick=self.link.callback(|_| Msg::nextPage)>{ "Next" }</button>
+                </div>
             </div>
         }
     }
@@ -108,4 +164,51 @@ impl ScoreBoardModel {
             </tr>
         }
     }
+
+    fn page_count(&self) -> u64 {
+        ((self.total + PAGE_SIZE - 1) / PAGE_SIZE).max(1)
+    }
+
+    // Ask the backend for the current page, newest games first
+    fn fetch_games(&mut self) {
+        let mut url = format!(
+            "http://localhost:8000/games?sort=newest&page={}&limit={}",
+            self.page, PAGE_SIZE
+        );
+        if !self.gameTypeFilter.is_empty() {
+            url += &format!("&game_type={}", encode_query(&self.gameTypeFilter));
+        }
+        if !self.opponentFilter.is_empty() {
+            url += &format!("&opponent={}", encode_query(&self.opponentFilter));
+        }
+        if !self.playerFilter.trim().is_empty() {
+            url += &format!("&player={}", encode_query(self.playerFilter.trim()));
+        }
+
+        let get_request = Request::get(url.as_str()).body(Nothing).unwrap();
+        let callback = self
+            .link
+            .callback(|response: Response<Json<Result<Vec<Game>, _>>>| {
+                let total = total_count(&response);
+                if let (meta, Json(Ok(body))) = response.into_parts() {
+                    if meta.status.is_success() {
+                        return Msg::FetchGamesComplete(body, total.unwrap_or(0));
+                    }
+                }
+                Msg::FetchGamesFailed
+            });
+
+        let task = self.fetch_service.fetch(get_request, callback);
+        self.fetch_task = Some(task.unwrap());
+    }
+}
+
+fn selected_value(selector: &str) -> String {
+    let select: SelectElement = document()
+        .query_selector(selector)
+        .unwrap()
+        .unwrap()
+        .try_into()
+        .unwrap();
+    select.value().unwrap_or_default()
 }
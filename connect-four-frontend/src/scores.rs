@@ -1,22 +1,35 @@
-use crate::{Game, PlayerWins};
+use crate::{total_count, Game, PlayerWins};
 use yew::format::{Json, Nothing};
 use yew::services::fetch::{FetchService, FetchTask, Request, Response};
 use yew::{prelude::*, virtual_dom::VNode, Properties};
 
+// Games the computer won that are listed, the latest first
+const COMPUTER_WINS_LISTED: u64 = 20;
+
+// Totals of the first table that are fetched without the games
+#[derive(Clone, Copy)]
+pub enum Count {
+    Played,
+    AgainstComputer,
+}
+
 pub struct ScoresModel {
     link: ComponentLink<Self>,
-    games: Option<Vec<Game>>,
+    gamesPlayed: Option<u64>,
+    gamesAgainstComputer: Option<u64>,
+    gamesWonByComputer: Option<u64>,
+    computerWins: Option<Vec<Game>>,
     gamesWonPerPlayer: Option<Vec<PlayerWins>>,
     fetch_service: FetchService,
-    fetch_task: Option<FetchTask>,
-    fetch_task2: Option<FetchTask>,
+    fetch_tasks: Vec<FetchTask>,
 }
 
 #[derive(Clone, PartialEq, Properties)]
 pub struct Props {}
 
 pub enum Msg {
-    FetchGamesComplete(Vec<Game>),
+    FetchCountComplete(Count, u64),
+    FetchGamesComplete(Vec<Game>, u64),
     FetchGamesFailed,
     FetchPlayerWinsComplete(Vec<PlayerWins>),
     FetchPlayerWinsFailed,
@@ -29,18 +42,25 @@ impl Component for ScoresModel {
     fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
         ScoresModel {
             link,
-            games: None,
+            gamesPlayed: None,
+            gamesAgainstComputer: None,
+            gamesWonByComputer: None,
+            computerWins: None,
             gamesWonPerPlayer: None,
             fetch_service: FetchService::new(),
-            fetch_task: None,
-            fetch_task2: None,
+            fetch_tasks: Vec::new(),
         }
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
-            Msg::FetchGamesComplete(body) => {
-                self.games = Some(body);
+            Msg::FetchCountComplete(count, total) => match count {
+                Count::Played => self.gamesPlayed = Some(total),
+                Count::AgainstComputer => self.gamesAgainstComputer = Some(total),
+            },
+            Msg::FetchGamesComplete(body, total) => {
+                self.computerWins = Some(body);
+                self.gamesWonByComputer = Some(total);
             }
             Msg::FetchGamesFailed => {
                 js! {alert("Failed to load game data...")}
@@ -60,22 +80,32 @@ impl Component for ScoresModel {
     }
 
     fn mounted(&mut self) -> ShouldRender {
-        let get_request = Request::get("http://localhost:8000/games")
-            .body(Nothing)
-            .unwrap();
+        self.fetch_count(Count::Played, "limit=0");
+        self.fetch_count(Count::AgainstComputer, "opponent=computer&limit=0");
+
+        let get_request = Request::get(
+            format!(
+                "http://localhost:8000/games?winner=Computer&sort=newest&limit={}",
+                COMPUTER_WINS_LISTED
+            )
+            .as_str(),
+        )
+        .body(Nothing)
+        .unwrap();
         let callback = self
             .link
             .callback(|response: Response<Json<Result<Vec<Game>, _>>>| {
+                let total = total_count(&response);
                 if let (meta, Json(Ok(body))) = response.into_parts() {
                     if meta.status.is_success() {
-                        return Msg::FetchGamesComplete(body);
+                        return Msg::FetchGamesComplete(body, total.unwrap_or(0));
                     }
                 }
                 Msg::FetchGamesFailed
             });
 
         let task = self.fetch_service.fetch(get_request, callback);
-        self.fetch_task = Some(task.unwrap());
+        self.fetch_tasks.push(task.unwrap());
 
         let get_request_wins = Request::get("http://localhost:8000/wins")
             .body(Nothing)
@@ -92,7 +122,7 @@ impl Component for ScoresModel {
                 });
 
         let task2 = self.fetch_service.fetch(get_request_wins, callback_wins);
-        self.fetch_task2 = Some(task2.unwrap());
+        self.fetch_tasks.push(task2.unwrap());
         true
     }
 
@@ -110,20 +140,11 @@ impl Component for ScoresModel {
                         <th>{"Games Against Computer"}</th>
                         <th>{"Games Computer Won"}</th>
                     </tr>
-                    {
-                        match &self.games {
-                            None => html!{{"Loading..."}},
-                            Some(games) => {
-                                html!{
-                                    <tr>
-                                        <td>{games.len()}</td>
-                                        <td>{games.into_iter().filter(|game| game.Player2Name == "Computer").count()}</td>
-                                        <td>{games.into_iter().filter(|game| game.WinnerName == "Computer").count()}</td>
-                                    </tr>
-                                }
-                            }
-                        }
-                    }
+                    <tr>
+                        <td>{count_text(self.gamesPlayed)}</td>
+                        <td>{count_text(self.gamesAgainstComputer)}</td>
+                        <td>{count_text(self.gamesWonByComputer)}</td>
+                    </tr>
                 </table>
                 <div>
                     <h4>{"Details of Games Won by Computer"}</h4>
@@ -136,15 +157,10 @@ impl Component for ScoresModel {
                         <th>{"When Played"}</th>
                     </tr>
                     {
-                        match &self.games {
+                        match &self.computerWins {
                             None => html!{{"Loading..."}},
                             Some(games) => {
-                                games.into_iter().filter_map(|game|
-                                    if(game.WinnerName == "Computer"){
-                                        return Some(self.view_row_computer_won(game));
-                                    } else {
-                                        return None;
-                                    }).collect::<Html>()
+                                games.into_iter().map(|game| self.view_row_computer_won(game)).collect::<Html>()
                             }
                         }
                     }
@@ -174,6 +190,24 @@ impl Component for ScoresModel {
 }
 
 impl ScoresModel {
+    // Ask the backend how many games match the query, without listing them
+    fn fetch_count(&mut self, count: Count, query: &str) {
+        let get_request = Request::get(format!("http://localhost:8000/games?{}", query).as_str())
+            .body(Nothing)
+            .unwrap();
+        let callback = self
+            .link
+            .callback(move |response: Response<Result<String, _>>| {
+                match (response.status().is_success(), total_count(&response)) {
+                    (true, Some(total)) => Msg::FetchCountComplete(count, total),
+                    _ => Msg::FetchGamesFailed,
+                }
+            });
+
+        let task = self.fetch_service.fetch(get_request, callback);
+        self.fetch_tasks.push(task.unwrap());
+    }
+
     fn view_row_computer_won(&self, game: &Game) -> Html {
         html! {
             <tr>
@@ -194,3 +228,10 @@ impl ScoresModel {
         }
     }
 }
+
+fn count_text(count: Option<u64>) -> String {
+    match count {
+        Some(count) => count.to_string(),
+        None => "Loading...".to_string(),
+    }
+}
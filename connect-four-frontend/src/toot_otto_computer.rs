@@ -1,4 +1,9 @@
-use connect_four_cli::toot_otto::{ChipType, DummyGrid, Game, Grid, State};
+use crate::ai_worker::{AiBridge, AiRequest, AiResponse, AiWorker};
+use connect_four_cli::board::Player;
+use connect_four_cli::save::SavedGame;
+use connect_four_cli::toot_otto::{
+    ChipType, Evaluator, Game, Grid, Inventory, SpelledWord, State, Variant,
+};
 use serde_json::json;
 use std::cell::RefCell;
 use std::f64::consts::PI;
@@ -8,6 +13,7 @@ use stdweb::unstable::TryInto;
 use stdweb::web::event::{ClickEvent, ResizeEvent};
 use stdweb::web::html_element::{CanvasElement, SelectElement};
 use stdweb::web::{document, window, CanvasRenderingContext2d, FillRule};
+use yew::agent::{Bridge, Bridged};
 use yew::format::Json;
 use yew::services::fetch::{FetchService, FetchTask, Request, Response};
 use yew::{prelude::*, virtual_dom::VNode, Properties};
@@ -18,6 +24,7 @@ pub struct TootOttoComputerModel {
     player1Name: String,
     player2Name: String,
     game: Rc<RefCell<Game>>,
+    worker: AiBridge,
     fetch_service: FetchService,
     fetch_task: Option<FetchTask>,
 }
@@ -40,6 +47,8 @@ pub enum Msg {
     gotPlayer2Name(String),
     startGame,
     clicked(Option<usize>),
+    cancel,
+    aiMoved(AiResponse),
     PostGameSuccess,
     PostGameFailed,
 }
@@ -72,22 +81,18 @@ fn draw_board(game: Rc<RefCell<Game>>) {
     context.restore();
 }
 
-fn draw(grid: &Grid, dummy_grid: &DummyGrid, num_rows: usize, num_cols: usize) {
+fn draw(grid: &Grid, num_rows: usize, num_cols: usize) {
     for y in 0..num_rows {
         for x in 0..num_cols {
-            let mut text = "";
+            let mut text = "".to_string();
             let mut fg_color = "transparent";
 
-            if grid.get(y, x) >= 1 {
-                fg_color = "#ff4136";
-            } else if grid.get(y, x) <= -1 {
-                fg_color = "#ffff00";
-            }
-
-            if dummy_grid.get(y, x) == 1 {
-                text = "T";
-            } else if dummy_grid.get(y, x) == -1 {
-                text = "O";
+            if let Some((player, chip_type)) = grid.get(y, x) {
+                fg_color = match player {
+                    Player::One => "#ff4136",
+                    Player::Two => "#ffff00",
+                };
+                text = chip_type.to_string();
             }
 
             draw_circle(
@@ -96,7 +101,7 @@ fn draw(grid: &Grid, dummy_grid: &DummyGrid, num_rows: usize, num_cols: usize) {
                 25.0,
                 fg_color.to_string(),
                 "black".to_string(),
-                text.to_string(),
+                text,
             );
         }
     }
@@ -122,7 +127,7 @@ fn draw_circle(x: f64, y: f64, r: f64, fill: String, stroke: String, text: Strin
     context.fill_text(text.as_str(), x - 8.5, y + 8.0, None);
 }
 
-fn print_win(winner: String) {
+fn print_win(winner: String, words: &[SpelledWord]) {
     let canvas: CanvasElement = document()
         .query_selector("#gameboard_toot_ai")
         .unwrap()
@@ -131,6 +136,16 @@ fn print_win(winner: String) {
         .unwrap();
     let context: CanvasRenderingContext2d = canvas.get_context().unwrap();
     let mut msg = "".to_string();
+
+    // Every word the last chip spelled, both of them when the game is drawn that way
+    if !words.is_empty() {
+        let spelled = words
+            .iter()
+            .map(|w| w.word.to_string())
+            .collect::<Vec<String>>();
+        msg.push_str(spelled.join(" and ").as_str());
+        msg.push_str(" - ");
+    }
     if winner == "Draw" {
         msg.push_str("It's a draw");
     } else {
@@ -145,29 +160,52 @@ fn print_win(winner: String) {
     context.restore();
 }
 
+fn print_thinking() {
+    let canvas: CanvasElement = document()
+        .query_selector("#gameboard_toot_ai")
+        .unwrap()
+        .unwrap()
+        .try_into()
+        .unwrap();
+    let context: CanvasRenderingContext2d = canvas.get_context().unwrap();
+    context.save();
+    context.set_font("14pt sans-serif");
+    context.set_fill_style_color("#111");
+    context.fill_text("Computer is thinking...", 130.0, 20.0, None);
+    context.restore();
+}
+
 fn animate(
     column: i64,
     move_val: i64,
     to_row: i64,
     cur_pos: i64,
     grid: Grid,
-    dummy_grid: DummyGrid,
     game: Rc<RefCell<Game>>,
     text: String,
+    worker: AiBridge,
 ) {
-    let cur_pos = cur_pos;
-    let mut fg_color = "transparent";
-    if move_val % 2 == 0 {
-        fg_color = "#ff4136";
-    } else if move_val % 2 == 1 {
-        fg_color = "#ffff00";
+    // Game was cancelled
+    if game.borrow().state == State::NonStarted {
+        return;
     }
 
+    let cur_pos = cur_pos;
+    let first_player = game.borrow().first_player;
+    let player = if move_val % 2 == 0 {
+        first_player
+    } else {
+        first_player.other()
+    };
+    let fg_color = match player {
+        Player::One => "#ff4136",
+        Player::Two => "#ffff00",
+    };
+
     if to_row * 75 >= cur_pos {
         clear_canvas();
         draw(
             &grid.clone(),
-            &dummy_grid.clone(),
             game.borrow().grid.num_rows,
             game.borrow().grid.num_cols,
         );
@@ -188,49 +226,30 @@ fn animate(
                 to_row,
                 cur_pos + 25.0 as i64,
                 grid.clone(),
-                dummy_grid.clone(),
                 game,
                 my_text,
+                worker,
             )
         });
     } else {
         draw(
             &grid.clone(),
-            &dummy_grid.clone(),
             game.borrow().grid.num_rows,
             game.borrow().grid.num_cols,
         );
         check_for_win(game.clone());
-        let_ai_move(game.clone());
+        let_ai_move(game.clone(), worker);
     }
 }
 
-fn let_ai_move(game: Rc<RefCell<Game>>) {
-    if game.borrow().state == State::Running && game.borrow().player_move_translate() == -1 {
+fn let_ai_move(game: Rc<RefCell<Game>>, worker: AiBridge) {
+    let ai_turn = game.borrow().current_player() == game.borrow().ai_player;
+    if game.borrow().state == State::Running && ai_turn {
         game.borrow_mut().state = State::Busy;
-        // let the computer make move
-        let prev_grid = game.clone().borrow().grid.clone();
-        let prev_dummy_grid = game.clone().borrow().dummy_grid.clone();
-        let insert_result = game.borrow_mut().ai_make_move();
-        if insert_result.is_ok() {
-            let mut text = "";
-            if insert_result.unwrap().3 == 1 {
-                text = "T";
-            } else if insert_result.unwrap().3 == -1 {
-                text = "O";
-            }
-
-            animate(
-                insert_result.unwrap().2 as i64,
-                insert_result.unwrap().1 as i64,
-                insert_result.unwrap().0 as i64,
-                0,
-                prev_grid,
-                prev_dummy_grid,
-                game.clone(),
-                text.to_string(),
-            );
-        }
+        print_thinking();
+        // let the computer make move, the worker answers with Msg::aiMoved
+        let request = AiRequest::toot_otto(&game.borrow());
+        worker.borrow_mut().send(request);
     } else if game.borrow().state == State::Busy {
         game.borrow_mut().state = State::Running;
     }
@@ -242,12 +261,50 @@ fn check_for_win(game: Rc<RefCell<Game>>) {
     match state {
         State::Done => {
             // draw finished
-            print_win(game.clone().borrow_mut().winner.clone());
+            let winner = game.borrow().winner.clone();
+            print_win(winner, &game.borrow().words);
         }
         _ => {}
     }
 }
 
+// Side of the computer and the player that moves first, read from the setup form
+fn selected_sides() -> (Player, Player) {
+    let side_box: SelectElement = document()
+        .query_selector("#side_dropdown")
+        .unwrap()
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+    let ai_player = match side_box.value().unwrap().as_str() {
+        "p2" => Player::One,
+        _ => Player::Two,
+    };
+
+    let first_box: SelectElement = document()
+        .query_selector("#first_player_dropdown")
+        .unwrap()
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+    let first_player = match first_box.value().unwrap().as_str() {
+        "computer" => ai_player,
+        "random" => {
+            let heads: bool = js! { return Math.random() < 0.5; }.try_into().unwrap();
+            if heads {
+                ai_player
+            } else {
+                ai_player.other()
+            }
+        }
+        _ => ai_player.other(),
+    };
+
+    return (ai_player, first_player);
+}
+
 fn clear_canvas() {
     let canvas: CanvasElement = document()
         .query_selector("#gameboard_toot_ai")
@@ -260,6 +317,30 @@ fn clear_canvas() {
 }
 
 impl TootOttoComputerModel {
+    fn connect_worker(link: &ComponentLink<Self>) -> Box<dyn Bridge<AiWorker>> {
+        AiWorker::bridge(link.callback(Msg::aiMoved))
+    }
+
+    fn tiles_left(&self) -> String {
+        let game = self.game.borrow();
+        format!(
+            "Tiles left: {} - {} and {} - {}",
+            game.p1, game.p1_chips, game.p2, game.p2_chips
+        )
+    }
+
+    // Options of the letter picker, one per letter of the game
+    fn letter_options(&self) -> Html {
+        let letters = self.game.borrow().variant.letters.clone();
+        html! {
+            <>
+                { for letters.iter().map(|letter| html! {
+                    <option value=letter.to_string()>{letter.to_string()}</option>
+                }) }
+            </>
+        }
+    }
+
     fn is_started(&self) -> bool {
         let state = self.game.clone().borrow().state.clone();
         return match state {
@@ -274,9 +355,9 @@ impl Component for TootOttoComputerModel {
     type Properties = Props;
 
     fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let variant = Variant::toot_otto();
         let game = Rc::new(RefCell::new(Game {
             grid: Grid::new(6, 7),
-            dummy_grid: DummyGrid::new(6, 7),
             p1: "".to_string(),
             p2: "".to_string(),
             with_ai: false,
@@ -284,13 +365,23 @@ impl Component for TootOttoComputerModel {
             winner: "".to_string(),
             p_move: 0,
             max_ai_depth: 4,
+            p1_chips: Inventory::new(&variant.letters, variant.chips_per_letter),
+            p2_chips: Inventory::new(&variant.letters, variant.chips_per_letter),
+            evaluator: Evaluator::Patterns,
+            variant,
+            words: Vec::new(),
+            first_player: Player::One,
+            ai_player: Player::Two,
+            history: Vec::new(),
         }));
+        let worker = Rc::new(RefCell::new(TootOttoComputerModel::connect_worker(&link)));
         TootOttoComputerModel {
             link,
             gameStarted: false,
             player1Name: "".to_string(),
             player2Name: "Computer".to_string(),
             game: game.clone(),
+            worker,
             fetch_service: FetchService::new(),
             fetch_task: None,
         }
@@ -336,6 +427,7 @@ impl Component for TootOttoComputerModel {
                     "easy" => 1,
                     "medium" => 2,
                     "hard" => 4,
+                    "expert" => 5,
                     _ => 4,
                 };
 
@@ -346,9 +438,16 @@ impl Component for TootOttoComputerModel {
                     self.player1Name.clone(),
                     self.player2Name.clone(),
                     max_depth,
+                    Variant::toot_otto(),
                 ));
+                let (ai_player, first_player) = selected_sides();
+                self.game.borrow_mut().set_ai_player(ai_player);
+                self.game.borrow_mut().first_player = first_player;
                 draw_board(self.game.clone());
                 self.game.borrow_mut().start_game();
+
+                // The computer may have the first move
+                let_ai_move(self.game.clone(), self.worker.clone());
             }
             Msg::clicked(col) => {
                 let state = self.game.borrow().state.clone();
@@ -360,12 +459,11 @@ impl Component for TootOttoComputerModel {
                     }
                     State::Running => {
                         if col.is_some()
-                            && self.game.clone().borrow().player_move_translate() == 1
+                            && self.game.borrow().current_player() != self.game.borrow().ai_player
                             /* && col.unwrap() >= 0 */
                             && col.unwrap() < self.game.borrow().grid.num_cols
                         {
                             let prev_grid = self.game.borrow().grid.clone();
-                            let prev_dummy_grid = self.game.borrow().dummy_grid.clone();
 
                             // Chip type
                             let sel_box: SelectElement = document()
@@ -375,24 +473,23 @@ impl Component for TootOttoComputerModel {
                                 .try_into()
                                 .unwrap();
 
-                            let chip_type = match sel_box.value().unwrap().as_str() {
-                                "chip_t" => ChipType::T,
-                                "chip_o" => ChipType::O,
-                                _ => panic!(),
+                            let chip_type = match sel_box.value().unwrap().chars().next() {
+                                Some(letter) => ChipType(letter),
+                                None => panic!(),
                             };
 
+                            if self.game.borrow().current_chips().remaining(chip_type) == 0 {
+                                js! {alert("No tiles of that letter left, play another letter.")}
+                                return false;
+                            }
+
                             let insert_result = self
                                 .game
                                 .borrow_mut()
                                 .make_move(chip_type, col.unwrap() as usize);
 
                             if insert_result.is_ok() {
-                                let mut text = "";
-                                if insert_result.unwrap().2 == 1 {
-                                    text = "T";
-                                } else if insert_result.unwrap().2 == -1 {
-                                    text = "O";
-                                }
+                                let text = insert_result.unwrap().2;
 
                                 animate(
                                     col.unwrap() as i64,
@@ -400,9 +497,9 @@ impl Component for TootOttoComputerModel {
                                     insert_result.unwrap().0 as i64,
                                     0,
                                     prev_grid,
-                                    prev_dummy_grid,
                                     self.game.clone(),
                                     text.to_string(),
+                                    self.worker.clone(),
                                 );
                             }
                             check_for_win(self.game.clone());
@@ -412,6 +509,40 @@ impl Component for TootOttoComputerModel {
                 }
                 check_for_win(self.game.clone());
             }
+            Msg::cancel => {
+                clear_canvas();
+                self.game.borrow_mut().state = State::NonStarted;
+
+                // Drop the search in progress, its answer goes to the old bridge
+                self.worker
+                    .replace(TootOttoComputerModel::connect_worker(&self.link));
+            }
+            Msg::aiMoved(response) => {
+                if let AiResponse::TootOtto { letter, col } = response {
+                    if self.game.borrow().state != State::Busy {
+                        return false;
+                    }
+                    let prev_grid = self.game.borrow().grid.clone();
+                    let chip_type = ChipType(letter);
+                    let insert_result = self.game.borrow_mut().make_move(chip_type, col);
+                    if insert_result.is_ok() {
+                        let text = insert_result.unwrap().2;
+
+                        animate(
+                            col as i64,
+                            insert_result.unwrap().1 as i64,
+                            insert_result.unwrap().0 as i64,
+                            0,
+                            prev_grid,
+                            self.game.clone(),
+                            text.to_string(),
+                            self.worker.clone(),
+                        );
+                    } else {
+                        self.game.borrow_mut().state = State::Running;
+                    }
+                }
+            }
             Msg::PostGameSuccess => {
                 js! {alert("Game was successfully saved.")}
             }
@@ -484,13 +615,14 @@ impl Component for TootOttoComputerModel {
                     html! {
                     <div>
                         <h4>{"New Game: "} {&self.player1Name} {" VS "} {&self.player2Name}</h4>
-                        <small>{"Winning Combination: "} {&self.player1Name} {" - TOOT and "} {&self.player2Name} {" - OTTO"}</small>
+                        <small>{"Winning Combination: "} {&self.game.borrow().p1} {" - "} {self.game.borrow().variant.word_string(Player::One)} {" and "} {&self.game.borrow().p2} {" - "} {self.game.borrow().variant.word_string(Player::Two)}</small>
+                        <p>{self.tiles_left()}</p>
                         <p>
                             {"Select a Disc Type: "}
                             <select id="chip_type_dropdown" style="margin: 5px">
-                                <option selected=true disabled=false value="chip_t">{"T"}</option>
-                                <option selected=false disabled=false value="chip_o">{"O"}</option>
+                                {self.letter_options()}
                             </select>
+                            <button style="margin: 5px" onclick=self.link.callback(|_| Msg::cancel)>{ "Cancel" }</button>
                         </p>
                     </div>
                     }
@@ -506,10 +638,20 @@ impl Component for TootOttoComputerModel {
                             <option selected=false disabled=false value="9_7">{"9 x 7"}</option>
                             <option selected=false disabled=false value="10_7">{"10 x 7"}</option>
                         </select>
+                        <select id="side_dropdown" style="margin: 5px">
+                            <option selected=true disabled=false value="p1">{"Play TOOT"}</option>
+                            <option selected=false disabled=false value="p2">{"Play OTTO"}</option>
+                        </select>
+                        <select id="first_player_dropdown" style="margin: 5px">
+                            <option selected=true disabled=false value="human">{"You Move First"}</option>
+                            <option selected=false disabled=false value="computer">{"Computer Moves First"}</option>
+                            <option selected=false disabled=false value="random">{"Random First Player"}</option>
+                        </select>
                         <select id="difficulty_dropdown" style="margin: 5px">
                             <option selected=true disabled=false value="easy">{"Easy"}</option>
                             <option selected=false disabled=false value="medium">{"Medium"}</option>
                             <option selected=false disabled=false value="hard">{"Hard"}</option>
+                            <option selected=false disabled=false value="expert">{"Expert"}</option>
                         </select>
                         <button style="margin: 5px" onclick=self.link.callback(|_| Msg::startGame)>{ "Start Game" }</button>
                     </div>
@@ -524,12 +666,19 @@ impl Component for TootOttoComputerModel {
 }
 
 impl TootOttoComputerModel {
+    // Send the moves along with the result, the backend plays them again to check it
     fn post_win(&mut self) {
+        let saved = SavedGame::from_toot_otto(&self.game.borrow());
         let json_sg = json!({
             "gameType": "TOOT-OTTO with Computer",
-            "Player1Name": self.player1Name,
-            "Player2Name": self.player2Name,
+            "Player1Name": saved.p1,
+            "Player2Name": saved.p2,
             "WinnerName": self.game.clone().borrow_mut().winner.clone(),
+            "rows": saved.rows,
+            "cols": saved.cols,
+            "moves": saved.moves,
+            "first_player": saved.first_player,
+            "variant": saved.variant,
         });
         let callback = self.link.callback(|response: Response<Result<String, _>>| {
             if response.status().is_success() {
@@ -19,7 +19,11 @@ use stdweb::web::{document, window, CanvasRenderingContext2d, FillRule};
 
 use stdweb::web::event::{ClickEvent, ResizeEvent};
 
-use connect_four_cli::toot_otto::{ChipType, DummyGrid, Game, Grid, State};
+use connect_four_cli::board::Player;
+use connect_four_cli::save::SavedGame;
+use connect_four_cli::toot_otto::{
+    ChipType, Evaluator, Game, Grid, Inventory, SpelledWord, State, Variant,
+};
 use std::cell::RefCell;
 use std::f64::consts::PI;
 use std::rc::Rc;
@@ -74,22 +78,18 @@ fn draw_board(game: Rc<RefCell<Game>>) {
     context.restore();
 }
 
-fn draw(grid: &Grid, dummy_grid: &DummyGrid, num_rows: usize, num_cols: usize) {
+fn draw(grid: &Grid, num_rows: usize, num_cols: usize) {
     for y in 0..num_rows {
         for x in 0..num_cols {
-            let mut text = "";
+            let mut text = "".to_string();
             let mut fg_color = "transparent";
 
-            if grid.get(y, x) >= 1 {
-                fg_color = "#ff4136";
-            } else if grid.get(y, x) <= -1 {
-                fg_color = "#ffff00";
-            }
-
-            if dummy_grid.get(y, x) == 1 {
-                text = "T";
-            } else if dummy_grid.get(y, x) == -1 {
-                text = "O";
+            if let Some((player, chip_type)) = grid.get(y, x) {
+                fg_color = match player {
+                    Player::One => "#ff4136",
+                    Player::Two => "#ffff00",
+                };
+                text = chip_type.to_string();
             }
 
             draw_circle(
@@ -98,7 +98,7 @@ fn draw(grid: &Grid, dummy_grid: &DummyGrid, num_rows: usize, num_cols: usize) {
                 25.0,
                 fg_color.to_string(),
                 "black".to_string(),
-                text.to_string(),
+                text,
             );
         }
     }
@@ -124,7 +124,7 @@ fn draw_circle(x: f64, y: f64, r: f64, fill: String, stroke: String, text: Strin
     context.fill_text(text.as_str(), x - 8.5, y + 8.0, None);
 }
 
-fn print_win(winner: String) {
+fn print_win(winner: String, words: &[SpelledWord]) {
     let canvas: CanvasElement = document()
         .query_selector("#gameboard_c4_human")
         .unwrap()
@@ -133,6 +133,16 @@ fn print_win(winner: String) {
         .unwrap();
     let context: CanvasRenderingContext2d = canvas.get_context().unwrap();
     let mut msg = "".to_string();
+
+    // Every word the last chip spelled, both of them when the game is drawn that way
+    if !words.is_empty() {
+        let spelled = words
+            .iter()
+            .map(|w| w.word.to_string())
+            .collect::<Vec<String>>();
+        msg.push_str(spelled.join(" and ").as_str());
+        msg.push_str(" - ");
+    }
     if winner == "Draw" {
         msg.push_str("It's a draw");
     } else {
@@ -153,7 +163,6 @@ fn animate(
     to_row: i64,
     cur_pos: i64,
     grid: Grid,
-    dummy_grid: DummyGrid,
     game: Rc<RefCell<Game>>,
     text: String,
 ) {
@@ -169,7 +178,6 @@ fn animate(
         clear_canvas();
         draw(
             &grid.clone(),
-            &dummy_grid.clone(),
             game.borrow().grid.num_rows,
             game.borrow().grid.num_cols,
         );
@@ -190,7 +198,6 @@ fn animate(
                 to_row,
                 cur_pos + 25.0 as i64,
                 grid.clone(),
-                dummy_grid.clone(),
                 game,
                 my_text,
             )
@@ -206,7 +213,8 @@ fn check_for_win(game: Rc<RefCell<Game>>) {
     match state {
         State::Done => {
             // draw finished
-            print_win(game.clone().borrow_mut().winner.clone());
+            let winner = game.borrow().winner.clone();
+            print_win(winner, &game.borrow().words);
         }
         _ => {}
     }
@@ -224,6 +232,26 @@ fn clear_canvas() {
 }
 
 impl TootOttoHumanModel {
+    fn tiles_left(&self) -> String {
+        let game = self.game.borrow();
+        format!(
+            "Tiles left: {} - {} and {} - {}",
+            self.player1Name, game.p1_chips, self.player2Name, game.p2_chips
+        )
+    }
+
+    // Options of the letter picker, one per letter of the game
+    fn letter_options(&self) -> Html {
+        let letters = self.game.borrow().variant.letters.clone();
+        html! {
+            <>
+                { for letters.iter().map(|letter| html! {
+                    <option value=letter.to_string()>{letter.to_string()}</option>
+                }) }
+            </>
+        }
+    }
+
     fn is_started(&self) -> bool {
         let state = self.game.clone().borrow().state.clone();
         return match state {
@@ -238,9 +266,9 @@ impl Component for TootOttoHumanModel {
     type Properties = Props;
 
     fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let variant = Variant::toot_otto();
         let game = Rc::new(RefCell::new(Game {
             grid: Grid::new(6, 7),
-            dummy_grid: DummyGrid::new(6, 7),
             p1: "".to_string(),
             p2: "".to_string(),
             with_ai: false,
@@ -248,6 +276,14 @@ impl Component for TootOttoHumanModel {
             winner: "".to_string(),
             p_move: 0,
             max_ai_depth: 4,
+            p1_chips: Inventory::new(&variant.letters, variant.chips_per_letter),
+            p2_chips: Inventory::new(&variant.letters, variant.chips_per_letter),
+            evaluator: Evaluator::Patterns,
+            variant,
+            words: Vec::new(),
+            first_player: Player::One,
+            ai_player: Player::Two,
+            history: Vec::new(),
         }));
         TootOttoHumanModel {
             link,
@@ -296,6 +332,7 @@ impl Component for TootOttoHumanModel {
                     self.player1Name.clone(),
                     self.player2Name.clone(),
                     4,
+                    Variant::toot_otto(),
                 ));
                 draw_board(self.game.clone());
                 self.game.borrow_mut().start_game();
@@ -314,7 +351,6 @@ impl Component for TootOttoHumanModel {
                             && col.unwrap() < self.game.borrow().grid.num_cols
                         {
                             let prev_grid = self.game.borrow().grid.clone();
-                            let prev_dummy_grid = self.game.borrow().dummy_grid.clone();
 
                             // Chip type
                             let sel_box: SelectElement = document()
@@ -324,24 +360,23 @@ impl Component for TootOttoHumanModel {
                                 .try_into()
                                 .unwrap();
 
-                            let chip_type = match sel_box.value().unwrap().as_str() {
-                                "chip_t" => ChipType::T,
-                                "chip_o" => ChipType::O,
-                                _ => panic!(),
+                            let chip_type = match sel_box.value().unwrap().chars().next() {
+                                Some(letter) => ChipType(letter),
+                                None => panic!(),
                             };
 
+                            if self.game.borrow().current_chips().remaining(chip_type) == 0 {
+                                js! {alert("No tiles of that letter left, play another letter.")}
+                                return false;
+                            }
+
                             let insert_result = self
                                 .game
                                 .borrow_mut()
                                 .make_move(chip_type, col.unwrap() as usize);
 
                             if insert_result.is_ok() {
-                                let mut text = "";
-                                if insert_result.unwrap().2 == 1 {
-                                    text = "T";
-                                } else if insert_result.unwrap().2 == -1 {
-                                    text = "O";
-                                }
+                                let text = insert_result.unwrap().2;
 
                                 animate(
                                     col.unwrap() as i64,
@@ -349,7 +384,6 @@ impl Component for TootOttoHumanModel {
                                     insert_result.unwrap().0 as i64,
                                     0,
                                     prev_grid,
-                                    prev_dummy_grid,
                                     self.game.clone(),
                                     text.to_string(),
                                 );
@@ -434,11 +468,11 @@ impl Component for TootOttoHumanModel {
                                 <div>
                                     <h4>{"New Game: "}{&self.player1Name}{" VS "}{&self.player2Name}</h4>
                                     <small>{"Disc Colors: "}{&self.player1Name}{" - Red and "}{&self.player2Name}{" - Yellow"}</small>
+                                    <p>{self.tiles_left()}</p>
                                     <p>
                                         {"Select a Disc Type: "}
                                         <select id="chip_type_dropdown" style="margin: 5px">
-                                            <option selected=true disabled=false value="chip_t">{"T"}</option>
-                                            <option selected=false disabled=false value="chip_o">{"O"}</option>
+                                            {self.letter_options()}
                                         </select>
                                     </p>
                                 </div>
@@ -469,12 +503,19 @@ impl Component for TootOttoHumanModel {
 }
 
 impl TootOttoHumanModel {
+    // Send the moves along with the result, the backend plays them again to check it
     fn post_win(&mut self) {
+        let saved = SavedGame::from_toot_otto(&self.game.borrow());
         let json_sg = json!({
             "gameType": "TOOT-OTTO with Human",
-            "Player1Name": self.player1Name,
-            "Player2Name": self.player2Name,
+            "Player1Name": saved.p1,
+            "Player2Name": saved.p2,
             "WinnerName": self.game.clone().borrow_mut().winner.clone(),
+            "rows": saved.rows,
+            "cols": saved.cols,
+            "moves": saved.moves,
+            "first_player": saved.first_player,
+            "variant": saved.variant,
         });
         let callback = self.link.callback(|response: Response<Result<String, _>>| {
             if response.status().is_success() {